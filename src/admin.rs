@@ -0,0 +1,233 @@
+use chore_planner::{
+    bot::ReplyMsg,
+    command::{execute_command, MainCommand},
+    config::{Config, HouseholdSettings},
+    db::Db,
+    locale::Locale,
+    week::Week,
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::Local;
+use clap::{ArgAction, Parser, Subcommand};
+use std::path::PathBuf;
+
+/// administrate the chore_planner's database from the shell
+///
+/// all chat commands are available, use `chore_planner_admin help` for an overview
+///
+/// the configuration is read like the chore_planner's from the config file at
+/// CHORE_PLANNER_CONFIG and the environment variables, the options below override it
+#[derive(Parser)]
+#[command(version, author)]
+struct AdminCli {
+    /// the household to administrate, only needed when there are multiple
+    #[arg(long)]
+    household: Option<String>,
+
+    /// the path of the SQLite database
+    #[arg(long)]
+    db_path: Option<String>,
+
+    /// how many weeks to plan into the future
+    #[arg(long)]
+    weeks_to_plan: Option<u32>,
+
+    /// probability distribution parameter in [0, 1]
+    ///
+    /// a gamma stored in the database takes precedence
+    #[arg(long)]
+    gamma: Option<f64>,
+
+    /// try to exclude busy tenants from chore assignments
+    #[arg(long, action = ArgAction::Set)]
+    try_exclude_busy_tenants: Option<bool>,
+
+    /// the language of the messages
+    ///
+    /// a locale stored in the database takes precedence
+    #[arg(long, value_enum)]
+    locale: Option<Locale>,
+
+    /// use the last week as current week when the database doesn't know the current week yet
+    #[arg(long, action = ArgAction::Set)]
+    fallback_to_last_week: Option<bool>,
+
+    /// the name of the bot used in the printed replan commands
+    #[arg(long, default_value = "@chore_planner_bot")]
+    bot_name: String,
+
+    #[command(subcommand)]
+    command: AdminCommand,
+}
+
+#[derive(Subcommand)]
+enum AdminCommand {
+    #[command(flatten)]
+    Chat(MainCommand),
+    /// check the database's integrity
+    ///
+    /// this is also performed every time the database is opened
+    Check,
+    /// set the current week without performing the weekly action
+    SetWeek {
+        /// the new current week
+        #[arg(long, value_parser = 1..54)]
+        // this needs to be an i64 because of value_parser
+        week: i64,
+
+        /// the year of the new current week
+        #[arg(long)]
+        year: i32,
+    },
+    /// store a new gamma in the database
+    ///
+    /// this overrides CHORE_PLANNER_GAMMA from the next restart of the chore_planner on
+    SetGamma {
+        /// probability distribution parameter in [0, 1]
+        #[arg(long)]
+        gamma: f64,
+    },
+    /// list all global variables stored in the KeyValue table
+    KeyValue,
+    /// import the history of a flat from CSV files, i.e., from a paper chore chart
+    ///
+    /// the files use the format of the export, weeks are written like 33/2024
+    /// nothing is imported when any row is invalid
+    Import {
+        /// CSV with the columns tenant, room, move_in_week and optionally move_out_week and chat_tag
+        ///
+        /// missing tenants and rooms are created
+        #[arg(long)]
+        lives_in: Option<PathBuf>,
+
+        /// CSV with the columns week, chore and worker of weeks before the current week
+        ///
+        /// the chores need to exist already
+        #[arg(long)]
+        chore_logs: Option<PathBuf>,
+    },
+}
+
+/// Get the settings of the household chosen with --household from the configuration, overridden
+/// by the options.
+fn household_settings(cli: &AdminCli) -> Result<HouseholdSettings> {
+    let mut households = Config::load_unvalidated()?.household_settings();
+    let names = households
+        .iter()
+        .map(|household| household.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut settings = match &cli.household {
+        Some(name) => match households
+            .iter()
+            .position(|household| household.name == *name)
+        {
+            Some(i) => households.remove(i),
+            None => bail!(
+                "the household {} doesn't exist, use one of: {}",
+                name,
+                names
+            ),
+        },
+        None if households.len() == 1 => households.remove(0),
+        None => bail!("choose one of the households with --household: {}", names),
+    };
+
+    if let Some(db_path) = &cli.db_path {
+        settings.db_path = db_path.clone();
+    }
+    settings.weeks_to_plan = cli.weeks_to_plan.unwrap_or(settings.weeks_to_plan);
+    settings.gamma = cli.gamma.unwrap_or(settings.gamma);
+    settings.try_exclude_busy_tenants = cli
+        .try_exclude_busy_tenants
+        .unwrap_or(settings.try_exclude_busy_tenants);
+    settings.locale = cli.locale.unwrap_or(settings.locale);
+    settings.fallback_to_last_week = cli
+        .fallback_to_last_week
+        .unwrap_or(settings.fallback_to_last_week);
+    if settings.db_path.is_empty() {
+        bail!("the database path is missing, set it in the configuration or with --db-path");
+    }
+    Ok(settings)
+}
+
+/// Open the database and perform the requested command.
+async fn run(cli: AdminCli) -> Result<ReplyMsg> {
+    let settings = household_settings(&cli)?;
+    let mut fallback_week = Week::from(Local::now().date_naive());
+    if settings.fallback_to_last_week {
+        fallback_week = Week::from_db(fallback_week.db_week() - 1);
+    }
+    let mut db = Db::new(
+        &format!("sqlite://{}", settings.db_path),
+        fallback_week,
+        settings.weeks_to_plan,
+        settings.gamma,
+        settings.try_exclude_busy_tenants,
+        settings.locale,
+        rand::random::<u64>(),
+        false,
+    )
+    .await?;
+
+    match cli.command {
+        AdminCommand::Chat(command) => execute_command(&mut db, &command, &cli.bot_name).await,
+        AdminCommand::Check => {
+            db.integrity_check().await?;
+            Ok(ReplyMsg::from_text("the database is fine"))
+        }
+        AdminCommand::SetWeek { week, year } => {
+            db.set_week_internal(Week::new(week as u32, year)?).await;
+            Ok(ReplyMsg::from_text(&format!(
+                "the current week is: {}",
+                db.get_week_internal().await
+            )))
+        }
+        AdminCommand::SetGamma { gamma } => db.set_gamma(gamma).await,
+        AdminCommand::KeyValue => Ok(ReplyMsg::from_mono(
+            &db.get_key_values()
+                .await?
+                .into_iter()
+                .map(|(key, value)| format!("{}: {}", key, value.unwrap_or_default()))
+                .collect::<Vec<String>>()
+                .join("\n"),
+        )),
+        AdminCommand::Import {
+            lives_in,
+            chore_logs,
+        } => {
+            let read = |path: Option<PathBuf>| -> Result<Option<String>> {
+                path.map(|p| {
+                    std::fs::read_to_string(&p)
+                        .with_context(|| format!("failed to read {}", p.display()))
+                })
+                .transpose()
+            };
+            db.import_history(read(lives_in)?.as_deref(), read(chore_logs)?.as_deref())
+                .await
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = AdminCli::parse();
+    match run(cli).await {
+        Ok(msg) => {
+            println!("{}", msg.mono_msg());
+            // Write attachments like exports into the working directory.
+            for attachment in msg.attachments {
+                if let Err(e) = std::fs::write(&attachment.filename, attachment.content) {
+                    eprintln!("error: failed to write {}: {:#}", attachment.filename, e);
+                    std::process::exit(1);
+                }
+                println!("wrote {}", attachment.filename);
+            }
+        }
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -1,15 +1,204 @@
 use anyhow::{bail, Error, Result};
+use serde::Serialize;
 use std::collections::HashSet;
 use std::ops::{Add, AddAssign};
 use std::str::FromStr;
+use tabled::{
+    builder::Builder,
+    settings::{object::Segment, Alignment, Settings},
+    Tabled,
+};
+
+/// a piece of text within a paragraph
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(tag = "type", content = "text", rename_all = "snake_case")]
+pub enum Inline {
+    Text(String),
+    Bold(String),
+    /// a short piece of code like a command
+    Code(String),
+    /// a tenant's name that notifies them through their chat tag
+    Mention {
+        name: String,
+        tag: String,
+    },
+}
+
+impl Inline {
+    /// Mention a tenant when they have a chat tag or just write their name.
+    pub fn mention(name: &str, tag: Option<&str>) -> Self {
+        match tag {
+            Some(tag) => Inline::Mention {
+                name: name.to_string(),
+                tag: tag.to_string(),
+            },
+            None => Inline::Text(name.to_string()),
+        }
+    }
+
+    /// the text without any formatting
+    pub fn text(&self) -> &str {
+        match self {
+            Inline::Text(text) | Inline::Bold(text) | Inline::Code(text) => text,
+            Inline::Mention { name, .. } => name,
+        }
+    }
+}
+
+/// a part of a message
+///
+/// Each bot renders blocks in its native format.
+/// The monospace rendering from Block::mono can always be used as a fallback.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Block {
+    /// a heading with a level starting at 1
+    Heading {
+        level: usize,
+        text: String,
+    },
+    Paragraph {
+        inlines: Vec<Inline>,
+    },
+    /// a table with a header row
+    Table {
+        header: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    /// preformatted text that needs to be shown in monospace font
+    Code {
+        text: String,
+    },
+    /// an empty line
+    Blank,
+}
+
+impl Block {
+    pub fn heading(level: usize, text: &str) -> Self {
+        Block::Heading {
+            level,
+            text: text.to_string(),
+        }
+    }
+
+    /// Create a paragraph of unformatted text.
+    pub fn text(text: &str) -> Self {
+        Block::Paragraph {
+            inlines: vec![Inline::Text(text.to_string())],
+        }
+    }
+
+    pub fn code(text: &str) -> Self {
+        Block::Code {
+            text: text.to_string(),
+        }
+    }
+
+    /// Create a table from some rows with the header defined by T.
+    pub fn table<T: Tabled>(rows: impl IntoIterator<Item = T>) -> Self {
+        Block::Table {
+            header: T::headers().into_iter().map(|h| h.to_string()).collect(),
+            rows: rows
+                .into_iter()
+                .map(|r| r.fields().into_iter().map(|f| f.to_string()).collect())
+                .collect(),
+        }
+    }
+
+    /// Create a table from some rows with a custom header, i.e., a translated one.
+    pub fn table_with_header<T: Tabled>(
+        header: &[&str],
+        rows: impl IntoIterator<Item = T>,
+    ) -> Self {
+        debug_assert_eq!(header.len(), T::LENGTH);
+        match Block::table(rows) {
+            Block::Table { rows, .. } => Block::Table {
+                header: header.iter().map(|h| h.to_string()).collect(),
+                rows,
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    /// Render the block as monospace text.
+    pub fn mono(&self) -> String {
+        match self {
+            Block::Heading { level, text } => format!("{} {}", "#".repeat(*level), text),
+            Block::Paragraph { inlines } => inlines.iter().map(Inline::text).collect(),
+            Block::Table { header, rows } => {
+                let mut builder = Builder::default();
+                builder.push_record(header.clone());
+                for row in rows {
+                    builder.push_record(row.clone());
+                }
+                builder
+                    .build()
+                    .modify(
+                        Segment::all(),
+                        Settings::new(Alignment::center(), Alignment::center()),
+                    )
+                    .to_string()
+            }
+            Block::Code { text } => text.clone(),
+            Block::Blank => String::new(),
+        }
+    }
+}
+
+/// a chat command a bot can offer as button below a message
+///
+/// Bots without buttons ignore actions, the command can always be typed instead.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Action {
+    pub label: String,
+    /// the chat command including the bot's name
+    pub command: String,
+}
+
+impl Action {
+    pub fn new(label: &str, command: &str) -> Self {
+        Action {
+            label: label.to_string(),
+            command: command.to_string(),
+        }
+    }
+}
+
+/// Messages with a longer monospace rendering are sent as a file by bots that can send files.
+///
+/// This is about three Telegram messages.
+pub const FILE_THRESHOLD: usize = 12000;
+
+/// a text file sent along with a message
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Attachment {
+    pub filename: String,
+    pub content: String,
+    /// the text shown with the file
+    pub caption: Option<String>,
+}
+
+impl Attachment {
+    pub fn new(filename: &str, content: String) -> Self {
+        Attachment {
+            filename: filename.to_string(),
+            content,
+            caption: None,
+        }
+    }
+}
 
 /// a message the chat bot should write
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct ReplyMsg {
-    /// the message to send in monospace font
-    pub mono_msg: String,
+    /// the structured content of the message
+    pub blocks: Vec<Block>,
     /// the user chat tags to send
     pub tags: HashSet<String>,
+    /// the actions to offer below the message
+    pub actions: Vec<Action>,
+    /// the files to send after the message
+    pub attachments: Vec<Attachment>,
 }
 
 impl Add for ReplyMsg {
@@ -23,37 +212,180 @@ impl Add for ReplyMsg {
 
 impl AddAssign for ReplyMsg {
     fn add_assign(&mut self, rhs: ReplyMsg) {
-        if !self.mono_msg.is_empty() && !rhs.mono_msg.is_empty() {
-            self.mono_msg += "\n\n\n\n";
+        // separate the messages by three empty lines
+        if !self.is_empty() && !rhs.is_empty() {
+            self.blocks
+                .extend([Block::Blank, Block::Blank, Block::Blank]);
         }
-        self.mono_msg += &rhs.mono_msg;
+        self.blocks.extend(rhs.blocks);
         self.tags.extend(rhs.tags);
+        for action in rhs.actions {
+            self.push_action(action);
+        }
+        self.attachments.extend(rhs.attachments);
+    }
+}
+
+impl Default for ReplyMsg {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl ReplyMsg {
     pub fn new() -> Self {
         ReplyMsg {
-            mono_msg: String::new(),
+            blocks: Vec::new(),
             tags: HashSet::new(),
+            actions: Vec::new(),
+            attachments: Vec::new(),
         }
     }
+
+    /// Create a message that is shown in monospace font.
     pub fn from_mono(mono_msg: &str) -> Self {
+        ReplyMsg::from_blocks(match mono_msg.is_empty() {
+            true => vec![],
+            false => vec![Block::code(mono_msg)],
+        })
+    }
+
+    /// Create a message of unformatted text.
+    pub fn from_text(text: &str) -> Self {
+        ReplyMsg::from_blocks(vec![Block::text(text)])
+    }
+
+    pub fn from_blocks(blocks: Vec<Block>) -> Self {
         ReplyMsg {
-            mono_msg: mono_msg.to_string(),
+            blocks,
             tags: HashSet::new(),
+            actions: Vec::new(),
+            attachments: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty() && self.attachments.is_empty()
+    }
+
+    /// Offer an action unless the same action is already offered or it has no command.
+    pub fn push_action(&mut self, action: Action) {
+        if !action.command.is_empty() && !self.actions.contains(&action) {
+            self.actions.push(action);
+        }
+    }
+
+    /// Get the tags that aren't mentioned inline, those need to be sent separately.
+    pub fn unmentioned_tags(&self) -> Vec<String> {
+        let mentioned: HashSet<&str> = self
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                Block::Paragraph { inlines } => Some(inlines),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|inline| match inline {
+                Inline::Mention { tag, .. } => Some(tag.as_str()),
+                _ => None,
+            })
+            .collect();
+        let mut tags: Vec<String> = self
+            .tags
+            .iter()
+            .filter(|tag| !mentioned.contains(tag.as_str()))
+            .cloned()
+            .collect();
+        tags.sort();
+        tags
+    }
+
+    /// Move the blocks of a message longer than limit into a text file.
+    ///
+    /// This avoids splitting a long report into many messages. The first heading becomes the
+    /// file's caption. Tags and actions are kept, the actions end up below a short message
+    /// repeating the heading, or the filename when there is no heading.
+    pub fn into_file_when_longer_than(mut self, limit: usize, filename: &str) -> Self {
+        let mono_msg = self.mono_msg();
+        if mono_msg.len() <= limit {
+            return self;
         }
+        let heading = self
+            .blocks
+            .iter()
+            .find(|block| matches!(block, Block::Heading { .. }))
+            .cloned();
+        let mut attachment = Attachment::new(filename, mono_msg);
+        attachment.caption = match &heading {
+            Some(Block::Heading { text, .. }) => Some(text.clone()),
+            _ => None,
+        };
+        self.blocks = match self.actions.is_empty() {
+            true => vec![],
+            false => vec![heading.unwrap_or_else(|| Block::text(filename))],
+        };
+        self.attachments.insert(0, attachment);
+        self
+    }
+
+    /// Render the message as monospace text.
+    ///
+    /// This is the fallback for bots that can't render the blocks natively.
+    pub fn mono_msg(&self) -> String {
+        self.blocks
+            .iter()
+            .map(Block::mono)
+            .collect::<Vec<String>>()
+            .join("\n")
     }
 }
 
+/// something the bot received that the chore_planner needs to react to
+#[derive(Debug, PartialEq)]
+pub enum BotUpdate {
+    /// a message intended for the bot
+    /// (i.e. a message that starts with the bots name)
+    Msg(String),
+    /// a request to perform the weekly action, same as a SIGHUP
+    WeeklyAction,
+    /// a vote in a non-anonymous poll
+    PollAnswer {
+        /// the SentPoll::vote_key of the poll
+        vote_key: String,
+        /// all chat tags that could refer to the voter, the first one identifies them
+        voter_tags: Vec<String>,
+        /// the index of the chosen option or None when the vote was retracted
+        option: Option<usize>,
+    },
+}
+
 /// a bot that supports receiving and sending messages
+// The bots are only used within the chore_planner, so Send bounds on the futures aren't needed.
+#[allow(async_fn_in_trait)]
 pub trait MessagableBot {
-    /// Wait for the next message intended for the bot
-    /// (i.e. a message that starts with the bots name).
+    /// Wait for the next update intended for the bot.
     /// This can be used in a loop.
-    async fn next_msg(&mut self) -> Option<String>;
+    ///
+    /// The chat the update came from becomes the selected chat.
+    async fn next_update(&mut self) -> Option<BotUpdate>;
+    /// Get the chat messages and polls are sent to.
+    ///
+    /// Each household has its own chat. The chats are numbered in the order the bot got them.
+    fn selected_chat(&self) -> usize;
+    /// Send all following messages and polls to another chat.
+    fn select_chat(&mut self, chat: usize);
     /// Send a message or an error.
+    ///
+    /// The message's attachments are sent with send_file afterwards.
     async fn send_msg(&mut self, msg: Result<ReplyMsg>);
+    /// Whether replies longer than FILE_THRESHOLD should be sent as a file with send_file.
+    fn sends_long_msgs_as_file(&self) -> bool {
+        false
+    }
+    /// Send a file with an optional caption to the selected chat.
+    async fn send_file(&mut self, name: &str, _bytes: &[u8], _caption: Option<&str>) -> Result<()> {
+        bail!("this bot can't send the file {}", name)
+    }
     /// Get the name of the bot i.e., the prefix of all accepted received messages.
     fn get_name(&self) -> &str;
 
@@ -63,21 +395,35 @@ pub trait MessagableBot {
     async fn shutdown(&mut self);
 }
 
+/// a poll created by a bot
+#[derive(Debug, PartialEq)]
+pub struct SentPoll {
+    /// the identifier to stop the poll with
+    pub id: i64,
+    /// the identifier BotUpdate::PollAnswer refers to
+    ///
+    /// None when the poll is anonymous and the bot doesn't report single votes.
+    pub vote_key: Option<String>,
+}
+
 /// a bot that supports creating polls
+#[allow(async_fn_in_trait)]
 pub trait PollableBot {
     /// Create a new poll with a question and list of options.
-    /// Return the identifier of this poll.
-    async fn send_poll(&mut self, question: &str, options: Vec<String>) -> Result<i64>;
+    async fn send_poll(&mut self, question: &str, options: Vec<String>) -> Result<SentPoll>;
     /// Stop the specified poll and return a list of (option, count_chosen) tuples.
     async fn stop_poll(&mut self, poll_id: i64) -> Result<Vec<(String, u32)>>;
 }
 
-/// the types of protocols the chore_planner supports in production
+/// the types of protocols the chore_planner supports
 ///
 /// The TestBot is not made for production and thus not listed.
+/// The ConsoleBot isn't made for production either but it's useful for trying the chore_planner
+/// locally.
 pub enum BotProtocol {
     Telegram,
     Signal,
+    Console,
 }
 
 impl FromStr for BotProtocol {
@@ -88,6 +434,7 @@ impl FromStr for BotProtocol {
         match lowercase {
             "telegram" => Ok(BotProtocol::Telegram),
             "signal" => Ok(BotProtocol::Signal),
+            "console" => Ok(BotProtocol::Console),
             _ => bail!("chat protocol '{lowercase}' is not supported"),
         }
     }
@@ -1,12 +1,19 @@
 use crate::{
-    bot::{MessagableBot, PollableBot, ReplyMsg},
-    db::Db,
+    bot::{Action, MessagableBot, PollableBot, ReplyMsg, FILE_THRESHOLD},
+    db::{
+        chore_commands::{ChoreRatingRule, PoorRatingAction},
+        settings_commands::{ScoreDecay, StartScorePolicy},
+        stats_commands::StatsSort,
+        Db,
+    },
+    locale::Locale,
+    template::TemplateKind,
     week::Week,
 };
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use chrono::Local;
-use clap::{command, Parser, Subcommand};
+use clap::{ArgAction, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 // TODO: author unused
@@ -19,7 +26,7 @@ struct Cli {
 
 #[derive(Subcommand)]
 #[command(arg_required_else_help = true)]
-enum MainCommand {
+pub enum MainCommand {
     /// change the planned worker
     ///
     /// the referenced tenant won't be considered for any chores in that week
@@ -38,6 +45,28 @@ enum MainCommand {
         #[arg(long, alias = "Year")]
         year: i32,
     },
+    /// swap all chores of a week with another tenant
+    ///
+    /// leave out the other tenant to get offered everyone to swap with
+    #[command(alias = "Swap")]
+    Swap {
+        /// the name of the tenant who wants to swap
+        #[arg(long, alias = "Tenant")]
+        tenant: String,
+
+        /// the name of the tenant to swap with
+        #[arg(long, alias = "With")]
+        with: Option<String>,
+
+        /// the affected week
+        #[arg(long, alias = "Week", value_parser = 1..54)]
+        // this needs to be an i64 because of value_parser
+        week: i64,
+
+        /// the year of the affected week
+        #[arg(long, alias = "Year")]
+        year: i32,
+    },
     /// create a report
     #[command(alias = "Report")]
     Report {
@@ -50,6 +79,51 @@ enum MainCommand {
         #[arg(long, alias = "Year")]
         year: i32,
     },
+    /// show per tenant and per chore statistics of some weeks
+    #[command(alias = "Stats")]
+    Stats {
+        /// first week to include in the statistics
+        #[arg(long, alias = "Week", value_parser = 1..54)]
+        // this needs to be an i64 because of value_parser
+        week: i64,
+
+        /// year of the first week to include in the statistics
+        #[arg(long, alias = "Year")]
+        year: i32,
+
+        /// last week to include, leave out to include everything until last week
+        #[arg(long, alias = "EndWeek", value_parser = 1..54, requires = "end_year")]
+        end_week: Option<i64>,
+
+        /// year of the last week to include
+        #[arg(long, alias = "EndYear", requires = "end_week")]
+        end_year: Option<i32>,
+
+        /// sort by name or descending by chores done, rating or unwilling weeks
+        #[arg(long, alias = "Sort", value_enum, default_value_t)]
+        sort: StatsSort,
+    },
+    /// preview how fair the plan turns out over the next weeks
+    ///
+    /// this doesn't change anything, the simulation runs on a copy of the database
+    #[command(alias = "Simulate")]
+    Simulate {
+        /// the amount of weeks to simulate
+        #[arg(long, alias = "Weeks", value_parser = clap::value_parser!(u32).range(1..=520))]
+        weeks: u32,
+
+        /// the gamma to simulate with, defaults to the current gamma
+        #[arg(long, alias = "Gamma")]
+        gamma: Option<f64>,
+
+        /// whether to try excluding busy tenants, defaults to the current setting
+        #[arg(long, alias = "TryExcludeBusyTenants")]
+        try_exclude_busy_tenants: Option<bool>,
+
+        /// the seed of the random number generator, the same seed creates the same simulation
+        #[arg(long, alias = "Seed", default_value_t = 0)]
+        seed: u64,
+    },
     /// administrate tenants
     #[command(alias = "Tenant")]
     Tenant {
@@ -68,11 +142,55 @@ enum MainCommand {
         #[command(subcommand)]
         command: Option<ExemptionCommand>,
     },
+    /// set which chores a tenant likes or dislikes
+    ///
+    /// tenants are chosen more often for chores they like and less often for chores they
+    /// dislike, the score still ensures everyone does their fair share in the long run
+    #[command(alias = "Preference")]
+    Preference {
+        #[command(subcommand)]
+        command: Option<PreferenceCommand>,
+    },
+    /// administrate constraints on how often tenants are chosen
+    #[command(alias = "Constraint")]
+    Constraint {
+        #[command(subcommand)]
+        command: Option<ConstraintCommand>,
+    },
+    /// view or change how the chore_planner plans
+    ///
+    /// changed settings are stored in the database and override the config
+    #[command(alias = "Settings")]
+    Settings {
+        #[command(subcommand)]
+        command: Option<SettingsCommand>,
+    },
+    /// credit tenants for extra work
+    #[command(alias = "Score")]
+    Score {
+        #[command(subcommand)]
+        command: Option<ScoreCommand>,
+    },
+    /// export the entire history for analysing it in a spreadsheet
+    ///
+    /// this sends the chore logs, ratings, move ins, exemptions and scores as files
+    #[command(alias = "Export")]
+    Export {
+        /// csv creates one file per table, json a single file
+        #[arg(long, alias = "Format", value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+    },
+    /// customise the messages the bot sends
+    #[command(alias = "Template")]
+    Template {
+        #[command(subcommand)]
+        command: Option<TemplateCommand>,
+    },
 }
 
 #[derive(Subcommand)]
 #[command(arg_required_else_help = true)]
-enum TenantCommand {
+pub enum TenantCommand {
     /// list current tenants
     List,
     /// let a tenant move in
@@ -88,6 +206,8 @@ enum TenantCommand {
         /// if the tenant has a unique chat tag (telegram doesn't enforce this) it can be used for
         /// tagging
         ///
+        /// telegram takes a numeric user id or an @username, signal a phone number or a UUID
+        ///
         /// when the tenant already exists and you set this, the tag will be updated
         #[arg(long, alias = "Tag")]
         tag: Option<String>,
@@ -116,7 +236,7 @@ enum TenantCommand {
 
 #[derive(Subcommand)]
 #[command(arg_required_else_help = true)]
-enum ChoreCommand {
+pub enum ChoreCommand {
     /// list all active chores
     List,
     /// create a new chore for all to enjoy
@@ -143,11 +263,49 @@ enum ChoreCommand {
         #[arg(long, alias = "Name")]
         name: String,
     },
+    /// mark a chore as done and open its rating poll before the week is over
+    Done {
+        /// the name of the chore
+        #[arg(long, alias = "Chore")]
+        chore: String,
+
+        /// the week the chore was done in
+        #[arg(long, alias = "Week", value_parser = 1..54)]
+        // this needs to be an i64 because of value_parser
+        week: i64,
+
+        /// the year of the week
+        #[arg(long, alias = "Year")]
+        year: i32,
+    },
+    /// list the rules for poorly rated chores
+    RatingRules,
+    /// make the ratings of a chore matter
+    ///
+    /// chores rated below the minimum either count only partially in the scores or are redone by
+    /// the same tenant, only chores from this week on are affected
+    RatingRule {
+        /// the name of the chore
+        #[arg(long, alias = "Chore")]
+        chore: String,
+
+        /// the minimum average rating, leave out to remove the rule
+        #[arg(long, alias = "MinRating", requires = "action")]
+        min_rating: Option<f64>,
+
+        /// partial counts the chore only partially, redo assigns it to the same tenant again
+        #[arg(long, alias = "Action", value_enum, requires = "min_rating")]
+        action: Option<RatingRuleAction>,
+
+        /// how much a poorly rated chore counts with partial, in [0, 1)
+        #[arg(long, alias = "ScoreFactor", required_if_eq("action", "partial"))]
+        score_factor: Option<f64>,
+    },
 }
 
 #[derive(Subcommand)]
 #[command(arg_required_else_help = true)]
-enum ExemptionCommand {
+pub enum ExemptionCommand {
     /// list all chore exemptions
     List,
     /// create a new reason for exempting someone from a chore
@@ -200,14 +358,235 @@ enum ExemptionCommand {
     },
 }
 
+#[derive(Subcommand)]
+#[command(arg_required_else_help = true)]
+pub enum ScoreCommand {
+    /// list all score adjustments
+    List,
+    /// add to a tenant's score, e.g., for cleaning up after a party
+    ///
+    /// doing a chore once is worth 1, a negative amount debits the tenant
+    Adjust {
+        /// the name of the tenant to credit
+        #[arg(long, alias = "Tenant")]
+        tenant: String,
+
+        /// the chore to credit, leave out to split the amount among all active chores
+        #[arg(long, alias = "Chore")]
+        chore: Option<String>,
+
+        /// the amount to add to the tenant's score
+        #[arg(long, alias = "Amount", allow_negative_numbers = true)]
+        amount: f64,
+
+        /// why the score is adjusted
+        #[arg(long, alias = "Reason")]
+        reason: String,
+    },
+}
+
+#[derive(Subcommand)]
+#[command(arg_required_else_help = true)]
+pub enum ConstraintCommand {
+    /// list all constraints
+    List,
+    /// set how many weeks before and after a week a tenant's chores make them busy
+    ///
+    /// busy tenants are only avoided when CHORE_PLANNER_TRY_EXCLUDE_BUSY_TENANTS is enabled
+    BusyWindow {
+        /// the amount of weeks, 1 considers the last, this and the next week
+        #[arg(long, alias = "Weeks")]
+        weeks: u32,
+    },
+    /// limit how many chores a tenant may do in the same week
+    ///
+    /// this is a hard constraint, when no one else can do a chore it isn't planned
+    MaxChores {
+        /// the name of the tenant to limit
+        #[arg(long, alias = "Tenant")]
+        tenant: String,
+
+        /// the maximum amount of chores per week, leave out to remove the limit
+        #[arg(long, alias = "Max", value_parser = clap::value_parser!(u32).range(1..))]
+        max: Option<u32>,
+    },
+    /// prevent a tenant from doing the same chore again too soon
+    ///
+    /// this is a soft constraint, it is ignored when no one else can do the chore
+    CoolDown {
+        /// the name of the tenant to cool down
+        #[arg(long, alias = "Tenant")]
+        tenant: String,
+
+        /// the name of the chore to cool down from
+        #[arg(long, alias = "Chore")]
+        chore: String,
+
+        /// the minimum amount of weeks between doing the chore twice, leave out to remove the
+        /// cool-down
+        #[arg(long, alias = "Weeks", value_parser = clap::value_parser!(u32).range(1..))]
+        weeks: Option<u32>,
+    },
+}
+
+#[derive(Subcommand)]
+#[command(arg_required_else_help = true)]
+pub enum PreferenceCommand {
+    /// list the preferences of all current tenants
+    List,
+    /// set how much a tenant likes a chore
+    Set {
+        /// the name of the tenant
+        #[arg(long, alias = "Tenant")]
+        tenant: String,
+
+        /// the name of the chore
+        #[arg(long, alias = "Chore")]
+        chore: String,
+
+        /// how much the tenant likes the chore
+        #[arg(long, alias = "Preference", value_enum)]
+        preference: Preference,
+    },
+}
+
+#[derive(Subcommand)]
+#[command(arg_required_else_help = true)]
+pub enum SettingsCommand {
+    /// list all settings
+    Get,
+    /// change a setting
+    Set {
+        #[command(subcommand)]
+        setting: Option<Setting>,
+    },
+}
+
+#[derive(Subcommand)]
+#[command(arg_required_else_help = true)]
+pub enum Setting {
+    /// how many weeks to plan into the future
+    ///
+    /// the new weeks are planned right away
+    WeeksToPlan {
+        /// the amount of weeks in [0, 52]
+        #[arg(long, alias = "Value")]
+        value: u32,
+    },
+    /// probability distribution parameter
+    ///
+    /// the higher gamma the less a low score increases the probability of being chosen, only
+    /// chores planned from now on are affected
+    Gamma {
+        /// the new gamma in [0, 1]
+        #[arg(long, alias = "Value")]
+        value: f64,
+    },
+    /// try to exclude busy tenants from chore assignments
+    ///
+    /// only chores planned from now on are affected
+    TryExcludeBusyTenants {
+        /// true or false
+        #[arg(long, alias = "Value", action = ArgAction::Set)]
+        value: bool,
+    },
+    /// the language of the messages
+    ///
+    /// the names of the commands stay english, only their help is translated
+    Locale {
+        /// the new locale
+        #[arg(long, alias = "Value", value_enum)]
+        value: Locale,
+    },
+    /// how old chores lose their weight in the scores
+    ///
+    /// without decay tenants who did many chores long ago are shielded from chores for a long time
+    ScoreDecay {
+        /// half-life halves the weight every few weeks, window only counts the last few weeks
+        #[arg(long, alias = "Value", value_enum)]
+        value: ScoreDecayMode,
+
+        /// the half-life or the size of the window in weeks
+        #[arg(long, alias = "Weeks", required_if_eq_any([("value", "half-life"), ("value", "window")]))]
+        weeks: Option<u32>,
+    },
+    /// the score tenants start with when they move in for the first time
+    ///
+    /// tenants who already moved in keep their score
+    StartScore {
+        /// zero, the mean score of the other tenants or the score of the room's previous tenant who
+        /// moved out
+        #[arg(long, alias = "Value", value_enum)]
+        value: StartScorePolicy,
+    },
+}
+
+#[derive(Subcommand)]
+#[command(arg_required_else_help = true)]
+pub enum TemplateCommand {
+    /// list all templates and the placeholders they may use
+    List,
+    /// replace the template of a message
+    ///
+    /// placeholders are written in braces, i.e., {tenant}, unknown placeholders are rejected
+    Set {
+        /// the message to customise
+        #[arg(long, alias = "Kind", value_enum)]
+        kind: TemplateKind,
+
+        /// the new template
+        #[arg(long, alias = "Template")]
+        template: String,
+    },
+    /// go back to the default template of the locale
+    Reset {
+        /// the message to reset
+        #[arg(long, alias = "Kind", value_enum)]
+        kind: TemplateKind,
+    },
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum Preference {
+    Like,
+    Neutral,
+    Dislike,
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum ScoreDecayMode {
+    None,
+    HalfLife,
+    Window,
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum RatingRuleAction {
+    Partial,
+    Redo,
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
 /// Assigning a tenant to a ChoreLog prints a message instructing the tenant on how to mark
 /// themselves as unwilling. This command is defined here and must be passed in many database
 /// functions.
-fn fmt_replan_cmd<B>(bot: &B) -> impl Fn(&str, Week) -> String
+pub fn fmt_replan_cmd<B>(bot: &B) -> impl Fn(&str, Week) -> String
 where
     B: MessagableBot,
 {
-    let bot_name = String::from(bot.get_name());
+    fmt_replan_cmd_for(bot.get_name())
+}
+
+/// Format the replan command for a bot called bot_name.
+///
+/// Use this when there is no bot, e.g. in the admin CLI.
+pub fn fmt_replan_cmd_for(bot_name: &str) -> impl Fn(&str, Week) -> String {
+    let bot_name = String::from(bot_name);
     move |tenant, week| {
         format!(
             "{} replan --tenant {} --week {} --year {}",
@@ -219,18 +598,75 @@ where
     }
 }
 
+/// Format the done command for a bot called bot_name, it takes the chore and week.
+pub fn fmt_done_cmd_for(bot_name: &str) -> impl Fn(&str, Week) -> String {
+    let bot_name = String::from(bot_name);
+    move |chore, week| {
+        format!(
+            "{} chore done --chore {} --week {} --year {}",
+            bot_name,
+            shellwords::escape(chore),
+            week.iso_week().week(),
+            week.iso_week().year()
+        )
+    }
+}
+
+/// Format the swap command for a bot called bot_name, it takes the tenant, the tenant to swap
+/// with and the week.
+pub fn fmt_swap_cmd_for(bot_name: &str) -> impl Fn(&str, Option<&str>, Week) -> String {
+    let bot_name = String::from(bot_name);
+    move |tenant, other, week| {
+        let with = other
+            .map(|other| format!(" --with {}", shellwords::escape(other)))
+            .unwrap_or_default();
+        format!(
+            "{} swap --tenant {}{} --week {} --year {}",
+            bot_name,
+            shellwords::escape(tenant),
+            with,
+            week.iso_week().week(),
+            week.iso_week().year()
+        )
+    }
+}
+
 /// Parse a command string, perform the required action and return some response.
-async fn run_command<F>(db: &mut Db, input: &str, fmt_replan_cmd: F) -> Result<ReplyMsg>
+async fn run_command<B>(db: &mut Db, bot: &mut B, input: &str) -> Result<ReplyMsg>
 where
-    F: Fn(&str, Week) -> String,
+    B: MessagableBot + PollableBot,
 {
     println!("{}", input);
     let split_input = shellwords::split(input)?;
+    let mut command = db.locale().localize_command(Cli::command());
     // The help pages are also handled as errors and are thus send to the bot with this.
-    let cli = Cli::try_parse_from(split_input)?;
+    let matches = command.try_get_matches_from_mut(split_input)?;
+    let cli = Cli::from_arg_matches(&matches).map_err(|e| e.format(&mut command))?;
 
     match &cli.command {
-        Some(MainCommand::Tenant { command }) => match command {
+        // Only the bot can open the rating poll.
+        Some(MainCommand::Chore {
+            command: Some(ChoreCommand::Done { chore, week, year }),
+        }) => {
+            db.open_rating_poll(bot, chore, Week::new(*week as u32, *year)?)
+                .await
+        }
+        Some(command) => execute_command(db, command, bot.get_name()).await,
+        None => panic!(),
+    }
+}
+
+/// Perform the action of an already parsed command and return some response.
+///
+/// bot_name is needed for the commands in the response, e.g. the replan command.
+pub async fn execute_command(
+    db: &mut Db,
+    command: &MainCommand,
+    bot_name: &str,
+) -> Result<ReplyMsg> {
+    let fmt_replan_cmd = fmt_replan_cmd_for(bot_name);
+    match command {
+        MainCommand::Tenant { command } => match command {
             Some(TenantCommand::List) => db.list_tenants().await,
             Some(TenantCommand::MoveIn { name, tag, room }) => {
                 db.move_in(name, tag, room, fmt_replan_cmd).await
@@ -239,7 +675,7 @@ where
             Some(TenantCommand::CreateRoom { name }) => db.create_room(name).await,
             None => panic!(),
         },
-        Some(MainCommand::Chore { command }) => match command {
+        MainCommand::Chore { command } => match command {
             Some(ChoreCommand::List) => db.list_plan(None).await,
             Some(ChoreCommand::Create { name, description }) => {
                 db.create_chore(name, description, fmt_replan_cmd).await
@@ -250,9 +686,31 @@ where
             Some(ChoreCommand::Reactivate { name }) => {
                 db.set_chore_active_state(name, true, fmt_replan_cmd).await
             }
+            Some(ChoreCommand::Done { .. }) => bail!(db.locale().done_only_in_chat()),
+            Some(ChoreCommand::RatingRules) => db.list_chore_rating_rules().await,
+            Some(ChoreCommand::RatingRule {
+                chore,
+                min_rating,
+                action,
+                score_factor,
+            }) => {
+                let rule =
+                    min_rating
+                        .zip(action.as_ref())
+                        .map(|(min_rating, action)| ChoreRatingRule {
+                            min_rating,
+                            action: match action {
+                                RatingRuleAction::Partial => PoorRatingAction::Partial {
+                                    score_factor: score_factor.unwrap_or_default(),
+                                },
+                                RatingRuleAction::Redo => PoorRatingAction::Redo,
+                            },
+                        });
+                db.set_chore_rating_rule(chore, rule).await
+            }
             None => panic!(),
         },
-        Some(MainCommand::Exemption { command }) => match command {
+        MainCommand::Exemption { command } => match command {
             Some(ExemptionCommand::List) => db.list_exemptions().await,
             Some(ExemptionCommand::Create { reason, chores }) => {
                 db.create_exemption_reason(reason, chores).await
@@ -269,14 +727,127 @@ where
             }
             None => panic!(),
         },
-        Some(MainCommand::Replan { tenant, week, year }) => {
+        MainCommand::Preference { command } => match command {
+            Some(PreferenceCommand::List) => db.list_preferences().await,
+            Some(PreferenceCommand::Set {
+                tenant,
+                chore,
+                preference,
+            }) => {
+                let preference = match preference {
+                    Preference::Like => 1,
+                    Preference::Neutral => 0,
+                    Preference::Dislike => -1,
+                };
+                db.set_preference(tenant, chore, preference).await
+            }
+            None => panic!(),
+        },
+        MainCommand::Constraint { command } => match command {
+            Some(ConstraintCommand::List) => db.list_constraints().await,
+            Some(ConstraintCommand::BusyWindow { weeks }) => db.set_busy_window(*weeks).await,
+            Some(ConstraintCommand::MaxChores { tenant, max }) => {
+                db.set_chore_limit(tenant, *max).await
+            }
+            Some(ConstraintCommand::CoolDown {
+                tenant,
+                chore,
+                weeks,
+            }) => db.set_cool_down(tenant, chore, *weeks).await,
+            None => panic!(),
+        },
+        MainCommand::Settings { command } => match command {
+            Some(SettingsCommand::Get) => db.list_settings().await,
+            Some(SettingsCommand::Set { setting }) => match setting {
+                Some(Setting::WeeksToPlan { value }) => {
+                    db.set_weeks_to_plan(*value, fmt_replan_cmd).await
+                }
+                Some(Setting::Gamma { value }) => db.set_gamma(*value).await,
+                Some(Setting::TryExcludeBusyTenants { value }) => {
+                    db.set_try_exclude_busy_tenants(*value).await
+                }
+                Some(Setting::Locale { value }) => db.set_locale(*value).await,
+                Some(Setting::ScoreDecay { value, weeks }) => {
+                    let weeks = weeks.unwrap_or_default();
+                    db.set_score_decay(match value {
+                        ScoreDecayMode::None => ScoreDecay::None,
+                        ScoreDecayMode::HalfLife => ScoreDecay::HalfLife { weeks },
+                        ScoreDecayMode::Window => ScoreDecay::Window { weeks },
+                    })
+                    .await
+                }
+                Some(Setting::StartScore { value }) => db.set_start_score_policy(*value).await,
+                None => panic!(),
+            },
+            None => panic!(),
+        },
+        MainCommand::Score { command } => match command {
+            Some(ScoreCommand::List) => db.list_score_adjustments(None).await,
+            Some(ScoreCommand::Adjust {
+                tenant,
+                chore,
+                amount,
+                reason,
+            }) => {
+                db.adjust_score(tenant, chore.as_deref(), *amount, reason)
+                    .await
+            }
+            None => panic!(),
+        },
+        MainCommand::Export { format } => match format {
+            ExportFormat::Csv => db.export_csv().await,
+            ExportFormat::Json => db.export_json().await,
+        },
+        MainCommand::Template { command } => match command {
+            Some(TemplateCommand::List) => db.list_templates().await,
+            Some(TemplateCommand::Set { kind, template }) => db.set_template(*kind, template).await,
+            Some(TemplateCommand::Reset { kind }) => db.reset_template(*kind).await,
+            None => panic!(),
+        },
+        MainCommand::Replan { tenant, week, year } => {
             db.replan(tenant, Week::new(*week as u32, *year)?, fmt_replan_cmd)
                 .await
         }
-        Some(MainCommand::Report { week, year }) => {
+        MainCommand::Swap {
+            tenant,
+            with,
+            week,
+            year,
+        } => {
+            db.swap(
+                tenant,
+                with.as_deref(),
+                Week::new(*week as u32, *year)?,
+                fmt_swap_cmd_for(bot_name),
+            )
+            .await
+        }
+        MainCommand::Report { week, year } => {
             db.print_report(Week::new(*week as u32, *year)?).await
         }
-        None => panic!(),
+        MainCommand::Stats {
+            week,
+            year,
+            end_week,
+            end_year,
+            sort,
+        } => {
+            let end_week = end_week
+                .zip(*end_year)
+                .map(|(end_week, end_year)| Week::new(end_week as u32, end_year))
+                .transpose()?;
+            db.print_stats(Week::new(*week as u32, *year)?, end_week, *sort)
+                .await
+        }
+        MainCommand::Simulate {
+            weeks,
+            gamma,
+            try_exclude_busy_tenants,
+            seed,
+        } => {
+            db.simulate(*weeks, *gamma, *try_exclude_busy_tenants, *seed)
+                .await
+        }
     }
 }
 
@@ -299,18 +870,52 @@ pub async fn weekly_action<B: MessagableBot + PollableBot>(db: &mut Db, bot: &mu
     bot.send_msg(db.update_plan(fmt_replan_cmd(bot)).await)
         .await;
 
-    if let Err(e) = db.stop_rating_polls(bot).await {
-        eprintln!("Error stopping polls: {:?}", e);
+    match db.stop_rating_polls(bot).await {
+        Ok(redos) if !redos.is_empty() => bot.send_msg(Ok(redos)).await,
+        Ok(_) => {}
+        Err(e) => eprintln!("Error stopping polls: {:?}", e),
     }
     if let Err(e) = db.create_rating_polls(bot).await {
         eprintln!("Error creating polls: {:?}", e);
     }
 
-    bot.send_msg(db.print_next_week_banner().await).await;
+    let show_plan = Action::new(
+        db.locale().show_plan_action(),
+        &format!("{} chore list", bot.get_name()),
+    );
+    bot.send_msg(
+        db.print_next_week_banner(
+            fmt_replan_cmd(bot),
+            fmt_done_cmd_for(bot.get_name()),
+            fmt_swap_cmd_for(bot.get_name()),
+        )
+        .await
+        .map(|mut msg| {
+            msg.push_action(show_plan);
+            msg
+        }),
+    )
+    .await;
+}
+
+/// Run a command given without the bot's name as if it was sent to the bot and return the
+/// response.
+pub async fn run_command_for_bot<B: MessagableBot + PollableBot>(
+    db: &mut Db,
+    bot: &mut B,
+    command: &str,
+) -> Result<ReplyMsg> {
+    let input = format!("{} {}", shellwords::escape(bot.get_name()), command);
+    run_command(db, bot, &input).await
 }
 
 /// Run a command and return the response to the bot.
-pub async fn handle_next_msg<B: MessagableBot>(db: &mut Db, bot: &mut B, msg: &str) {
-    bot.send_msg(run_command(db, msg, fmt_replan_cmd(bot)).await)
-        .await;
+///
+/// Long responses like reports are sent as a single file when the bot supports it.
+pub async fn handle_next_msg<B: MessagableBot + PollableBot>(db: &mut Db, bot: &mut B, msg: &str) {
+    let mut reply = run_command(db, bot, msg).await;
+    if bot.sends_long_msgs_as_file() {
+        reply = reply.map(|r| r.into_file_when_longer_than(FILE_THRESHOLD, "reply.txt"));
+    }
+    bot.send_msg(reply).await;
 }
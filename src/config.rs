@@ -0,0 +1,488 @@
+use crate::{bot::BotProtocol, db::MAX_WEEKS_TO_PLAN, locale::Locale};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{env, fmt::Display, fs, str::FromStr};
+
+#[cfg(test)]
+#[path = "./tests/config_test.rs"]
+mod config_test;
+
+/// the configuration of the chore_planner
+///
+/// The configuration is read from the TOML file at CHORE_PLANNER_CONFIG, when set, and then
+/// overridden by the environment variables.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// one of Telegram, Signal or Console
+    pub chat_protocol: Option<String>,
+    /// the path to the SQLite database
+    ///
+    /// Unused with multiple households, each of them has its own database.
+    pub db_path: Option<String>,
+    /// advance the current week every time the weekly action is performed
+    pub debug: bool,
+    /// how many weeks to plan into the future
+    pub weeks_to_plan: u32,
+    /// the probability distribution parameter in [0, 1]
+    pub gamma: f64,
+    /// try excluding tenants that already do a chore the last, this or the next week
+    pub try_exclude_busy_tenants: bool,
+    /// use last week instead of this week when the database doesn't know the current week yet
+    pub fallback_to_last_week: bool,
+    /// the language of the messages, en or de
+    pub locale: Locale,
+    pub http: HttpConfig,
+    pub telegram: TelegramConfig,
+    pub signal: SignalConfig,
+    /// the households served by one chore_planner, a single household when empty
+    pub households: Vec<HouseholdConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpConfig {
+    /// where the HTTP API listens, disabled when None
+    pub address: Option<String>,
+    /// the bearer token needed for running commands, commands are disabled when None
+    pub token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct TelegramConfig {
+    pub bot_token: Option<String>,
+    /// Unused with multiple households, each of them has its own chat.
+    pub chat_id: Option<i64>,
+    pub anonymous_polls: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct SignalConfig {
+    /// the address of the signal-cli daemon
+    pub endpoint: Option<String>,
+    /// Unused with multiple households, each of them has its own group.
+    pub group_id: Option<String>,
+    pub account_name: Option<String>,
+    pub display_name: Option<String>,
+    pub allow_message_from_self: bool,
+}
+
+/// the settings of one household
+///
+/// Settings that are None default to the global ones.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct HouseholdConfig {
+    pub name: String,
+    pub db_path: Option<String>,
+    pub telegram_chat_id: Option<i64>,
+    pub signal_group_id: Option<String>,
+    pub debug: Option<bool>,
+    pub weeks_to_plan: Option<u32>,
+    pub gamma: Option<f64>,
+    pub try_exclude_busy_tenants: Option<bool>,
+    pub fallback_to_last_week: Option<bool>,
+    pub locale: Option<Locale>,
+}
+
+/// the settings of one household with the global settings filled in
+#[derive(Debug, PartialEq)]
+pub struct HouseholdSettings {
+    pub name: String,
+    pub db_path: String,
+    pub telegram_chat_id: Option<i64>,
+    pub signal_group_id: Option<String>,
+    pub debug: bool,
+    pub weeks_to_plan: u32,
+    pub gamma: f64,
+    pub try_exclude_busy_tenants: bool,
+    pub fallback_to_last_week: bool,
+    pub locale: Locale,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            chat_protocol: None,
+            db_path: None,
+            debug: false,
+            weeks_to_plan: 5,
+            gamma: 0.0,
+            try_exclude_busy_tenants: false,
+            fallback_to_last_week: true,
+            locale: Locale::En,
+            http: HttpConfig::default(),
+            telegram: TelegramConfig::default(),
+            signal: SignalConfig::default(),
+            households: vec![],
+        }
+    }
+}
+
+impl Default for TelegramConfig {
+    fn default() -> Self {
+        TelegramConfig {
+            bot_token: None,
+            chat_id: None,
+            anonymous_polls: true,
+        }
+    }
+}
+
+/// Override value with the environment variable called name, when it is set.
+///
+/// Errors are collected in errors.
+fn env_override<T>(name: &str, value: &mut T, errors: &mut Vec<String>)
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    if let Ok(env_value) = env::var(name) {
+        match env_value.parse::<T>() {
+            Ok(env_value) => *value = env_value,
+            Err(e) => errors.push(format!("{} '{}' is invalid: {}", name, env_value, e)),
+        }
+    }
+}
+
+/// Override an optional value with the environment variable called name, when it is set.
+fn env_override_opt<T>(name: &str, value: &mut Option<T>, errors: &mut Vec<String>)
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    if let Ok(env_value) = env::var(name) {
+        match env_value.parse::<T>() {
+            Ok(env_value) => *value = Some(env_value),
+            Err(e) => errors.push(format!("{} '{}' is invalid: {}", name, env_value, e)),
+        }
+    }
+}
+
+impl Config {
+    /// Read the configuration file at CHORE_PLANNER_CONFIG, override it with the environment
+    /// variables and validate the result.
+    ///
+    /// The error lists every problem at once.
+    pub fn load() -> Result<Config> {
+        let (config, mut errors) = Self::read()?;
+        config.validate(&mut errors);
+        if !errors.is_empty() {
+            bail!("invalid configuration:\n- {}", errors.join("\n- "));
+        }
+        Ok(config)
+    }
+
+    /// Read the configuration like load but don't validate it.
+    ///
+    /// The admin CLI has no chat and may override the settings of its household.
+    pub fn load_unvalidated() -> Result<Config> {
+        let (config, errors) = Self::read()?;
+        if !errors.is_empty() {
+            bail!("invalid configuration:\n- {}", errors.join("\n- "));
+        }
+        Ok(config)
+    }
+
+    /// Read the configuration file and override it with the environment variables.
+    ///
+    /// Return the configuration and the invalid environment variables.
+    fn read() -> Result<(Config, Vec<String>)> {
+        let mut config = match env::var("CHORE_PLANNER_CONFIG") {
+            Ok(path) => {
+                let content = fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read the config file {}", path))?;
+                toml::from_str(&content)
+                    .with_context(|| format!("failed to parse the config file {}", path))?
+            }
+            Err(_) => Config::default(),
+        };
+        let mut errors = vec![];
+        config.apply_env(&mut errors);
+        Ok((config, errors))
+    }
+
+    /// Override the configuration with the environment variables that are set.
+    fn apply_env(&mut self, errors: &mut Vec<String>) {
+        env_override_opt(
+            "CHORE_PLANNER_CHAT_PROTOCOL",
+            &mut self.chat_protocol,
+            errors,
+        );
+        env_override_opt("CHORE_PLANNER_DB_PATH", &mut self.db_path, errors);
+        env_override("CHORE_PLANNER_DEBUG", &mut self.debug, errors);
+        env_override(
+            "CHORE_PLANNER_WEEKS_TO_PLAN",
+            &mut self.weeks_to_plan,
+            errors,
+        );
+        env_override("CHORE_PLANNER_GAMMA", &mut self.gamma, errors);
+        env_override(
+            "CHORE_PLANNER_TRY_EXCLUDE_BUSY_TENANTS",
+            &mut self.try_exclude_busy_tenants,
+            errors,
+        );
+        env_override(
+            "CHORE_PLANNER_FALLBACK_TO_LAST_WEEK",
+            &mut self.fallback_to_last_week,
+            errors,
+        );
+        env_override("CHORE_PLANNER_LOCALE", &mut self.locale, errors);
+        env_override_opt("CHORE_PLANNER_HTTP_ADDRESS", &mut self.http.address, errors);
+        env_override_opt("CHORE_PLANNER_HTTP_TOKEN", &mut self.http.token, errors);
+        env_override_opt("TELEGRAM_BOT_TOKEN", &mut self.telegram.bot_token, errors);
+        env_override_opt("TELEGRAM_CHAT_ID", &mut self.telegram.chat_id, errors);
+        env_override(
+            "TELEGRAM_ANONYMOUS_POLLS",
+            &mut self.telegram.anonymous_polls,
+            errors,
+        );
+        env_override_opt("SIGNAL_CLI_ENDPOINT", &mut self.signal.endpoint, errors);
+        env_override_opt("SIGNAL_GROUP_ID", &mut self.signal.group_id, errors);
+        env_override_opt("SIGNAL_ACCOUNT_NAME", &mut self.signal.account_name, errors);
+        env_override_opt("SIGNAL_DISPLAY_NAME", &mut self.signal.display_name, errors);
+        env_override(
+            "SIGNAL_ALLOW_MESSAGE_FROM_SELF",
+            &mut self.signal.allow_message_from_self,
+            errors,
+        );
+
+        // CHORE_PLANNER_HOUSEHOLDS replaces the list of households, households from the config
+        // file keep their settings.
+        if let Ok(names) = env::var("CHORE_PLANNER_HOUSEHOLDS") {
+            let mut households = vec![];
+            for name in names.split(',').map(|name| name.trim()) {
+                households.push(match self.households.iter().position(|h| h.name == name) {
+                    Some(i) => self.households.remove(i),
+                    None => HouseholdConfig {
+                        name: name.to_string(),
+                        ..Default::default()
+                    },
+                });
+            }
+            self.households = households;
+        }
+        // A household's variables end with its uppercase name.
+        for household in self.households.iter_mut() {
+            let var = |name: &str| format!("{}_{}", name, household.name.to_uppercase());
+            env_override_opt(
+                &var("CHORE_PLANNER_DB_PATH"),
+                &mut household.db_path,
+                errors,
+            );
+            env_override_opt(
+                &var("TELEGRAM_CHAT_ID"),
+                &mut household.telegram_chat_id,
+                errors,
+            );
+            env_override_opt(
+                &var("SIGNAL_GROUP_ID"),
+                &mut household.signal_group_id,
+                errors,
+            );
+            env_override_opt(&var("CHORE_PLANNER_DEBUG"), &mut household.debug, errors);
+            env_override_opt(
+                &var("CHORE_PLANNER_WEEKS_TO_PLAN"),
+                &mut household.weeks_to_plan,
+                errors,
+            );
+            env_override_opt(&var("CHORE_PLANNER_GAMMA"), &mut household.gamma, errors);
+            env_override_opt(
+                &var("CHORE_PLANNER_TRY_EXCLUDE_BUSY_TENANTS"),
+                &mut household.try_exclude_busy_tenants,
+                errors,
+            );
+            env_override_opt(
+                &var("CHORE_PLANNER_FALLBACK_TO_LAST_WEEK"),
+                &mut household.fallback_to_last_week,
+                errors,
+            );
+            env_override_opt(&var("CHORE_PLANNER_LOCALE"), &mut household.locale, errors);
+        }
+    }
+
+    /// Check the configuration and collect all problems in errors.
+    pub(crate) fn validate(&self, errors: &mut Vec<String>) {
+        let protocol = match &self.chat_protocol {
+            Some(protocol) => match protocol.parse::<BotProtocol>() {
+                Ok(protocol) => Some(protocol),
+                Err(e) => {
+                    errors.push(e.to_string());
+                    None
+                }
+            },
+            None => {
+                errors.push("chat_protocol (CHORE_PLANNER_CHAT_PROTOCOL) is missing".to_string());
+                None
+            }
+        };
+
+        let households = self.household_settings();
+        for (i, household) in households.iter().enumerate() {
+            if !(0.0..=1.0).contains(&household.gamma) {
+                errors.push(format!(
+                    "gamma of the household {} needs to be in [0, 1]",
+                    household.name
+                ));
+            }
+            if household.weeks_to_plan > MAX_WEEKS_TO_PLAN {
+                errors.push(format!(
+                    "weeks_to_plan of the household {} needs to be in [0, {}]",
+                    household.name, MAX_WEEKS_TO_PLAN
+                ));
+            }
+            if household.db_path.is_empty() {
+                errors.push(match self.households.is_empty() {
+                    true => "db_path (CHORE_PLANNER_DB_PATH) is missing".to_string(),
+                    false => format!(
+                        "db_path (CHORE_PLANNER_DB_PATH_{}) of the household {} is missing",
+                        household.name.to_uppercase(),
+                        household.name
+                    ),
+                });
+            }
+            if matches!(protocol, Some(BotProtocol::Signal)) && household.signal_group_id.is_none()
+            {
+                errors.push(match self.households.is_empty() {
+                    true => "signal.group_id (SIGNAL_GROUP_ID) is missing".to_string(),
+                    false => format!(
+                        "signal_group_id (SIGNAL_GROUP_ID_{}) of the household {} is missing",
+                        household.name.to_uppercase(),
+                        household.name
+                    ),
+                });
+            }
+            if matches!(protocol, Some(BotProtocol::Telegram)) {
+                match household.telegram_chat_id {
+                    None => errors.push(match self.households.is_empty() {
+                        true => "telegram.chat_id (TELEGRAM_CHAT_ID) is missing".to_string(),
+                        false => format!(
+                            "telegram_chat_id (TELEGRAM_CHAT_ID_{}) of the household {} is missing",
+                            household.name.to_uppercase(),
+                            household.name
+                        ),
+                    }),
+                    Some(chat_id) => {
+                        if households[..i]
+                            .iter()
+                            .any(|h| h.telegram_chat_id == Some(chat_id))
+                        {
+                            errors.push(format!(
+                                "the telegram_chat_id {} of the household {} is used twice",
+                                chat_id, household.name
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        for (i, household) in self.households.iter().enumerate() {
+            if household.name.is_empty()
+                || !household
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_')
+            {
+                errors.push(format!(
+                    "the household name '{}' may only contain letters, digits and underscores",
+                    household.name
+                ));
+            }
+            if self.households[..i]
+                .iter()
+                .any(|h| h.name == household.name)
+            {
+                errors.push(format!(
+                    "the household name '{}' is used twice",
+                    household.name
+                ));
+            }
+        }
+        if let Some(address) = &self.http.address {
+            if address.is_empty() {
+                errors.push("http.address (CHORE_PLANNER_HTTP_ADDRESS) is empty".to_string());
+            }
+            if self
+                .http
+                .token
+                .as_ref()
+                .is_some_and(|t| t.trim().is_empty())
+            {
+                errors.push("http.token (CHORE_PLANNER_HTTP_TOKEN) is empty".to_string());
+            }
+        }
+
+        match protocol {
+            Some(BotProtocol::Telegram) => {
+                if self.telegram.bot_token.is_none() {
+                    errors.push("telegram.bot_token (TELEGRAM_BOT_TOKEN) is missing".to_string());
+                }
+            }
+            Some(BotProtocol::Signal) => {
+                if self.signal.endpoint.is_none() {
+                    errors.push("signal.endpoint (SIGNAL_CLI_ENDPOINT) is missing".to_string());
+                }
+                if self.signal.account_name.is_none() {
+                    errors.push("signal.account_name (SIGNAL_ACCOUNT_NAME) is missing".to_string());
+                }
+                if self.signal.display_name.is_none() {
+                    errors.push("signal.display_name (SIGNAL_DISPLAY_NAME) is missing".to_string());
+                }
+            }
+            Some(BotProtocol::Console) | None => {}
+        }
+    }
+
+    /// Get the settings of all households with the global settings filled in.
+    ///
+    /// Without configured households there is a single one called default.
+    pub fn household_settings(&self) -> Vec<HouseholdSettings> {
+        if self.households.is_empty() {
+            return vec![HouseholdSettings {
+                name: "default".to_string(),
+                db_path: self.db_path.clone().unwrap_or_default(),
+                telegram_chat_id: self.telegram.chat_id,
+                signal_group_id: self.signal.group_id.clone(),
+                debug: self.debug,
+                weeks_to_plan: self.weeks_to_plan,
+                gamma: self.gamma,
+                try_exclude_busy_tenants: self.try_exclude_busy_tenants,
+                fallback_to_last_week: self.fallback_to_last_week,
+                locale: self.locale,
+            }];
+        }
+        self.households
+            .iter()
+            .map(|household| HouseholdSettings {
+                name: household.name.clone(),
+                db_path: household.db_path.clone().unwrap_or_default(),
+                telegram_chat_id: household.telegram_chat_id,
+                signal_group_id: household.signal_group_id.clone(),
+                debug: household.debug.unwrap_or(self.debug),
+                weeks_to_plan: household.weeks_to_plan.unwrap_or(self.weeks_to_plan),
+                gamma: household.gamma.unwrap_or(self.gamma),
+                try_exclude_busy_tenants: household
+                    .try_exclude_busy_tenants
+                    .unwrap_or(self.try_exclude_busy_tenants),
+                fallback_to_last_week: household
+                    .fallback_to_last_week
+                    .unwrap_or(self.fallback_to_last_week),
+                locale: household.locale.unwrap_or(self.locale),
+            })
+            .collect()
+    }
+
+    /// Render the configuration as TOML with the secrets hidden.
+    pub fn to_redacted_toml(&self) -> Result<String> {
+        let mut config = toml::Value::try_from(self)?;
+        for (table, key) in [("http", "token"), ("telegram", "bot_token")] {
+            if let Some(value) = config.get_mut(table).and_then(|table| table.get_mut(key)) {
+                *value = toml::Value::String("<redacted>".to_string());
+            }
+        }
+        Ok(toml::to_string_pretty(&config)?)
+    }
+}
@@ -0,0 +1,197 @@
+use crate::bot::{BotUpdate, MessagableBot, PollableBot, ReplyMsg, SentPoll};
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use std::{collections::HashMap, io::Write};
+use tokio::io::{stdin, AsyncBufReadExt, BufReader, Lines, Stdin};
+
+/// the meta-command that triggers the weekly action
+const WEEKLY_ACTION_CMD: &str = "!weekly";
+/// the meta-command that switches to another household's chat
+const HOUSEHOLD_CMD: &str = "!household";
+
+/// a bot reading commands from stdin and writing replies to stdout
+///
+/// This is made for trying the chore_planner locally.
+/// The bot's name is optional in front of commands.
+pub struct ConsoleBot {
+    name: String,
+    lines: Lines<BufReader<Stdin>>,
+    /// the options of all polls created since the bot started
+    polls: HashMap<i64, (String, Vec<String>)>,
+    /// the names of all households, the console acts as the chat of each of them
+    households: Vec<String>,
+    selected_chat: usize,
+}
+
+impl ConsoleBot {
+    pub fn new(households: Vec<String>) -> Self {
+        println!(
+            "Type commands like 'tenant list' or '--help'. '{}' performs the weekly action.",
+            WEEKLY_ACTION_CMD
+        );
+        if households.len() > 1 {
+            println!(
+                "'{} <name>' switches to another household: {}",
+                HOUSEHOLD_CMD,
+                households.join(", ")
+            );
+        }
+        ConsoleBot {
+            name: "@chore_planner_bot".to_string(),
+            lines: BufReader::new(stdin()).lines(),
+            polls: HashMap::new(),
+            households,
+            selected_chat: 0,
+        }
+    }
+
+    /// Switch to the chat of the household called name.
+    fn switch_household(&mut self, name: &str) {
+        match self.households.iter().position(|h| h == name) {
+            Some(chat) => self.selected_chat = chat,
+            None => println!(
+                "there is no household '{}', choose one of: {}",
+                name,
+                self.households.join(", ")
+            ),
+        }
+    }
+
+    /// Read the next non-empty line from stdin.
+    ///
+    /// Return None when stdin is closed.
+    async fn read_line(&mut self, prompt: &str) -> Result<Option<String>> {
+        loop {
+            print!("{}", prompt);
+            std::io::stdout().flush()?;
+            match self.lines.next_line().await? {
+                Some(line) if line.trim().is_empty() => continue,
+                Some(line) => return Ok(Some(line.trim().to_string())),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+impl MessagableBot for ConsoleBot {
+    async fn next_update(&mut self) -> Option<BotUpdate> {
+        // Only show the household when there is a choice.
+        let prompt = match self.households.len() > 1 {
+            true => format!("{}> ", self.households[self.selected_chat]),
+            false => "> ".to_string(),
+        };
+        match self.read_line(&prompt).await {
+            Ok(Some(line)) if line == WEEKLY_ACTION_CMD => Some(BotUpdate::WeeklyAction),
+            Ok(Some(line)) if line.starts_with(HOUSEHOLD_CMD) => {
+                self.switch_household(line[HOUSEHOLD_CMD.len()..].trim());
+                None
+            }
+            Ok(Some(line)) if line.to_lowercase().starts_with(&self.name) => {
+                Some(BotUpdate::Msg(line))
+            }
+            Ok(Some(line)) => Some(BotUpdate::Msg(format!("{} {}", self.name, line))),
+            Ok(None) => {
+                println!("\nstdin is closed; stop the chore_planner with Ctrl-C");
+                std::future::pending().await
+            }
+            Err(e) => {
+                eprintln!("reading from stdin failed: {:#}", e);
+                None
+            }
+        }
+    }
+
+    async fn send_msg(&mut self, msg: Result<ReplyMsg>) {
+        let msg = msg.unwrap_or_else(|e| ReplyMsg::from_mono(&e.to_string()));
+        let mono_msg = msg.mono_msg();
+        if !mono_msg.trim().is_empty() {
+            println!("{}", mono_msg.trim());
+        }
+        let tags = msg.unmentioned_tags();
+        if !tags.is_empty() {
+            println!("{}", tags.join(" "));
+        }
+        for attachment in &msg.attachments {
+            if let Err(e) = self
+                .send_file(
+                    &attachment.filename,
+                    attachment.content.as_bytes(),
+                    attachment.caption.as_deref(),
+                )
+                .await
+            {
+                eprintln!("Error sending file {}: {:?}", attachment.filename, e);
+            }
+        }
+    }
+
+    /// Write the file into the working directory.
+    async fn send_file(&mut self, name: &str, bytes: &[u8], caption: Option<&str>) -> Result<()> {
+        tokio::fs::write(name, bytes).await?;
+        match caption {
+            Some(caption) => println!("wrote {}: {}", name, caption),
+            None => println!("wrote {}", name),
+        }
+        Ok(())
+    }
+
+    fn selected_chat(&self) -> usize {
+        self.selected_chat
+    }
+
+    fn select_chat(&mut self, chat: usize) {
+        self.selected_chat = chat;
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    async fn shutdown(&mut self) {}
+}
+
+impl PollableBot for ConsoleBot {
+    async fn send_poll(&mut self, question: &str, options: Vec<String>) -> Result<SentPoll> {
+        // Use the time as id so that polls from a previous run don't get mixed up with new ones.
+        let mut poll_id = Utc::now().timestamp_millis();
+        while self.polls.contains_key(&poll_id) {
+            poll_id += 1;
+        }
+        println!("Poll {}: {}", poll_id, question);
+        for option in &options {
+            println!("  - {}", option);
+        }
+        self.polls.insert(poll_id, (question.to_string(), options));
+        Ok(SentPoll {
+            id: poll_id,
+            vote_key: None,
+        })
+    }
+
+    async fn stop_poll(&mut self, poll_id: i64) -> Result<Vec<(String, u32)>> {
+        let Some((question, options)) = self.polls.remove(&poll_id) else {
+            bail!(
+                "the poll {} wasn't created since the console bot started",
+                poll_id
+            );
+        };
+        println!("Poll {} closed: {}", poll_id, question);
+        println!("How many votes did each option get?");
+        let mut results = Vec::new();
+        for option in options {
+            let count = loop {
+                let line = self
+                    .read_line(&format!("{}: ", option))
+                    .await?
+                    .context("stdin is closed")?;
+                match line.parse::<u32>() {
+                    Ok(count) => break count,
+                    Err(_) => println!("'{}' isn't a number of votes", line),
+                }
+            };
+            results.push((option, count));
+        }
+        Ok(results)
+    }
+}
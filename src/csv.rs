@@ -0,0 +1,83 @@
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use tabled::Tabled;
+
+/// Render rows as CSV with the header defined by T.
+///
+/// Fields are quoted as in RFC 4180 when needed.
+pub fn to_csv<T: Tabled>(rows: &[T]) -> String {
+    fn escape(field: &str) -> String {
+        match field.contains([',', '"', '\n', '\r']) {
+            true => format!("\"{}\"", field.replace('"', "\"\"")),
+            false => field.to_string(),
+        }
+    }
+    let mut csv = String::new();
+    for record in std::iter::once(T::headers()).chain(rows.iter().map(Tabled::fields)) {
+        csv += &record
+            .iter()
+            .map(|field| escape(field))
+            .collect::<Vec<_>>()
+            .join(",");
+        csv += "\r\n";
+    }
+    csv
+}
+
+/// Parse CSV with a header into one map from column name to field per record.
+///
+/// Empty lines are skipped. Fail when a record doesn't have as many fields as the header.
+pub fn parse_csv(csv: &str) -> Result<Vec<HashMap<String, String>>> {
+    let mut records: Vec<Vec<String>> = vec![];
+    let mut record: Vec<String> = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (in_quotes, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => in_quotes = false,
+            (true, c) => field.push(c),
+            (false, '"') if field.is_empty() => in_quotes = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n') => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if in_quotes {
+        bail!("the CSV has an unclosed quote");
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records.retain(|r| !(r.len() == 1 && r[0].trim().is_empty()));
+
+    let mut records = records.into_iter();
+    let Some(header) = records.next() else {
+        bail!("the CSV is empty, it needs at least a header");
+    };
+    let header: Vec<String> = header.into_iter().map(|h| h.trim().to_string()).collect();
+    records
+        .enumerate()
+        .map(|(i, record)| {
+            if record.len() != header.len() {
+                // count the header as line 1
+                bail!(
+                    "record {} has {} fields but the header has {}",
+                    i + 2,
+                    record.len(),
+                    header.len()
+                );
+            }
+            Ok(header.iter().cloned().zip(record).collect())
+        })
+        .collect()
+}
@@ -1,25 +1,46 @@
 // backend helper functions
+mod chore;
+mod constraint;
 mod exemption;
+mod import;
 mod key_value;
 mod plan;
+mod preference;
 mod scheme;
+mod simulation;
+mod template;
 mod tenant;
 
 // front end interface with command system
 pub mod chore_commands;
+pub mod constraint_commands;
 pub mod exemption_commands;
+pub mod export_commands;
 pub mod plan_commands;
+pub mod preference_commands;
 pub mod rating;
 pub mod report_commands;
+pub mod score_commands;
+pub mod settings_commands;
+pub mod simulation_commands;
+pub mod stats_commands;
+pub mod template_commands;
 pub mod tenant_commands;
 
-use crate::{bot::ReplyMsg, week::Week};
+use crate::{
+    bot::{Action, Block, Inline, ReplyMsg},
+    locale::{Heading, Locale, TableHeader},
+    week::Week,
+};
 
 use anyhow::{bail, Result};
 use rand::{rngs::StdRng, SeedableRng};
 use sqlx::{sqlite::SqliteConnectOptions, ConnectOptions, Row, SqliteConnection};
 use std::str::FromStr;
 
+/// Planning further ahead than a year isn't useful as everything changes anyways.
+pub const MAX_WEEKS_TO_PLAN: u32 = 52;
+
 #[cfg(test)]
 #[path = "./tests/db_test.rs"]
 mod db_test;
@@ -32,6 +53,8 @@ pub struct Db {
     /// The global variable is set at the first weekly action.
     fallback_week: Week,
     /// how many weeks to plan ahead and thus create ChoreLogs for
+    ///
+    /// A weeks_to_plan stored in the KeyValue table takes precedence.
     weeks_to_plan: u32,
     /// probability distribution parameter between in [0, 1]
     /// The higher gamma the more extremely the score effects the tenants probability of being
@@ -41,11 +64,20 @@ pub struct Db {
     /// 1 results in all tenants having the same probability regardless of their score.
     ///
     /// See the mathematical proof in the repo.
+    ///
+    /// A gamma stored in the KeyValue table takes precedence.
     gamma: f64,
     /// When true the chore_planner tries to exclude busy tenants from chore assignments for this week.
-    /// Busy tenants are those already doing a chore the last, this or the next week.
+    /// Busy tenants are those already doing a chore within the busy window (by default the last,
+    /// this or the next week).
     /// If you want to prevent the same tenant doing two chores in the same week, enable this.
+    ///
+    /// A try_exclude_busy_tenants stored in the KeyValue table takes precedence.
     try_exclude_busy_tenants: bool,
+    /// the language of the messages
+    ///
+    /// A locale stored in the KeyValue table takes precedence.
+    locale: Locale,
     rng: StdRng,
     // Increase the week every time a SIGHUP is received.
     debug: bool,
@@ -54,12 +86,14 @@ pub struct Db {
 impl Db {
     /// Create a new database or load a database from some path.
     /// When the debug mode is on, advance to the next week every time the week is updated.
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         path: &str,
         fallback_week: Week,
         weeks_to_plan: u32,
         gamma: f64,
         try_exclude_busy_tenants: bool,
+        locale: Locale,
         seed: u64,
         debug: bool,
     ) -> Result<Self> {
@@ -79,14 +113,41 @@ impl Db {
             weeks_to_plan,
             gamma,
             try_exclude_busy_tenants,
+            locale,
             rng: StdRng::seed_from_u64(seed),
             debug,
         };
         db.migrate().await?;
         db.integrity_check().await?;
+        if let Some(gamma) = db.get_gamma_internal().await {
+            if !(0.0..=1.0).contains(&gamma) {
+                bail!("gamma stored in the database needs to be in [0, 1]");
+            }
+            db.gamma = gamma;
+        }
+        if let Some(weeks_to_plan) = db.get_weeks_to_plan_internal().await {
+            if weeks_to_plan > MAX_WEEKS_TO_PLAN {
+                bail!(
+                    "weeks_to_plan stored in the database needs to be in [0, {}]",
+                    MAX_WEEKS_TO_PLAN
+                );
+            }
+            db.weeks_to_plan = weeks_to_plan;
+        }
+        if let Some(try_exclude_busy_tenants) = db.get_try_exclude_busy_tenants_internal().await {
+            db.try_exclude_busy_tenants = try_exclude_busy_tenants;
+        }
+        if let Some(locale) = db.get_locale_internal().await {
+            db.locale = locale;
+        }
         Ok(db)
     }
 
+    /// Get the language of the messages.
+    pub fn locale(&self) -> Locale {
+        self.locale
+    }
+
     /// Update the current week.
     /// Ignore the provided week when debug mode is enabled. In that case the current week is
     /// simply incremented.
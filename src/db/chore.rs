@@ -0,0 +1,25 @@
+use crate::db::*;
+
+use anyhow::Result;
+
+impl Db {
+    /// Get the id of a chore.
+    pub async fn get_chore_id(&mut self, name: &str) -> Result<Option<u32>> {
+        let rows = sqlx::query(
+            r#"
+SELECT Chore.id
+FROM Chore
+WHERE Chore.name = ?1;
+"#,
+        )
+        .bind(name)
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        match rows.len() {
+            0 => Ok(None),
+            1 => Ok(Some(rows[0].try_get(0)?)),
+            _ => bail!("get_chore_id returned more than one row"),
+        }
+    }
+}
@@ -1,6 +1,23 @@
 use crate::db::*;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use tabled::Tabled;
+
+/// what happens to a ChoreLog rated below the min_rating of its chore
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PoorRatingAction {
+    /// the ChoreLog only counts score_factor times in the scores
+    Partial { score_factor: f64 },
+    /// the ChoreLog doesn't count and the worker has to do the chore again
+    Redo,
+}
+
+/// an opt-in rule making ratings matter for a chore
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChoreRatingRule {
+    pub min_rating: f64,
+    pub action: PoorRatingAction,
+}
 
 impl Db {
     /// Create a new chore.
@@ -26,12 +43,12 @@ INSERT INTO Chore VALUES
         .rows_affected();
         self.integrity_check().await?;
         if affected_rows != 1 {
-            bail!("affected {} rows", affected_rows);
+            bail!(self.locale.affected_rows(affected_rows));
         }
 
         let plan_update = self.update_plan(fmt_replan_cmd).await?;
         // only list plan separately when not already done in update
-        match plan_update.mono_msg.is_empty() {
+        match plan_update.is_empty() {
             true => Ok(self.list_plan(None).await? + plan_update),
             false => Ok(plan_update),
         }
@@ -63,14 +80,133 @@ SET active = ?1
         .rows_affected();
         self.integrity_check().await?;
         if affected_rows != 1 {
-            bail!("affected {} rows", affected_rows);
+            bail!(self.locale.affected_rows(affected_rows));
         }
 
         let plan_update = self.update_plan(fmt_replan_cmd).await?;
         // only list plan separately when not already done in update
-        match plan_update.mono_msg.is_empty() {
+        match plan_update.is_empty() {
             true => Ok(self.list_plan(None).await? + plan_update),
             false => Ok(plan_update),
         }
     }
+
+    /// Print a nice list of all ChoreRatingRules.
+    pub async fn list_chore_rating_rules(&mut self) -> Result<ReplyMsg> {
+        #[derive(Tabled)]
+        struct ChoreRatingRuleRow {
+            chore: String,
+            min_rating: String,
+            action: String,
+            since: Week,
+        }
+        let sql_rows = sqlx::query(
+            r#"
+SELECT Chore.name, ChoreRatingRule.min_rating, ChoreRatingRule.action, ChoreRatingRule.score_factor, ChoreRatingRule.week
+FROM ChoreRatingRule
+JOIN Chore
+    ON Chore.id = ChoreRatingRule.chore_id
+ORDER BY Chore.id;
+"#,
+        )
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        let rows = sql_rows
+            .into_iter()
+            .map(|r| {
+                let action: String = r.try_get(2)?;
+                let score_factor: f64 = r.try_get(3)?;
+                Ok(ChoreRatingRuleRow {
+                    chore: r.try_get(0)?,
+                    min_rating: format!("{:.2}", r.try_get::<f64, usize>(1)?),
+                    action: self.locale.poor_rating_action(match action.as_str() {
+                        "redo" => PoorRatingAction::Redo,
+                        _ => PoorRatingAction::Partial { score_factor },
+                    }),
+                    since: Week::from_db(r.try_get(4)?),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ReplyMsg::from_blocks(vec![
+            Block::heading(1, self.locale.heading(Heading::RatingRules)),
+            Block::table_with_header(self.locale.table_header(TableHeader::RatingRules), rows),
+        ]))
+    }
+
+    /// Make ChoreLogs of a chore rated below a minimum either count only partially or be redone.
+    /// Remove the rule when rule is None.
+    ///
+    /// Only ChoreLogs from this week on are affected.
+    pub async fn set_chore_rating_rule(
+        &mut self,
+        chore: &str,
+        rule: Option<ChoreRatingRule>,
+    ) -> Result<ReplyMsg> {
+        let chore_id: Option<i64> = sqlx::query(
+            r#"
+SELECT Chore.id
+FROM Chore
+    WHERE Chore.name = ?1;
+"#,
+        )
+        .bind(chore)
+        .fetch_optional(&mut self.con)
+        .await?
+        .map(|r| r.try_get(0))
+        .transpose()?;
+        self.integrity_check().await?;
+        let Some(chore_id) = chore_id else {
+            bail!(self.locale.chore_doesnt_exist(chore));
+        };
+
+        let affected_rows = match rule {
+            Some(rule) => {
+                let max_rating = self.locale.rating_options().len() as f64;
+                if !(1.0..=max_rating).contains(&rule.min_rating) {
+                    bail!(self.locale.min_rating_out_of_range(max_rating));
+                }
+                let (action, score_factor) = match rule.action {
+                    PoorRatingAction::Partial { score_factor } => {
+                        if !(0.0..1.0).contains(&score_factor) {
+                            bail!(self.locale.score_factor_out_of_range());
+                        }
+                        ("partial", score_factor)
+                    }
+                    PoorRatingAction::Redo => ("redo", 0.0),
+                };
+                sqlx::query(
+                    r#"
+REPLACE INTO ChoreRatingRule VALUES (?1, ?2, ?3, ?4, ?5);
+"#,
+                )
+                .bind(chore_id)
+                .bind(rule.min_rating)
+                .bind(action)
+                .bind(score_factor)
+                .bind(self.get_week_internal().await.db_week())
+                .execute(&mut self.con)
+                .await?
+                .rows_affected()
+            }
+            None => sqlx::query(
+                r#"
+DELETE FROM ChoreRatingRule
+WHERE ChoreRatingRule.chore_id = ?1;
+"#,
+            )
+            .bind(chore_id)
+            .execute(&mut self.con)
+            .await?
+            .rows_affected(),
+        };
+        self.integrity_check().await?;
+        if affected_rows != 1 {
+            bail!(self
+                .locale
+                .affected_table_rows(affected_rows, "ChoreRatingRule"));
+        }
+        Ok(self.list_chore_rating_rules().await? + self.list_tenants().await?)
+    }
 }
@@ -0,0 +1,75 @@
+use crate::db::*;
+
+use anyhow::Result;
+use std::collections::HashSet;
+
+impl Db {
+    /// Get all tenants that already do as many chores in a week as their TenantChoreLimit allows.
+    ///
+    /// The ChoreLog of the chore in question isn't counted; it's the one being (re)planned.
+    pub async fn get_limited_tenants(
+        &mut self,
+        week: Week,
+        chore: &str,
+    ) -> Result<HashSet<String>> {
+        let sql_rows = sqlx::query(
+            r#"
+SELECT Tenant.name
+FROM Tenant
+JOIN TenantChoreLimit
+    ON Tenant.id = TenantChoreLimit.tenant_id
+JOIN ChoreLog
+    ON Tenant.id = ChoreLog.worker
+    AND ChoreLog.week = ?1
+    AND ChoreLog.chore_id != (SELECT Chore.id FROM Chore WHERE Chore.name = ?2)
+GROUP BY Tenant.id, Tenant.name, TenantChoreLimit.max_chores_per_week
+HAVING COUNT(ChoreLog.chore_id) >= TenantChoreLimit.max_chores_per_week;
+"#,
+        )
+        .bind(week.db_week())
+        .bind(chore)
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        sql_rows
+            .into_iter()
+            .map(|r| -> Result<String> { Ok(r.try_get(0)?) })
+            .collect()
+    }
+
+    /// Get all tenants that did or will do a chore too close to a week according to their
+    /// TenantCoolDown for that chore.
+    pub async fn get_cooling_down_tenants(
+        &mut self,
+        week: Week,
+        chore: &str,
+    ) -> Result<HashSet<String>> {
+        let sql_rows = sqlx::query(
+            r#"
+SELECT Tenant.name
+FROM Tenant
+JOIN TenantCoolDown
+    ON Tenant.id = TenantCoolDown.tenant_id
+JOIN Chore
+    ON Chore.id = TenantCoolDown.chore_id
+JOIN ChoreLog
+    ON Tenant.id = ChoreLog.worker
+    AND Chore.id = ChoreLog.chore_id
+    AND ChoreLog.week != ?1
+    AND ChoreLog.week > ?1 - TenantCoolDown.weeks
+    AND ChoreLog.week < ?1 + TenantCoolDown.weeks
+WHERE Chore.name = ?2
+GROUP BY Tenant.id, Tenant.name;
+"#,
+        )
+        .bind(week.db_week())
+        .bind(chore)
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        sql_rows
+            .into_iter()
+            .map(|r| -> Result<String> { Ok(r.try_get(0)?) })
+            .collect()
+    }
+}
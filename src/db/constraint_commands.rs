@@ -0,0 +1,166 @@
+use crate::db::*;
+
+use anyhow::{bail, Result};
+use tabled::Tabled;
+
+impl Db {
+    /// Print a nice list of all tenant constraints.
+    pub async fn list_constraints(&mut self) -> Result<ReplyMsg> {
+        #[derive(Tabled)]
+        struct ConstraintRow {
+            tenant: String,
+            chore: String,
+            constraint: String,
+        }
+        let sql_rows = sqlx::query(
+            r#"
+SELECT Tenant.name, NULL, TenantChoreLimit.max_chores_per_week, NULL
+FROM TenantChoreLimit
+JOIN Tenant
+    ON Tenant.id = TenantChoreLimit.tenant_id
+UNION ALL
+SELECT Tenant.name, Chore.name, NULL, TenantCoolDown.weeks
+FROM TenantCoolDown
+JOIN Tenant
+    ON Tenant.id = TenantCoolDown.tenant_id
+JOIN Chore
+    ON Chore.id = TenantCoolDown.chore_id
+ORDER BY 1, 2;
+"#,
+        )
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        let rows = sql_rows
+            .into_iter()
+            .map(|r| {
+                let max_chores_per_week: Option<u32> = r.try_get(2)?;
+                let cool_down_weeks: Option<u32> = r.try_get(3)?;
+                Ok(ConstraintRow {
+                    tenant: r.try_get(0)?,
+                    chore: r.try_get::<Option<String>, usize>(1)?.unwrap_or_default(),
+                    constraint: match (max_chores_per_week, cool_down_weeks) {
+                        (Some(max), _) => self.locale.max_chores_constraint(max),
+                        (_, Some(weeks)) => self.locale.cool_down_constraint(weeks),
+                        (None, None) => String::new(),
+                    },
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ReplyMsg::from_blocks(vec![
+            Block::heading(1, self.locale.heading(Heading::Constraints)),
+            Block::text(
+                &self
+                    .locale
+                    .busy_window(self.get_busy_window_internal().await),
+            ),
+            Block::Blank,
+            Block::table_with_header(self.locale.table_header(TableHeader::Constraints), rows),
+        ]))
+    }
+
+    /// Set how many weeks before and after a week a tenant's chores make them busy.
+    pub async fn set_busy_window(&mut self, weeks: u32) -> Result<ReplyMsg> {
+        self.set_busy_window_internal(weeks).await;
+        self.list_constraints().await
+    }
+
+    /// Limit how many chores a tenant may do in the same week.
+    /// Remove the limit when max_chores_per_week is None.
+    pub async fn set_chore_limit(
+        &mut self,
+        tenant: &str,
+        max_chores_per_week: Option<u32>,
+    ) -> Result<ReplyMsg> {
+        let tenant = Self::capitalize_tenant_name(tenant);
+        if self.get_tenant_id(&tenant).await?.is_none() {
+            bail!(self.locale.tenant_doesnt_exist(&tenant));
+        }
+        let affected_rows = match max_chores_per_week {
+            Some(max_chores_per_week) => sqlx::query(
+                r#"
+REPLACE INTO TenantChoreLimit VALUES
+    ((SELECT Tenant.id FROM Tenant WHERE Tenant.name = ?1), ?2);
+"#,
+            )
+            .bind(&tenant)
+            .bind(max_chores_per_week)
+            .execute(&mut self.con)
+            .await?
+            .rows_affected(),
+            None => sqlx::query(
+                r#"
+DELETE FROM TenantChoreLimit
+WHERE TenantChoreLimit.tenant_id = (SELECT Tenant.id FROM Tenant WHERE Tenant.name = ?1);
+"#,
+            )
+            .bind(&tenant)
+            .execute(&mut self.con)
+            .await?
+            .rows_affected(),
+        };
+        self.integrity_check().await?;
+        // removing a limit that doesn't exist is fine
+        if affected_rows > 1 || (max_chores_per_week.is_some() && affected_rows != 1) {
+            bail!(self
+                .locale
+                .affected_table_rows(affected_rows, "TenantChoreLimit"));
+        }
+        self.list_constraints().await
+    }
+
+    /// Prevent a tenant from doing the same chore again within some weeks.
+    /// Remove the cool-down when weeks is None.
+    pub async fn set_cool_down(
+        &mut self,
+        tenant: &str,
+        chore: &str,
+        weeks: Option<u32>,
+    ) -> Result<ReplyMsg> {
+        let tenant = Self::capitalize_tenant_name(tenant);
+        if self.get_tenant_id(&tenant).await?.is_none() {
+            bail!(self.locale.tenant_doesnt_exist(&tenant));
+        }
+        if self.get_chore_id(chore).await?.is_none() {
+            bail!(self.locale.chore_doesnt_exist(chore));
+        }
+        let affected_rows = match weeks {
+            Some(weeks) => sqlx::query(
+                r#"
+REPLACE INTO TenantCoolDown VALUES (
+    (SELECT Tenant.id FROM Tenant WHERE Tenant.name = ?1),
+    (SELECT Chore.id FROM Chore WHERE Chore.name = ?2),
+    ?3
+);
+"#,
+            )
+            .bind(&tenant)
+            .bind(chore)
+            .bind(weeks)
+            .execute(&mut self.con)
+            .await?
+            .rows_affected(),
+            None => sqlx::query(
+                r#"
+DELETE FROM TenantCoolDown
+WHERE TenantCoolDown.tenant_id = (SELECT Tenant.id FROM Tenant WHERE Tenant.name = ?1)
+AND TenantCoolDown.chore_id = (SELECT Chore.id FROM Chore WHERE Chore.name = ?2);
+"#,
+            )
+            .bind(&tenant)
+            .bind(chore)
+            .execute(&mut self.con)
+            .await?
+            .rows_affected(),
+        };
+        self.integrity_check().await?;
+        // removing a cool-down that doesn't exist is fine
+        if affected_rows > 1 || (weeks.is_some() && affected_rows != 1) {
+            bail!(self
+                .locale
+                .affected_table_rows(affected_rows, "TenantCoolDown"));
+        }
+        self.list_constraints().await
+    }
+}
@@ -1,20 +1,23 @@
 use crate::db::*;
 
 use anyhow::Result;
-use tabled::{
-    settings::{object::Segment, Alignment, Settings},
-    Table, Tabled,
-};
+use serde::Serialize;
+use tabled::Tabled;
+
+/// an exemption reason
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Exemption {
+    pub reason: String,
+    /// the active chores tenants with this exemption don't need to do
+    pub chores: Vec<String>,
+    /// the tenants currently granted this exemption
+    pub tenants: Vec<String>,
+}
 
 impl Db {
-    /// Print a nice list of all exemptions.
-    pub async fn list_exemptions(&mut self) -> Result<ReplyMsg> {
-        #[derive(Tabled)]
-        struct ExemptionRow {
-            reason: String,
-            chores: String,
-            tenants: String,
-        }
+    /// Get all exemption reasons with the active chores they're for and the tenants currently
+    /// granted them.
+    pub async fn get_exemptions(&mut self) -> Result<Vec<Exemption>> {
         let sql_rows = sqlx::query(
             r#"
 SELECT ExemptionReason.reason, Chores.chores, Tenants.tenants
@@ -42,24 +45,46 @@ LEFT JOIN (
         .fetch_all(&mut self.con)
         .await?;
         self.integrity_check().await?;
-        let rows = sql_rows
+        sql_rows
             .into_iter()
             .map(|r| {
-                Ok(ExemptionRow {
+                let split = |names: Option<String>| -> Vec<String> {
+                    names
+                        .map(|names| names.split('\n').map(String::from).collect())
+                        .unwrap_or_default()
+                };
+                Ok(Exemption {
                     reason: r.try_get(0)?,
-                    chores: r.try_get(1)?,
-                    tenants: r.try_get(2)?,
+                    chores: split(r.try_get(1)?),
+                    tenants: split(r.try_get(2)?),
                 })
             })
-            .collect::<Result<Vec<_>>>()?;
+            .collect()
+    }
 
-        Ok(ReplyMsg::from_mono(&format!(
-            "# Exemptions\n\n{}",
-            Table::new(rows).modify(
-                Segment::all(),
-                Settings::new(Alignment::center(), Alignment::center())
-            )
-        )))
+    /// Print a nice list of all exemptions.
+    pub async fn list_exemptions(&mut self) -> Result<ReplyMsg> {
+        #[derive(Tabled)]
+        struct ExemptionRow {
+            reason: String,
+            chores: String,
+            tenants: String,
+        }
+        let rows = self
+            .get_exemptions()
+            .await?
+            .into_iter()
+            .map(|e| ExemptionRow {
+                reason: e.reason,
+                chores: e.chores.join("\n"),
+                tenants: e.tenants.join("\n"),
+            });
+
+        Ok(ReplyMsg::from_blocks(vec![
+            Block::heading(1, self.locale.heading(Heading::Exemptions)),
+            Block::Blank,
+            Block::table_with_header(self.locale.table_header(TableHeader::Exemptions), rows),
+        ]))
     }
 
     /// Create a new exemption reason without granting it to anyone.
@@ -82,7 +107,9 @@ INSERT INTO ExemptionReason VALUES
         self.integrity_check().await?;
         let reason_id = res.last_insert_rowid();
         if res.rows_affected() != 1 {
-            bail!("affected {} ExemptionReason rows", res.rows_affected());
+            bail!(self
+                .locale
+                .affected_table_rows(res.rows_affected(), "ExemptionReason"));
         }
 
         // apply Chores to ExemptionReason
@@ -100,7 +127,9 @@ INSERT INTO ChoreExemption VALUES
             .rows_affected();
             self.integrity_check().await?;
             if affected_rows != 1 {
-                bail!("affected {} ChoreExemption rows", affected_rows);
+                bail!(self
+                    .locale
+                    .affected_table_rows(affected_rows, "ChoreExemption"));
             }
         }
         self.list_exemptions().await
@@ -128,7 +157,7 @@ WHERE ExemptionReason.reason = ?1;
         .await?
         .is_empty()
         {
-            bail!("the ExemptionReason {} doesn't exist", reason);
+            bail!(self.locale.exemption_reason_doesnt_exist(reason));
         }
 
         // delete all current ChoreExemptions
@@ -169,7 +198,9 @@ INSERT INTO ChoreExemption VALUES
             .rows_affected();
             self.integrity_check().await?;
             if affected_rows != 1 {
-                bail!("affected {} ChoreExemption rows", affected_rows);
+                bail!(self
+                    .locale
+                    .affected_table_rows(affected_rows, "ChoreExemption"));
             }
         }
         Ok(self.list_exemptions().await? + self.update_plan(fmt_replan_cmd).await?)
@@ -187,7 +218,7 @@ INSERT INTO ChoreExemption VALUES
     {
         let tenant = Self::capitalize_tenant_name(tenant);
         if self.is_tenant_exempt(reason, &tenant).await? {
-            bail!("tenant is already exempt");
+            bail!(self.locale.tenant_already_exempt());
         }
         let affected_rows = sqlx::query(
             r#"
@@ -211,7 +242,9 @@ INSERT INTO TenantExemption VALUES
         .rows_affected();
         self.integrity_check().await?;
         if affected_rows != 1 {
-            bail!("affected {} TenantExemption rows", affected_rows);
+            bail!(self
+                .locale
+                .affected_table_rows(affected_rows, "TenantExemption"));
         }
 
         Ok(self.list_exemptions().await? + self.update_plan(fmt_replan_cmd).await?)
@@ -229,7 +262,7 @@ INSERT INTO TenantExemption VALUES
     {
         let tenant = Self::capitalize_tenant_name(tenant);
         if !self.is_tenant_exempt(reason, &tenant).await? {
-            bail!("tenant is not exempt");
+            bail!(self.locale.tenant_not_exempt());
         }
         let affected_rows = sqlx::query(
             r#"
@@ -259,7 +292,9 @@ SET end_week = ?1
         .rows_affected();
         self.integrity_check().await?;
         if affected_rows != 1 {
-            bail!("affected {} TenantExemption rows", affected_rows);
+            bail!(self
+                .locale
+                .affected_table_rows(affected_rows, "TenantExemption"));
         }
         Ok(self.list_exemptions().await? + self.update_plan(fmt_replan_cmd).await?)
     }
@@ -0,0 +1,230 @@
+use crate::{bot::Attachment, csv::to_csv, db::*};
+
+use anyhow::Result;
+use serde::Serialize;
+use tabled::Tabled;
+
+/// a ChoreLog as exported
+#[derive(Serialize, Tabled, Debug, PartialEq)]
+pub struct ExportedChoreLog {
+    pub week: Week,
+    pub chore: String,
+    pub worker: String,
+    /// whether the rating poll has been stopped
+    pub completed: bool,
+}
+
+/// a single Rating as exported
+#[derive(Serialize, Tabled, Debug, PartialEq)]
+pub struct ExportedRating {
+    pub week: Week,
+    pub chore: String,
+    pub rating: u32,
+}
+
+/// a LivesIn as exported
+#[derive(Serialize, Tabled, Debug, PartialEq)]
+pub struct ExportedLivesIn {
+    pub tenant: String,
+    pub room: String,
+    pub move_in_week: Week,
+    #[tabled(display_with = "display_opt_week")]
+    pub move_out_week: Option<Week>,
+}
+
+/// a TenantExemption as exported
+#[derive(Serialize, Tabled, Debug, PartialEq)]
+pub struct ExportedExemption {
+    pub tenant: String,
+    pub reason: String,
+    pub start_week: Week,
+    #[tabled(display_with = "display_opt_week")]
+    pub end_week: Option<Week>,
+}
+
+/// a TenantScore as exported
+#[derive(Serialize, Tabled, Debug, PartialEq)]
+pub struct ExportedScore {
+    pub tenant: String,
+    pub chore: String,
+    pub score: f64,
+}
+
+/// the entire history of the household
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Export {
+    pub chore_logs: Vec<ExportedChoreLog>,
+    pub ratings: Vec<ExportedRating>,
+    pub lives_in: Vec<ExportedLivesIn>,
+    pub exemptions: Vec<ExportedExemption>,
+    pub scores: Vec<ExportedScore>,
+}
+
+fn display_opt_week(week: &Option<Week>) -> String {
+    week.map(|w| w.to_string()).unwrap_or_default()
+}
+
+impl Db {
+    /// Get everything that happened in the household.
+    pub async fn get_export(&mut self) -> Result<Export> {
+        let chore_log_rows = sqlx::query(
+            r#"
+SELECT ChoreLog.week, Chore.name, Tenant.name, ChoreLog.completed
+FROM ChoreLog
+JOIN Chore ON Chore.id = ChoreLog.chore_id
+JOIN Tenant ON Tenant.id = ChoreLog.worker
+ORDER BY ChoreLog.week, Chore.id;
+"#,
+        )
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        let chore_logs = chore_log_rows
+            .into_iter()
+            .map(|r| {
+                Ok(ExportedChoreLog {
+                    week: Week::from_db(r.try_get(0)?),
+                    chore: r.try_get(1)?,
+                    worker: r.try_get(2)?,
+                    completed: r.try_get(3)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let rating_rows = sqlx::query(
+            r#"
+SELECT Rating.week, Chore.name, Rating.rating
+FROM Rating
+JOIN Chore ON Chore.id = Rating.for_chore_log_chore_id
+ORDER BY Rating.week, Chore.id, Rating.id;
+"#,
+        )
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        let ratings = rating_rows
+            .into_iter()
+            .map(|r| {
+                Ok(ExportedRating {
+                    week: Week::from_db(r.try_get(0)?),
+                    chore: r.try_get(1)?,
+                    rating: r.try_get(2)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let lives_in_rows = sqlx::query(
+            r#"
+SELECT Tenant.name, LivesIn.room_name, LivesIn.move_in_week, LivesIn.move_out_week
+FROM LivesIn
+JOIN Tenant ON Tenant.id = LivesIn.tenant_id
+ORDER BY LivesIn.move_in_week, LivesIn.room_name;
+"#,
+        )
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        let lives_in = lives_in_rows
+            .into_iter()
+            .map(|r| {
+                Ok(ExportedLivesIn {
+                    tenant: r.try_get(0)?,
+                    room: r.try_get(1)?,
+                    move_in_week: Week::from_db(r.try_get(2)?),
+                    move_out_week: r.try_get::<Option<i64>, usize>(3)?.map(Week::from_db),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let exemption_rows = sqlx::query(
+            r#"
+SELECT Tenant.name, ExemptionReason.reason, TenantExemption.start_week, TenantExemption.end_week
+FROM TenantExemption
+JOIN Tenant ON Tenant.id = TenantExemption.tenant_id
+JOIN ExemptionReason ON ExemptionReason.id = TenantExemption.exemption_reason_id
+ORDER BY TenantExemption.start_week, Tenant.name;
+"#,
+        )
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        let exemptions = exemption_rows
+            .into_iter()
+            .map(|r| {
+                Ok(ExportedExemption {
+                    tenant: r.try_get(0)?,
+                    reason: r.try_get(1)?,
+                    start_week: Week::from_db(r.try_get(2)?),
+                    end_week: r.try_get::<Option<i64>, usize>(3)?.map(Week::from_db),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let score_rows = sqlx::query(
+            r#"
+SELECT Tenant.name, Chore.name, CAST(TenantScore.score AS REAL)
+FROM TenantScore
+JOIN Tenant ON Tenant.id = TenantScore.tenant_id
+JOIN Chore ON Chore.id = TenantScore.chore_id
+ORDER BY Tenant.name, Chore.id;
+"#,
+        )
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        let scores = score_rows
+            .into_iter()
+            .map(|r| {
+                Ok(ExportedScore {
+                    tenant: r.try_get(0)?,
+                    chore: r.try_get(1)?,
+                    score: r.try_get(2)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Export {
+            chore_logs,
+            ratings,
+            lives_in,
+            exemptions,
+            scores,
+        })
+    }
+
+    /// Export the household's history as one CSV file per table.
+    pub async fn export_csv(&mut self) -> Result<ReplyMsg> {
+        let export = self.get_export().await?;
+        let mut msg = self.describe_export(&export);
+        msg.attachments = vec![
+            Attachment::new("chore_logs.csv", to_csv(&export.chore_logs)),
+            Attachment::new("ratings.csv", to_csv(&export.ratings)),
+            Attachment::new("lives_in.csv", to_csv(&export.lives_in)),
+            Attachment::new("exemptions.csv", to_csv(&export.exemptions)),
+            Attachment::new("scores.csv", to_csv(&export.scores)),
+        ];
+        Ok(msg)
+    }
+
+    /// Export the household's history as a single JSON file.
+    pub async fn export_json(&mut self) -> Result<ReplyMsg> {
+        let export = self.get_export().await?;
+        let mut msg = self.describe_export(&export);
+        msg.attachments = vec![Attachment::new(
+            "chore_planner_export.json",
+            serde_json::to_string_pretty(&export)?,
+        )];
+        Ok(msg)
+    }
+
+    /// Summarize what an export contains.
+    fn describe_export(&self, export: &Export) -> ReplyMsg {
+        ReplyMsg::from_text(&self.locale.exported(
+            export.chore_logs.len(),
+            export.ratings.len(),
+            export.lives_in.len(),
+            export.exemptions.len(),
+            export.scores.len(),
+        ))
+    }
+}
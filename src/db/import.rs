@@ -0,0 +1,271 @@
+use crate::{csv::parse_csv, db::*};
+
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+
+/// Get a field of a parsed CSV record, failing when the column is missing or the field is empty.
+/// The error is written in the locale.
+fn required<'a>(
+    record: &'a HashMap<String, String>,
+    column: &str,
+    locale: Locale,
+) -> Result<&'a str> {
+    match record.get(column).map(|f| f.trim()) {
+        Some(field) if !field.is_empty() => Ok(field),
+        Some(_) => bail!(locale.import_column_empty(column)),
+        None => bail!(locale.import_column_missing(column)),
+    }
+}
+
+/// Get a field of a parsed CSV record, the column may be missing and the field empty.
+fn optional<'a>(record: &'a HashMap<String, String>, column: &str) -> Option<&'a str> {
+    record
+        .get(column)
+        .map(|f| f.trim())
+        .filter(|f| !f.is_empty())
+}
+
+impl Db {
+    /// Import the history of a flat, i.e., when moving from a paper chore chart.
+    ///
+    /// lives_in_csv has the columns tenant, room, move_in_week and optionally move_out_week and
+    /// chat_tag. Missing tenants and rooms are created.
+    /// chore_logs_csv has the columns week, chore and worker. The chores need to exist already
+    /// and only weeks before the current week may be imported. Those ChoreLogs are completed
+    /// without a rating poll.
+    /// Both use the format of the export, so other columns are ignored.
+    ///
+    /// Everything is imported in a single transaction, nothing is changed when any row fails.
+    pub async fn import_history(
+        &mut self,
+        lives_in_csv: Option<&str>,
+        chore_logs_csv: Option<&str>,
+    ) -> Result<ReplyMsg> {
+        sqlx::query("BEGIN TRANSACTION;")
+            .execute(&mut self.con)
+            .await?;
+        match self
+            .import_history_internal(lives_in_csv, chore_logs_csv)
+            .await
+        {
+            Ok(msg) => {
+                sqlx::query("COMMIT;").execute(&mut self.con).await?;
+                Ok(msg)
+            }
+            Err(e) => {
+                sqlx::query("ROLLBACK;").execute(&mut self.con).await?;
+                Err(e.context(self.locale.import_nothing_imported()))
+            }
+        }
+    }
+
+    async fn import_history_internal(
+        &mut self,
+        lives_in_csv: Option<&str>,
+        chore_logs_csv: Option<&str>,
+    ) -> Result<ReplyMsg> {
+        let mut imported_lives_in = 0;
+        if let Some(lives_in_csv) = lives_in_csv {
+            for (i, record) in parse_csv(lives_in_csv)?.iter().enumerate() {
+                self.import_lives_in(record)
+                    .await
+                    .with_context(|| self.locale.import_lives_in_failed(i + 1))?;
+                imported_lives_in += 1;
+            }
+        }
+
+        let mut imported_chore_logs = 0;
+        if let Some(chore_logs_csv) = chore_logs_csv {
+            let current_week = self.get_week_internal().await;
+            for (i, record) in parse_csv(chore_logs_csv)?.iter().enumerate() {
+                self.import_chore_log(record, current_week)
+                    .await
+                    .with_context(|| self.locale.import_chore_log_failed(i + 1))?;
+                imported_chore_logs += 1;
+            }
+        }
+
+        self.integrity_check().await?;
+        Ok(ReplyMsg::from_text(
+            &self.locale.imported(imported_lives_in, imported_chore_logs),
+        ))
+    }
+
+    /// Import a single LivesIn, creating its Tenant and Room if needed.
+    async fn import_lives_in(&mut self, record: &HashMap<String, String>) -> Result<()> {
+        let tenant = Self::capitalize_tenant_name(required(record, "tenant", self.locale)?);
+        let room = required(record, "room", self.locale)?;
+        let move_in_week: Week = required(record, "move_in_week", self.locale)?.parse()?;
+        let move_out_week = optional(record, "move_out_week")
+            .map(|w| w.parse::<Week>())
+            .transpose()?;
+        let chat_tag = optional(record, "chat_tag");
+        if let Some(move_out_week) = move_out_week {
+            if move_out_week.db_week() <= move_in_week.db_week() {
+                bail!(self
+                    .locale
+                    .import_move_out_not_after_move_in(move_in_week, move_out_week));
+            }
+        }
+
+        // The rows imported before are part of the transaction, so they are checked as well.
+        let overlapping = sqlx::query(
+            r#"
+SELECT Tenant.name, LivesIn.room_name
+FROM LivesIn
+JOIN Tenant ON Tenant.id = LivesIn.tenant_id
+WHERE (LivesIn.room_name = ?1 OR Tenant.name = ?2)
+    -- the other LivesIn starts before this one ends
+    AND (?4 IS NULL OR LivesIn.move_in_week < ?4)
+    -- the other LivesIn ends after this one starts
+    AND (LivesIn.move_out_week IS NULL OR LivesIn.move_out_week > ?3)
+ORDER BY LivesIn.move_in_week
+LIMIT 1;
+"#,
+        )
+        .bind(room)
+        .bind(&tenant)
+        .bind(move_in_week.db_week())
+        .bind(move_out_week.map(|w| w.db_week()))
+        .fetch_optional(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        if let Some(row) = overlapping {
+            let other_tenant: String = row.try_get(0)?;
+            let other_room: String = row.try_get(1)?;
+            match other_tenant == tenant {
+                true => bail!(self
+                    .locale
+                    .import_tenant_lived_elsewhere(&tenant, &other_room)),
+                false => bail!(self.locale.import_room_taken(&other_room, &other_tenant)),
+            }
+        }
+
+        sqlx::query(
+            r#"
+INSERT OR IGNORE INTO Room VALUES (?1);
+"#,
+        )
+        .bind(room)
+        .execute(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+
+        // Keep the chat tag of existing tenants.
+        sqlx::query(
+            r#"
+INSERT OR IGNORE INTO Tenant VALUES (NULL, ?1, ?2);
+"#,
+        )
+        .bind(&tenant)
+        .bind(chat_tag)
+        .execute(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+
+        let affected_rows = sqlx::query(
+            r#"
+INSERT INTO LivesIn VALUES ((SELECT Tenant.id FROM Tenant WHERE Tenant.name = ?1), ?2, ?3, ?4);
+"#,
+        )
+        .bind(&tenant)
+        .bind(room)
+        .bind(move_in_week.db_week())
+        .bind(move_out_week.map(|w| w.db_week()))
+        .execute(&mut self.con)
+        .await?
+        .rows_affected();
+        self.integrity_check().await?;
+        if affected_rows != 1 {
+            bail!(self.locale.affected_rows(affected_rows));
+        }
+        Ok(())
+    }
+
+    /// Import a single ChoreLog of a week before the current week.
+    ///
+    /// The history isn't rated anymore, so the ChoreLog is completed without a rating poll.
+    async fn import_chore_log(
+        &mut self,
+        record: &HashMap<String, String>,
+        current_week: Week,
+    ) -> Result<()> {
+        let week: Week = required(record, "week", self.locale)?.parse()?;
+        let chore = required(record, "chore", self.locale)?;
+        let worker = Self::capitalize_tenant_name(required(record, "worker", self.locale)?);
+        if week.db_week() >= current_week.db_week() {
+            bail!(self.locale.import_not_in_past(current_week, week));
+        }
+
+        let chore_id: Option<i64> = sqlx::query(
+            r#"
+SELECT Chore.id
+FROM Chore
+    WHERE Chore.name = ?1;
+"#,
+        )
+        .bind(chore)
+        .fetch_optional(&mut self.con)
+        .await?
+        .map(|r| r.try_get(0))
+        .transpose()?;
+        self.integrity_check().await?;
+        let Some(chore_id) = chore_id else {
+            bail!(self.locale.import_chore_doesnt_exist(chore));
+        };
+
+        let worker_id: Option<i64> = sqlx::query(
+            r#"
+SELECT Tenant.id
+FROM Tenant
+    WHERE Tenant.name = ?1;
+"#,
+        )
+        .bind(&worker)
+        .fetch_optional(&mut self.con)
+        .await?
+        .map(|r| r.try_get(0))
+        .transpose()?;
+        self.integrity_check().await?;
+        let Some(worker_id) = worker_id else {
+            bail!(self.locale.tenant_doesnt_exist(&worker));
+        };
+
+        // Work of tenants not living in the flat doesn't count towards their score.
+        let lived_in = sqlx::query(
+            r#"
+SELECT *
+FROM LivesIn
+    WHERE LivesIn.tenant_id = ?1
+    AND LivesIn.move_in_week <= ?2
+    AND (LivesIn.move_out_week IS NULL OR LivesIn.move_out_week > ?2);
+"#,
+        )
+        .bind(worker_id)
+        .bind(week.db_week())
+        .fetch_optional(&mut self.con)
+        .await?
+        .is_some();
+        self.integrity_check().await?;
+        if !lived_in {
+            bail!(self.locale.import_didnt_live_here(&worker, week));
+        }
+
+        let affected_rows = sqlx::query(
+            r#"
+INSERT INTO ChoreLog VALUES (?1, ?2, ?3, 1, NULL);
+"#,
+        )
+        .bind(chore_id)
+        .bind(week.db_week())
+        .bind(worker_id)
+        .execute(&mut self.con)
+        .await?
+        .rows_affected();
+        self.integrity_check().await?;
+        if affected_rows != 1 {
+            bail!(self.locale.affected_rows(affected_rows));
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,19 @@
-use crate::db::*;
+use crate::db::{
+    settings_commands::{ScoreDecay, StartScorePolicy},
+    *,
+};
+use std::fmt::Display;
+
+// Keys of the KeyValue table added after its migration:
+// busy_window the amount of weeks before and after a week a tenant's chores make them busy (default 1)
+// gamma overrides the gamma the chore_planner has been started with
+// weeks_to_plan overrides the weeks_to_plan the chore_planner has been started with
+// try_exclude_busy_tenants overrides the setting the chore_planner has been started with
+// locale overrides the locale the chore_planner has been started with
+// score_decay how old ChoreLogs lose their weight in the TenantScore: none, half-life or window (default none)
+// score_decay_weeks the half-life or the window in weeks
+// score_decay_factor the weight a week retains compared to the next week, 0.5^(1/half-life) for half-life, else 1
+// start_score the StartScore of new tenants: zero, mean or room (default zero)
 
 impl Db {
     pub async fn set_week_internal(&mut self, week: Week) {
@@ -13,6 +28,141 @@ impl Db {
         }
     }
 
+    pub async fn set_busy_window_internal(&mut self, busy_window: u32) {
+        self.set_kv("busy_window", &busy_window.to_string()).await
+    }
+
+    pub async fn get_busy_window_internal(&mut self) -> u32 {
+        self.get_parsed_kv("busy_window").await.unwrap_or(1)
+    }
+
+    pub async fn set_gamma_internal(&mut self, gamma: f64) {
+        self.set_kv("gamma", &gamma.to_string()).await
+    }
+
+    /// Return None when gamma hasn't been stored in the database.
+    pub async fn get_gamma_internal(&mut self) -> Option<f64> {
+        self.get_parsed_kv("gamma").await
+    }
+
+    pub async fn set_weeks_to_plan_internal(&mut self, weeks_to_plan: u32) {
+        self.set_kv("weeks_to_plan", &weeks_to_plan.to_string())
+            .await
+    }
+
+    /// Return None when weeks_to_plan hasn't been stored in the database.
+    pub async fn get_weeks_to_plan_internal(&mut self) -> Option<u32> {
+        self.get_parsed_kv("weeks_to_plan").await
+    }
+
+    pub async fn set_try_exclude_busy_tenants_internal(&mut self, try_exclude_busy_tenants: bool) {
+        self.set_kv(
+            "try_exclude_busy_tenants",
+            &try_exclude_busy_tenants.to_string(),
+        )
+        .await
+    }
+
+    /// Return None when try_exclude_busy_tenants hasn't been stored in the database.
+    pub async fn get_try_exclude_busy_tenants_internal(&mut self) -> Option<bool> {
+        self.get_parsed_kv("try_exclude_busy_tenants").await
+    }
+
+    pub async fn set_locale_internal(&mut self, locale: Locale) {
+        self.set_kv("locale", &locale.to_string()).await
+    }
+
+    /// Return None when the locale hasn't been stored in the database.
+    pub async fn get_locale_internal(&mut self) -> Option<Locale> {
+        self.get_parsed_kv("locale").await
+    }
+
+    /// Store the decay together with the factor the ScoreWeight VIEW needs.
+    /// The ScoreWeight VIEW can't see the fallback_week, so the current week is stored, too.
+    pub async fn set_score_decay_internal(&mut self, score_decay: ScoreDecay) {
+        let week = self.get_week_internal().await;
+        self.set_week_internal(week).await;
+        let (decay, weeks, factor) = match score_decay {
+            ScoreDecay::None => ("none", 0, 1.0),
+            ScoreDecay::HalfLife { weeks } => ("half-life", weeks, 0.5f64.powf(1.0 / weeks as f64)),
+            ScoreDecay::Window { weeks } => ("window", weeks, 1.0),
+        };
+        self.set_kv("score_decay", decay).await;
+        self.set_kv("score_decay_weeks", &weeks.to_string()).await;
+        self.set_kv("score_decay_factor", &factor.to_string()).await;
+    }
+
+    /// An invalid decay is treated like no decay, just like the ScoreWeight VIEW does.
+    pub async fn get_score_decay_internal(&mut self) -> ScoreDecay {
+        let weeks = self
+            .get_parsed_kv::<u32>("score_decay_weeks")
+            .await
+            .filter(|weeks| *weeks >= 1);
+        let factor = self
+            .get_parsed_kv::<f64>("score_decay_factor")
+            .await
+            .filter(|factor| 0.0 < *factor && *factor < 1.0);
+        match (self.get_kv("score_decay").await.as_deref(), weeks) {
+            (None | Some("none"), _) => ScoreDecay::None,
+            (Some("half-life"), Some(weeks)) if factor.is_some() => ScoreDecay::HalfLife { weeks },
+            (Some("window"), Some(weeks)) => ScoreDecay::Window { weeks },
+            (Some(decay), weeks) => {
+                eprintln!(
+                    "ignoring the invalid score decay '{}' over {:?} weeks in the KeyValue table",
+                    decay, weeks
+                );
+                ScoreDecay::None
+            }
+        }
+    }
+
+    pub async fn set_start_score_policy_internal(&mut self, policy: StartScorePolicy) {
+        self.set_kv("start_score", &policy.to_string()).await
+    }
+
+    pub async fn get_start_score_policy_internal(&mut self) -> StartScorePolicy {
+        self.get_parsed_kv("start_score").await.unwrap_or_default()
+    }
+
+    /// Get all keys and their values.
+    pub async fn get_key_values(&mut self) -> Result<Vec<(String, Option<String>)>> {
+        let rows = sqlx::query(
+            r#"
+SELECT KeyValue.key, KeyValue.value
+FROM KeyValue
+ORDER BY KeyValue.key;
+"#,
+        )
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        rows.into_iter()
+            .map(|r| -> Result<(String, Option<String>)> { Ok((r.try_get(0)?, r.try_get(1)?)) })
+            .collect()
+    }
+
+    /// Get the parsed value of a key from the database.
+    ///
+    /// A value that can't be parsed, e.g., because it was edited by hand, is treated like a missing
+    /// one.
+    async fn get_parsed_kv<T>(&mut self, key: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let value = self.get_kv(key).await?;
+        match value.parse::<T>() {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                eprintln!(
+                    "ignoring the invalid value '{}' of {} in the KeyValue table: {}",
+                    value, key, e
+                );
+                None
+            }
+        }
+    }
+
     /// get the value of a key from the database
     /// panics on error
     async fn get_kv(&mut self, key: &str) -> Option<String> {
@@ -1,8 +1,11 @@
-use crate::db::*;
+use crate::{
+    db::*,
+    template::{render_template, Placeholder, TemplateKind},
+};
 
 use anyhow::Result;
 use rand::{distributions::Distribution, distributions::WeightedIndex};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 impl Db {
     /// Remove all future ChoreLogs that aren't valid (anymore), i.e., because someone moved out.
@@ -52,7 +55,7 @@ AND ChoreLog.week = ?2;
                 .rows_affected();
                 self.integrity_check().await?;
                 if affected_rows != 1 {
-                    bail!("affected {} rows", affected_rows);
+                    bail!(self.locale.affected_rows(affected_rows));
                 }
             }
         }
@@ -92,9 +95,12 @@ AND ChoreLog.chore_id IS NULL;
         Ok(weeks_to_plan)
     }
 
-    /// Busy tenants are those already doing a chore the last, this or the next week.
+    /// Busy tenants are those already doing a chore within the busy window around a week.
+    /// With the default busy window of 1 those are the ones doing a chore the last, this or the
+    /// next week.
     /// Get a list of all those.
     async fn get_busy_tenants(&mut self, week: Week) -> Result<HashSet<String>> {
+        let busy_window = self.get_busy_window_internal().await;
         let sql_rows = sqlx::query(
             r#"
 SELECT Tenant.name
@@ -102,12 +108,13 @@ FROM Tenant
 JOIN ProfitingTenant
     ON Tenant.id = ProfitingTenant.tenant_id
     AND ProfitingTenant.did_work = 1
-    AND ProfitingTenant.week >= ?1 - 1
-    AND ProfitingTenant.week <= ?1 + 1
+    AND ProfitingTenant.week >= ?1 - ?2
+    AND ProfitingTenant.week <= ?1 + ?2
 GROUP BY Tenant.id, Tenant.name;
 "#,
         )
         .bind(week.db_week())
+        .bind(busy_window)
         .fetch_all(&mut self.con)
         .await?;
         self.integrity_check().await?;
@@ -122,7 +129,7 @@ GROUP BY Tenant.id, Tenant.name;
     ///
     /// The scores are just the score for the queried chores.
     /// You need to normalize the scores so that they add up to 0.
-    async fn get_all_available_tenants_unnormalized(
+    pub async fn get_all_available_tenants_unnormalized(
         &mut self,
         week: Week,
         chore: &str,
@@ -176,7 +183,11 @@ ORDER BY CAST(TenantScore.score AS REAL) ASC;
     }
 
     /// Return list of (tenant, score) tuples in ascending order of score.
-    /// Busy tenants are excluded if set to do so in the db struct.
+    ///
+    /// Tenants exceeding their TenantChoreLimit are always excluded (hard constraint).
+    /// Busy tenants are excluded if set to do so in the db struct and tenants cooling down from
+    /// this chore are excluded, too (soft constraints).
+    /// A soft constraint is ignored when it would exclude all remaining tenants.
     ///
     /// The scores are just the score for the queried chores.
     /// You need to normalize the scores so that they add up to 0.
@@ -185,11 +196,18 @@ ORDER BY CAST(TenantScore.score AS REAL) ASC;
         week: Week,
         chore: &str,
     ) -> Result<Vec<(String, f64)>> {
+        let limited_tenants = self.get_limited_tenants(week, chore).await?;
+        println!("limited tenants: {:?}", limited_tenants);
         let busy_tenants = self.get_busy_tenants(week).await?;
         println!("busy tenants: {:?}", busy_tenants);
-        let all_available_tenants = self
+        let cooling_down_tenants = self.get_cooling_down_tenants(week, chore).await?;
+        println!("cooling down tenants: {:?}", cooling_down_tenants);
+        let all_available_tenants: Vec<(String, f64)> = self
             .get_all_available_tenants_unnormalized(week, chore)
-            .await?;
+            .await?
+            .into_iter()
+            .filter(|(t, _)| !limited_tenants.contains(t))
+            .collect();
         println!("all available tenants: {:?}", all_available_tenants);
         let non_busy_available_tenants: Vec<(String, f64)> = all_available_tenants
             .clone()
@@ -198,17 +216,22 @@ ORDER BY CAST(TenantScore.score AS REAL) ASC;
             .collect();
 
         if !self.try_exclude_busy_tenants {
-            debug_assert_eq!(
-                self.get_all_available_tenants_unnormalized(week, chore)
-                    .await
-                    .unwrap(),
-                non_busy_available_tenants
-            );
+            debug_assert_eq!(all_available_tenants, non_busy_available_tenants);
         }
 
-        match non_busy_available_tenants.is_empty() {
-            true => Ok(all_available_tenants),
-            false => Ok(non_busy_available_tenants),
+        let available_tenants = match non_busy_available_tenants.is_empty() {
+            true => all_available_tenants,
+            false => non_busy_available_tenants,
+        };
+        let rested_available_tenants: Vec<(String, f64)> = available_tenants
+            .clone()
+            .into_iter()
+            .filter(|(t, _)| !cooling_down_tenants.contains(t))
+            .collect();
+
+        match rested_available_tenants.is_empty() {
+            true => Ok(available_tenants),
+            false => Ok(rested_available_tenants),
         }
     }
 
@@ -241,38 +264,69 @@ ORDER BY CAST(TenantScore.score AS REAL) ASC;
     ///
     /// The mathematical proof can be found in the repo.
     ///
+    /// Afterwards each probability is multiplied with PREFERENCE_FACTOR when the tenant likes the
+    /// chore or divided by it when they dislike it. The result is scaled to add up to 1 again.
+    /// As the multiplier is bounded, the score still pulls every tenant back towards 0: A tenant
+    /// liking a chore does it more often, thus gains score and is chosen less frequently.
+    /// Therefore the long-run fairness is kept intact.
+    ///
     /// The tenants must be ordered in ascending order of score.
     ///
+    /// preferences maps tenants to 1 (likes the chore) or -1 (dislikes the chore)
+    ///
     /// return list of probabilities in same order as tenants
-    pub fn calc_tenant_distribution(&self, tenants: Vec<(String, f64)>) -> Vec<f64> {
+    pub fn calc_tenant_distribution(
+        &self,
+        tenants: Vec<(String, f64)>,
+        preferences: &HashMap<String, i64>,
+    ) -> Vec<f64> {
         let n = tenants.len() as f64;
         const EPS: f64 = 0.0000001;
+        const PREFERENCE_FACTOR: f64 = 1.5;
         if tenants.is_empty() {
             return vec![];
         }
         // highest score
         let xn = tenants[tenants.len() - 1].1;
-        if xn.abs() < EPS {
-            return tenants.into_iter().map(|(_, _)| 1.0 / n).collect();
-        }
-        let dist: Vec<f64> = tenants
+        let dist: Vec<(String, f64)> = tenants
             .into_iter()
-            .map(|(_, score)| {
+            .map(|(tenant, score)| {
+                if xn.abs() < EPS {
+                    return (tenant, 1.0 / n);
+                }
                 // floats are icky, don't return something slightly negative
-                f64::max(
+                let p = f64::max(
                     // the main affine-linear transformation
                     1.0 / n - (1.0 - self.gamma) / (n * xn) * score,
                     0.0,
-                )
+                );
+                (tenant, p)
             })
             .collect();
-
         println!("tenant Distribution: {:?}", dist);
-        let sum: f64 = dist.iter().sum();
-        debug_assert!(dist.iter().all(|p| *p > 0.0 || *p < 1.0));
+        let sum: f64 = dist.iter().map(|(_, p)| p).sum();
+        debug_assert!(dist.iter().all(|(_, p)| *p > 0.0 || *p < 1.0));
+        debug_assert!((sum - 1.0).abs() < 0.0001);
+        if preferences.is_empty() {
+            return dist.into_iter().map(|(_, p)| p).collect();
+        }
+
+        let preferred_dist: Vec<f64> = dist
+            .into_iter()
+            .map(|(tenant, p)| match preferences.get(&tenant) {
+                Some(1) => p * PREFERENCE_FACTOR,
+                Some(-1) => p / PREFERENCE_FACTOR,
+                _ => p,
+            })
+            .collect();
+        let sum: f64 = preferred_dist.iter().sum();
+        let preferred_dist: Vec<f64> = preferred_dist.into_iter().map(|p| p / sum).collect();
+
+        println!("preferred tenant Distribution: {:?}", preferred_dist);
+        let sum: f64 = preferred_dist.iter().sum();
         debug_assert!((sum - 1.0).abs() < 0.0001);
 
-        dist
+        preferred_dist
     }
 
     /// Choose a tenant from a list of tenants ordered by score.
@@ -283,8 +337,9 @@ ORDER BY CAST(TenantScore.score AS REAL) ASC;
     pub async fn choose_tenant(
         &mut self,
         tenants: Vec<(String, f64)>,
+        preferences: &HashMap<String, i64>,
     ) -> Result<(String, f64, f64)> {
-        let dist = self.calc_tenant_distribution(tenants.clone());
+        let dist = self.calc_tenant_distribution(tenants.clone(), preferences);
         let idx = WeightedIndex::new(&dist)?.sample(&mut self.rng);
         Ok((tenants[idx].0.clone(), tenants[idx].1, dist[idx]))
     }
@@ -308,7 +363,8 @@ ORDER BY CAST(TenantScore.score AS REAL) ASC;
         if tenants.is_empty() {
             return Ok(ReplyMsg::new());
         }
-        let (tenant, score, prob) = self.choose_tenant(tenants).await?;
+        let preferences = self.get_preferences(chore).await?;
+        let (tenant, score, prob) = self.choose_tenant(tenants, &preferences).await?;
 
         let row = sqlx::query(
             r#"
@@ -322,6 +378,8 @@ WHERE Tenant.name = ?1;
         .await?;
         self.integrity_check().await?;
         let tag: Option<String> = row.try_get(0)?;
+        // Get the template before the ChoreLog is stored, so it isn't stored without announcement.
+        let template = self.get_template(TemplateKind::Assignment).await?;
 
         let affected_rows = sqlx::query(
             r#"
@@ -343,29 +401,35 @@ INSERT INTO ChoreLog VALUES
         .rows_affected();
         self.integrity_check().await?;
         if affected_rows != 1 {
-            bail!("affected {} rows", affected_rows);
+            bail!(self.locale.affected_rows(affected_rows));
         }
 
+        let replan_cmd = fmt_replan_cmd(&tenant, week);
         let week_delta = week.db_week() - self.get_week_internal().await.db_week();
-        let mut msg = ReplyMsg::from_mono(&format!(
-            "# {1} on {2} (in {6} {7}): {0}
-{0}, you have been chosen for the {1} on {2}.
-According to your effective score {3:.2} you've had a probability of {4:.0}% to be chosen.
-If you're unhappy about that, type this to schedule someone else:
-    {5}
-Alternatively you can move out and then back in if you're on vacation.",
-            &tenant,
-            chore,
-            week,
-            score,
-            prob * 100.0,
-            fmt_replan_cmd(&tenant, week),
-            week_delta,
-            if week_delta == 1 { "week" } else { "weeks" },
-        ));
+        let mut msg = ReplyMsg::from_blocks(vec![
+            Block::heading(
+                1,
+                &self.locale.chosen_heading(chore, week, week_delta, &tenant),
+            ),
+            Block::Paragraph {
+                inlines: render_template(&template, |placeholder| match placeholder {
+                    Placeholder::Week => Inline::Text(week.to_string()),
+                    Placeholder::Chore => Inline::Text(chore.to_string()),
+                    // Mention the tenant inline, so they get notified without a separate message.
+                    Placeholder::Tenant => Inline::mention(&tenant, tag.as_deref()),
+                    Placeholder::Probability => Inline::Text(format!("{:.0}%", prob * 100.0)),
+                    Placeholder::Score => Inline::Text(format!("{:.2}", score)),
+                    Placeholder::ReplanCommand => Inline::Code(replan_cmd.clone()),
+                }),
+            },
+        ]);
         if let Some(tag) = tag {
             msg.tags.insert(tag);
         }
+        msg.push_action(Action::new(
+            &self.locale.cant_do_action(&tenant, chore, Some(week)),
+            &replan_cmd,
+        ));
         Ok(msg)
     }
 }
@@ -1,11 +1,37 @@
 use crate::db::*;
 
 use anyhow::Result;
+use serde::Serialize;
 use sqlx::Row;
-use tabled::{
-    settings::{object::Segment, Alignment, Settings},
-    Table, Tabled,
-};
+use tabled::Tabled;
+
+/// an active chore and its ChoreLogs
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ChorePlan {
+    pub name: String,
+    pub description: String,
+    /// how often the chore has been done until and including the current week
+    pub times_performed: i32,
+    pub plan: Vec<PlannedChoreLog>,
+}
+
+/// a ChoreLog with the average rating it received
+#[derive(Serialize, Debug, PartialEq)]
+pub struct PlannedChoreLog {
+    pub week: Week,
+    pub tenant: String,
+    pub rating: Option<f32>,
+}
+
+/// a ChoreLog with everything needed to put it in a calendar
+#[derive(Serialize, Debug, PartialEq)]
+pub struct CalendarEntry {
+    pub chore_id: i64,
+    pub chore: String,
+    pub description: String,
+    pub week: Week,
+    pub tenant: String,
+}
 
 impl Db {
     /// Mark a tenant unwilling for some week.
@@ -34,23 +60,184 @@ REPLACE INTO Unwilling VALUES (
         .rows_affected();
         self.integrity_check().await?;
         if affected_rows != 1 {
-            bail!("affected {} rows", affected_rows);
+            bail!(self.locale.affected_rows(affected_rows));
         }
         self.update_plan(fmt_replan_cmd).await
     }
 
-    /// List all future ChoreLogs.
+    /// Swap all chores two tenants have in some week.
     ///
-    /// Or list all past ChoreLogs starting from start_week when start_week is Some.
-    pub async fn list_plan(&mut self, start_week: Option<Week>) -> Result<ReplyMsg> {
-        let cur_week = self.get_week_internal().await;
+    /// Both need to be available for the other's chores and none of the ChoreLogs may be rated
+    /// already. Without other, list the tenants with a chore in that week tenant could swap with.
+    ///
+    /// fmt_swap_cmd takes the tenant, the other tenant and the week for the swap command
+    pub async fn swap<F>(
+        &mut self,
+        tenant: &str,
+        other: Option<&str>,
+        week: Week,
+        fmt_swap_cmd: F,
+    ) -> Result<ReplyMsg>
+    where
+        F: Fn(&str, Option<&str>, Week) -> String,
+    {
+        let tenant = Self::capitalize_tenant_name(tenant);
+        if self.get_tenant_id(&tenant).await?.is_none() {
+            bail!(self.locale.tenant_doesnt_exist(&tenant));
+        }
+        if week.db_week() < self.get_week_internal().await.db_week() {
+            bail!(self.locale.swap_in_past(week));
+        }
+        let chore_logs = self.get_week_chore_logs(week).await?;
+        if !chore_logs.iter().any(|(_, worker, _)| *worker == tenant) {
+            bail!(self.locale.tenant_has_no_chore(&tenant, week));
+        }
 
-        struct Chore {
-            id: i32,
-            name: String,
-            description: String,
-            times_performed: i32,
+        let Some(other) = other else {
+            let mut msg = ReplyMsg::from_blocks(vec![Block::heading(
+                1,
+                &self.locale.swap_heading(&tenant, week),
+            )]);
+            let mut others = chore_logs
+                .iter()
+                .map(|(_, worker, _)| worker.clone())
+                .filter(|worker| *worker != tenant)
+                .collect::<Vec<_>>();
+            others.dedup();
+            for other in others {
+                if self.can_swap(&chore_logs, &tenant, &other, week).await? {
+                    let chores = chore_logs
+                        .iter()
+                        .filter(|(_, worker, _)| *worker == other)
+                        .map(|(chore, _, _)| chore.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    msg.push_action(Action::new(
+                        &self.locale.swap_with_action(&other, &chores),
+                        &fmt_swap_cmd(&tenant, Some(&other), week),
+                    ));
+                }
+            }
+            if msg.actions.is_empty() {
+                bail!(self.locale.nobody_to_swap_with(&tenant, week));
+            }
+            return Ok(msg);
+        };
+
+        let other = Self::capitalize_tenant_name(other);
+        let Some(other_id) = self.get_tenant_id(&other).await? else {
+            bail!(self.locale.tenant_doesnt_exist(&other));
+        };
+        if !self.can_swap(&chore_logs, &tenant, &other, week).await? {
+            bail!(self.locale.cant_swap(&tenant, &other, week));
         }
+        let tenant_id = self.get_tenant_id(&tenant).await?.unwrap();
+        let affected_rows = sqlx::query(
+            r#"
+UPDATE ChoreLog
+SET worker = CASE ChoreLog.worker WHEN ?2 THEN ?3 ELSE ?2 END
+WHERE ChoreLog.week = ?1
+AND ChoreLog.worker IN (?2, ?3);
+"#,
+        )
+        .bind(week.db_week())
+        .bind(tenant_id)
+        .bind(other_id)
+        .execute(&mut self.con)
+        .await?
+        .rows_affected();
+        self.integrity_check().await?;
+        let swapped_rows = chore_logs
+            .iter()
+            .filter(|(_, worker, _)| *worker == tenant || *worker == other)
+            .count();
+        if affected_rows != swapped_rows as u64 {
+            bail!(self.locale.affected_rows(affected_rows));
+        }
+
+        let sql_rows = sqlx::query(
+            r#"
+SELECT Tenant.chat_tag
+FROM Tenant
+WHERE Tenant.id IN (?1, ?2)
+AND Tenant.chat_tag IS NOT NULL;
+"#,
+        )
+        .bind(tenant_id)
+        .bind(other_id)
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        let mut msg = ReplyMsg::from_blocks(vec![Block::heading(
+            1,
+            &self.locale.swapped_heading(&tenant, &other, week),
+        )]);
+        for r in sql_rows {
+            msg.tags.insert(r.try_get(0)?);
+        }
+        Ok(msg)
+    }
+
+    /// Get the (chore, worker, rated) tuples of all ChoreLogs in some week ordered by worker.
+    ///
+    /// rated is true when the rating poll has been opened already.
+    async fn get_week_chore_logs(&mut self, week: Week) -> Result<Vec<(String, String, bool)>> {
+        let sql_rows = sqlx::query(
+            r#"
+SELECT Chore.name, Tenant.name, ChoreLog.rating_poll_id IS NOT NULL
+FROM ChoreLog
+JOIN Chore ON Chore.id = ChoreLog.chore_id
+JOIN Tenant ON Tenant.id = ChoreLog.worker
+WHERE ChoreLog.week = ?1
+ORDER BY Tenant.name, Chore.id;
+"#,
+        )
+        .bind(week.db_week())
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        sql_rows
+            .into_iter()
+            .map(|r| Ok((r.try_get(0)?, r.try_get(1)?, r.try_get(2)?)))
+            .collect()
+    }
+
+    /// Check whether tenant and other can swap their ChoreLogs of a week.
+    async fn can_swap(
+        &mut self,
+        chore_logs: &[(String, String, bool)],
+        tenant: &str,
+        other: &str,
+        week: Week,
+    ) -> Result<bool> {
+        if tenant == other {
+            return Ok(false);
+        }
+        for (chore, worker, rated) in chore_logs {
+            let new_worker = match worker {
+                worker if worker == tenant => other,
+                worker if worker == other => tenant,
+                _ => continue,
+            };
+            if *rated
+                || !self
+                    .get_all_available_tenants_unnormalized(week, chore)
+                    .await?
+                    .into_iter()
+                    .any(|(available_tenant, _)| available_tenant == new_worker)
+            {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Get all future ChoreLogs of all active chores.
+    ///
+    /// Or get all past ChoreLogs starting from start_week when start_week is Some.
+    pub async fn get_plan(&mut self, start_week: Option<Week>) -> Result<Vec<ChorePlan>> {
+        let cur_week = self.get_week_internal().await;
+
         let chore_rows = sqlx::query(
             r#"
 SELECT Chore.id, Chore.name, Chore.description, COUNT(ChoreLog.chore_id)
@@ -67,26 +254,22 @@ ORDER BY Chore.id;
         .fetch_all(&mut self.con)
         .await?;
         self.integrity_check().await?;
-        let chores = chore_rows
+        let mut chores = chore_rows
             .into_iter()
             .map(|r| {
-                Ok(Chore {
-                    id: r.try_get(0)?,
-                    name: r.try_get(1)?,
-                    description: r.try_get(2)?,
-                    times_performed: r.try_get::<i32, usize>(3)?,
-                })
+                Ok((
+                    r.try_get::<i32, usize>(0)?,
+                    ChorePlan {
+                        name: r.try_get(1)?,
+                        description: r.try_get(2)?,
+                        times_performed: r.try_get::<i32, usize>(3)?,
+                        plan: Vec::new(),
+                    },
+                ))
             })
             .collect::<Result<Vec<_>>>()?;
 
-        let mut out_mono = String::new();
-        for chore in chores {
-            #[derive(Tabled)]
-            struct ChoreLogRow {
-                week: String,
-                tenant: String,
-                rating: String,
-            }
+        for (chore_id, chore) in chores.iter_mut() {
             let chore_log_rows = sqlx::query(
                 r#"
 SELECT ChoreLog.week, Tenant.name, CAST(AVG(Rating.rating) AS FLOAT)
@@ -109,37 +292,109 @@ ORDER BY ChoreLog.week;
             .bind(start_week.unwrap_or(cur_week).db_week())
             // set after_last_week when a start date is set
             .bind(start_week.map(|_| cur_week.db_week()))
-            .bind(chore.id)
+            .bind(*chore_id)
             .fetch_all(&mut self.con)
             .await?;
             self.integrity_check().await?;
-            let rows = chore_log_rows
+            chore.plan = chore_log_rows
                 .into_iter()
                 .map(|r| {
-                    Ok(ChoreLogRow {
-                        week: Week::from_db(r.try_get(0)?).to_string(),
+                    Ok(PlannedChoreLog {
+                        week: Week::from_db(r.try_get(0)?),
                         tenant: r.try_get(1)?,
-                        rating: r
-                            .try_get::<Option<f32>, usize>(2)?
-                            .map_or("".to_string(), |v| format!("{:.2}", v)),
+                        rating: r.try_get(2)?,
                     })
                 })
                 .collect::<Result<Vec<_>>>()?;
+        }
+
+        Ok(chores.into_iter().map(|(_, chore)| chore).collect())
+    }
+
+    /// Get all ChoreLogs of active chores, only those of one tenant when tenant is Some.
+    pub async fn get_calendar_entries(
+        &mut self,
+        tenant: Option<&str>,
+    ) -> Result<Vec<CalendarEntry>> {
+        let tenant = tenant.map(Self::capitalize_tenant_name);
+        if let Some(tenant) = &tenant {
+            if self.get_tenant_id(tenant).await?.is_none() {
+                bail!(self.locale.tenant_doesnt_exist(tenant));
+            }
+        }
+        let sql_rows = sqlx::query(
+            r#"
+SELECT Chore.id, Chore.name, Chore.description, ChoreLog.week, Tenant.name
+FROM ChoreLog
+JOIN Chore
+    ON Chore.id = ChoreLog.chore_id
+JOIN Tenant
+    ON Tenant.id = ChoreLog.worker
+WHERE Chore.active = 1
+    AND (?1 IS NULL OR Tenant.name = ?1)
+ORDER BY ChoreLog.week, Chore.id;
+"#,
+        )
+        .bind(tenant)
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        sql_rows
+            .into_iter()
+            .map(|r| {
+                Ok(CalendarEntry {
+                    chore_id: r.try_get(0)?,
+                    chore: r.try_get(1)?,
+                    description: r.try_get(2)?,
+                    week: Week::from_db(r.try_get(3)?),
+                    tenant: r.try_get(4)?,
+                })
+            })
+            .collect()
+    }
 
-            out_mono += &format!(
-                "{}## {}\nTimes performed: {}\n{}\n\n### Plan\n{}",
-                if out_mono.is_empty() { "" } else { "\n\n\n" },
-                chore.name,
-                chore.times_performed,
-                chore.description,
-                Table::new(rows).modify(
-                    Segment::all(),
-                    Settings::new(Alignment::center(), Alignment::center())
-                )
-            );
+    /// List all future ChoreLogs.
+    ///
+    /// Or list all past ChoreLogs starting from start_week when start_week is Some.
+    pub async fn list_plan(&mut self, start_week: Option<Week>) -> Result<ReplyMsg> {
+        #[derive(Tabled)]
+        struct ChoreLogRow {
+            week: String,
+            tenant: String,
+            rating: String,
+        }
+
+        let mut blocks = vec![Block::heading(1, self.locale.heading(Heading::Chores))];
+        let chores = self.get_plan(start_week).await?;
+        if chores.is_empty() {
+            blocks.push(Block::Blank);
+        }
+        for (i, chore) in chores.into_iter().enumerate() {
+            let rows = chore.plan.into_iter().map(|chore_log| ChoreLogRow {
+                week: chore_log.week.to_string(),
+                tenant: chore_log.tenant,
+                rating: chore_log
+                    .rating
+                    .map_or("".to_string(), |v| format!("{:.2}", v)),
+            });
+
+            if i > 0 {
+                blocks.extend([Block::Blank, Block::Blank]);
+            }
+            blocks.extend([
+                Block::heading(2, &chore.name),
+                Block::text(&format!(
+                    "{}\n{}",
+                    self.locale.times_performed(chore.times_performed),
+                    chore.description
+                )),
+                Block::Blank,
+                Block::heading(3, self.locale.heading(Heading::Plan)),
+                Block::table_with_header(self.locale.table_header(TableHeader::Plan), rows),
+            ]);
         }
 
-        Ok(ReplyMsg::from_mono(&format!("# Chores\n{}", out_mono)))
+        Ok(ReplyMsg::from_blocks(blocks))
     }
 
     /// Go through all future ChoreLogs and figure out which ones to update. Update those.
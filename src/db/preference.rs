@@ -0,0 +1,31 @@
+use crate::db::*;
+
+use anyhow::Result;
+use std::collections::HashMap;
+
+impl Db {
+    /// Get the preference of every tenant that isn't neutral about a chore.
+    ///
+    /// Return map from tenant to 1 (likes the chore) or -1 (dislikes the chore).
+    pub async fn get_preferences(&mut self, chore: &str) -> Result<HashMap<String, i64>> {
+        let sql_rows = sqlx::query(
+            r#"
+SELECT Tenant.name, TenantChorePreference.preference
+FROM TenantChorePreference
+JOIN Tenant
+    ON Tenant.id = TenantChorePreference.tenant_id
+JOIN Chore
+    ON Chore.id = TenantChorePreference.chore_id
+WHERE Chore.name = ?1;
+"#,
+        )
+        .bind(chore)
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        sql_rows
+            .into_iter()
+            .map(|r| -> Result<(String, i64)> { Ok((r.try_get(0)?, r.try_get(1)?)) })
+            .collect()
+    }
+}
@@ -0,0 +1,115 @@
+use crate::db::*;
+
+use anyhow::{bail, Result};
+use tabled::Tabled;
+
+impl Db {
+    /// Print a nice list of all chores each current tenant likes and dislikes.
+    pub async fn list_preferences(&mut self) -> Result<ReplyMsg> {
+        #[derive(Tabled)]
+        struct PreferenceRow {
+            tenant: String,
+            likes: String,
+            dislikes: String,
+        }
+        let sql_rows = sqlx::query(
+            r#"
+SELECT Tenant.name,
+    GROUP_CONCAT(IIF(TenantChorePreference.preference = 1, Chore.name, NULL), CHAR(10)),
+    GROUP_CONCAT(IIF(TenantChorePreference.preference = -1, Chore.name, NULL), CHAR(10))
+FROM Tenant
+JOIN TenantChorePreference
+    ON Tenant.id = TenantChorePreference.tenant_id
+JOIN Chore
+    ON Chore.id = TenantChorePreference.chore_id
+    AND Chore.active = 1
+-- only list current tenants
+JOIN LivesIn
+    ON Tenant.id = LivesIn.tenant_id
+    AND LivesIn.move_in_week <= ?1
+    AND (LivesIn.move_out_week IS NULL OR LivesIn.move_out_week > ?1)
+GROUP BY Tenant.id, Tenant.name
+ORDER BY Tenant.name;
+"#,
+        )
+        .bind(self.get_week_internal().await.db_week())
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        let rows = sql_rows
+            .into_iter()
+            .map(|r| {
+                Ok(PreferenceRow {
+                    tenant: r.try_get(0)?,
+                    likes: r.try_get::<Option<String>, usize>(1)?.unwrap_or_default(),
+                    dislikes: r.try_get::<Option<String>, usize>(2)?.unwrap_or_default(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ReplyMsg::from_blocks(vec![
+            Block::heading(1, self.locale.heading(Heading::Preferences)),
+            Block::Blank,
+            Block::table_with_header(self.locale.table_header(TableHeader::Preferences), rows),
+        ]))
+    }
+
+    /// Set how much a tenant likes a chore.
+    ///
+    /// preference is 1 when the tenant likes the chore, -1 when they dislike it and 0 when they
+    /// are neutral.
+    pub async fn set_preference(
+        &mut self,
+        tenant: &str,
+        chore: &str,
+        preference: i64,
+    ) -> Result<ReplyMsg> {
+        let tenant = Self::capitalize_tenant_name(tenant);
+        if self.get_tenant_id(&tenant).await?.is_none() {
+            bail!(self.locale.tenant_doesnt_exist(&tenant));
+        }
+        if self.get_chore_id(chore).await?.is_none() {
+            bail!(self.locale.chore_doesnt_exist(chore));
+        }
+        match preference {
+            0 => {
+                sqlx::query(
+                    r#"
+DELETE FROM TenantChorePreference
+WHERE TenantChorePreference.tenant_id = (SELECT Tenant.id FROM Tenant WHERE Tenant.name = ?1)
+AND TenantChorePreference.chore_id = (SELECT Chore.id FROM Chore WHERE Chore.name = ?2);
+"#,
+                )
+                .bind(&tenant)
+                .bind(chore)
+                .execute(&mut self.con)
+                .await?;
+                self.integrity_check().await?;
+            }
+            _ => {
+                let affected_rows = sqlx::query(
+                    r#"
+REPLACE INTO TenantChorePreference VALUES (
+    (SELECT Tenant.id FROM Tenant WHERE Tenant.name = ?1),
+    (SELECT Chore.id FROM Chore WHERE Chore.name = ?2),
+    ?3
+);
+"#,
+                )
+                .bind(&tenant)
+                .bind(chore)
+                .bind(preference)
+                .execute(&mut self.con)
+                .await?
+                .rows_affected();
+                self.integrity_check().await?;
+                if affected_rows != 1 {
+                    bail!(self
+                        .locale
+                        .affected_table_rows(affected_rows, "TenantChorePreference"));
+                }
+            }
+        }
+        self.list_preferences().await
+    }
+}
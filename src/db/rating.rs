@@ -1,15 +1,5 @@
 use crate::{bot::PollableBot, db::*};
 
-use anyhow::Context;
-
-pub const RATING_OPTIONS: [&str; 5] = [
-    "1  😢  You didn't do anything.",
-    "2  👎  Barely noticeable.",
-    "3  😮  Acceptable.",
-    "4  👍  Well done.",
-    "5  ❤️  Perfect!",
-];
-
 impl Db {
     /// Create a poll for all ChoreLogs of last week.
     ///
@@ -25,7 +15,9 @@ JOIN Tenant
 JOIN Chore
     ON Chore.id = ChoreLog.chore_id
 WHERE ChoreLog.week = ?1
-AND ChoreLog.rating_poll_id IS NULL;
+AND ChoreLog.rating_poll_id IS NULL
+-- imported ChoreLogs are completed without a poll
+AND ChoreLog.completed = 0;
 "#,
         )
         .bind(week.db_week())
@@ -36,38 +28,215 @@ AND ChoreLog.rating_poll_id IS NULL;
         for row in rows {
             let chore: String = row.try_get(0)?;
             let tenant: String = row.try_get(1)?;
+            self.create_rating_poll(bot, &chore, &tenant, week).await?;
+        }
+        Ok(())
+    }
 
-            let id = bot
-                .send_poll(
-                    &format!("How well did {} do the {} on {}?", tenant, chore, week),
-                    RATING_OPTIONS.iter().map(|r| r.to_string()).collect(),
-                )
-                .await?;
+    /// Open the rating poll of a ChoreLog of this week before the week is over, because its
+    /// worker is done.
+    ///
+    /// The poll is stopped with the other polls once the week is over.
+    pub async fn open_rating_poll<T: PollableBot>(
+        &mut self,
+        bot: &mut T,
+        chore: &str,
+        week: Week,
+    ) -> Result<ReplyMsg> {
+        if self.get_chore_id(chore).await?.is_none() {
+            bail!(self.locale.chore_doesnt_exist(chore));
+        }
+        // Polls of earlier weeks have been opened by the weekly action already.
+        let cur_week = self.get_week_internal().await;
+        if week != cur_week {
+            bail!(self.locale.done_not_this_week(cur_week));
+        }
+        let row = sqlx::query(
+            r#"
+SELECT Tenant.name, ChoreLog.rating_poll_id IS NOT NULL
+FROM ChoreLog
+JOIN Tenant
+    ON Tenant.id = ChoreLog.worker
+JOIN Chore
+    ON Chore.id = ChoreLog.chore_id
+WHERE ChoreLog.week = ?1
+AND Chore.name = ?2;
+"#,
+        )
+        .bind(week.db_week())
+        .bind(chore)
+        .fetch_optional(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        let Some(row) = row else {
+            bail!(self.locale.chore_log_doesnt_exist(chore, week));
+        };
+        let tenant: String = row.try_get(0)?;
+        let has_poll: bool = row.try_get(1)?;
+        if has_poll {
+            bail!(self.locale.chore_log_already_rated(chore, week));
+        }
 
-            let affected_rows = sqlx::query(
-                r#"
+        self.create_rating_poll(bot, chore, &tenant, week).await?;
+        Ok(ReplyMsg::from_blocks(vec![Block::heading(
+            1,
+            &self.locale.done_heading(chore, week, &tenant),
+        )]))
+    }
+
+    /// Create the rating poll of a ChoreLog.
+    async fn create_rating_poll<T: PollableBot>(
+        &mut self,
+        bot: &mut T,
+        chore: &str,
+        tenant: &str,
+        week: Week,
+    ) -> Result<()> {
+        let poll = bot
+            .send_poll(
+                &self.locale.rating_question(tenant, chore, week),
+                self.locale
+                    .rating_options()
+                    .iter()
+                    .map(|r| r.to_string())
+                    .collect(),
+            )
+            .await?;
+
+        let affected_rows = sqlx::query(
+            r#"
 UPDATE ChoreLog
 SET rating_poll_id = ?1
     WHERE ChoreLog.week = ?2
     AND ChoreLog.chore_id = (SELECT Chore.id FROM Chore WHERE Chore.name = ?3);
+"#,
+        )
+        .bind(poll.id)
+        .bind(week.db_week())
+        .bind(chore)
+        .execute(&mut self.con)
+        .await?
+        .rows_affected();
+        self.integrity_check().await?;
+        if affected_rows != 1 {
+            bail!(self.locale.affected_rows(affected_rows));
+        }
+
+        // Remember how the votes refer to the poll.
+        if let Some(vote_key) = poll.vote_key {
+            let affected_rows = sqlx::query(
+                r#"
+INSERT INTO RatingPoll VALUES (?1, ?2);
 "#,
             )
-            .bind(id)
-            .bind(week.db_week())
-            .bind(chore)
+            .bind(poll.id)
+            .bind(vote_key)
             .execute(&mut self.con)
             .await?
             .rows_affected();
             self.integrity_check().await?;
             if affected_rows != 1 {
-                bail!("affected {} rows", affected_rows);
+                bail!(self.locale.affected_rows(affected_rows));
+            }
+        }
+        Ok(())
+    }
+
+    /// Store a vote of a non-anonymous rating poll, replacing the voter's previous vote.
+    ///
+    /// Votes of polls that aren't rating polls are ignored.
+    /// option is the index in Locale::rating_options or None when the vote was retracted.
+    pub async fn store_poll_vote(
+        &mut self,
+        vote_key: &str,
+        voter_tags: &[String],
+        option: Option<usize>,
+    ) -> Result<()> {
+        let Some(voter) = voter_tags.first() else {
+            bail!(self.locale.voter_without_chat_tag());
+        };
+        if option.is_some_and(|o| o >= self.locale.rating_options().len()) {
+            bail!(self.locale.rating_option_doesnt_exist(option.unwrap()));
+        }
+        let row = sqlx::query(
+            r#"
+SELECT RatingPoll.rating_poll_id
+FROM RatingPoll
+    WHERE RatingPoll.vote_key = ?1;
+"#,
+        )
+        .bind(vote_key)
+        .fetch_optional(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        let Some(row) = row else {
+            println!("ignoring vote for unknown poll {}", vote_key);
+            return Ok(());
+        };
+        let rating_poll_id: i64 = row.try_get(0)?;
+
+        sqlx::query(
+            r#"
+DELETE FROM PollVote
+    WHERE PollVote.rating_poll_id = ?1
+    AND PollVote.voter = ?2;
+"#,
+        )
+        .bind(rating_poll_id)
+        .bind(voter)
+        .execute(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        let Some(option) = option else {
+            return Ok(());
+        };
+
+        // Find out which tenant voted, so that the worker's vote can be excluded.
+        let mut tenant_id: Option<i64> = None;
+        for tag in voter_tags {
+            let row = sqlx::query(
+                r#"
+SELECT Tenant.id
+FROM Tenant
+    -- chat tags like Telegram usernames are case-insensitive
+    WHERE Tenant.chat_tag = ?1 COLLATE NOCASE;
+"#,
+            )
+            .bind(tag)
+            .fetch_optional(&mut self.con)
+            .await?;
+            self.integrity_check().await?;
+            if let Some(row) = row {
+                tenant_id = Some(row.try_get(0)?);
+                break;
             }
         }
+
+        let affected_rows = sqlx::query(
+            r#"
+INSERT INTO PollVote VALUES (?1, ?2, ?3, ?4);
+"#,
+        )
+        .bind(rating_poll_id)
+        .bind(voter)
+        .bind(tenant_id)
+        .bind(option as u32 + 1)
+        .execute(&mut self.con)
+        .await?
+        .rows_affected();
+        self.integrity_check().await?;
+        if affected_rows != 1 {
+            bail!(self.locale.affected_rows(affected_rows));
+        }
         Ok(())
     }
 
     /// Stop all open polls and store their results in the database.
-    pub async fn stop_rating_polls<T: PollableBot>(&mut self, bot: &mut T) -> Result<()> {
+    ///
+    /// The results of non-anonymous polls are taken from the stored votes, excluding the worker's
+    /// own vote.
+    /// Return the announcements of the chores that need to be redone.
+    pub async fn stop_rating_polls<T: PollableBot>(&mut self, bot: &mut T) -> Result<ReplyMsg> {
         let rows = sqlx::query(
             r#"
 SELECT ChoreLog.rating_poll_id
@@ -87,6 +256,7 @@ FROM ChoreLog
             .map(|r| -> Result<i64> { Ok(r.try_get(0)?) })
             .collect::<Result<Vec<i64>>>()?;
 
+        let mut out = ReplyMsg::new();
         for poll_id in poll_ids {
             let results = bot.stop_poll(poll_id).await.unwrap_or_else(|e| {
                 eprintln!(
@@ -95,35 +265,10 @@ FROM ChoreLog
                 );
                 vec![]
             });
-            for (rating_str, count) in results {
-                // Figure out what to store in the db.
-                let rating = rating_str
-                    .split(' ')
-                    .next()
-                    .context("failed to parse rating_str")?
-                    .parse::<u32>()?;
-                // Store that count times.
-                for _ in 0..count {
-                    let affected_rows = sqlx::query(
-                        r#"
-INSERT INTO Rating VALUES (
-    NULL,
-    (SELECT ChoreLog.chore_id FROM ChoreLog WHERE ChoreLog.rating_poll_id = ?1),
-    (SELECT ChoreLog.week     FROM ChoreLog WHERE ChoreLog.rating_poll_id = ?1),
-    ?2
-);
-"#,
-                    )
-                    .bind(poll_id)
-                    .bind(rating)
-                    .execute(&mut self.con)
-                    .await?
-                    .rows_affected();
-                    self.integrity_check().await?;
-                    if affected_rows != 1 {
-                        bail!("affected {} rows", affected_rows);
-                    }
-                }
+            if self.store_poll_votes_as_ratings(poll_id).await? {
+                println!("stored the votes of poll {} as ratings", poll_id);
+            } else {
+                self.store_poll_results_as_ratings(poll_id, results).await?;
             }
             let affected_rows = sqlx::query(
                 r#"
@@ -138,7 +283,188 @@ SET completed = 1
             .rows_affected();
             self.integrity_check().await?;
             if affected_rows != 1 {
-                bail!("affected {} rows", affected_rows);
+                bail!(self.locale.affected_rows(affected_rows));
+            }
+            out += self.plan_redo(poll_id).await?;
+        }
+        Ok(out)
+    }
+
+    /// Assign the worker of a poorly rated ChoreLog the same chore this week, when its
+    /// ChoreRatingRule asks for a redo.
+    ///
+    /// Nothing is assigned when the worker isn't available this week, i.e., because they moved
+    /// out. Whoever has been planned for the chore this week is told that they don't have to do it
+    /// anymore.
+    async fn plan_redo(&mut self, poll_id: i64) -> Result<ReplyMsg> {
+        let row = sqlx::query(
+            r#"
+SELECT Chore.name, Tenant.name, Tenant.chat_tag
+FROM ChoreLog
+JOIN PoorlyRatedChoreLog
+    ON PoorlyRatedChoreLog.chore_id = ChoreLog.chore_id
+    AND PoorlyRatedChoreLog.week = ChoreLog.week
+JOIN Chore ON Chore.id = ChoreLog.chore_id
+JOIN Tenant ON Tenant.id = ChoreLog.worker
+    WHERE ChoreLog.rating_poll_id = ?1
+    AND PoorlyRatedChoreLog.redo = 1;
+"#,
+        )
+        .bind(poll_id)
+        .fetch_optional(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        let Some(row) = row else {
+            return Ok(ReplyMsg::new());
+        };
+        let chore: String = row.try_get(0)?;
+        let tenant: String = row.try_get(1)?;
+        let tag: Option<String> = row.try_get(2)?;
+
+        let week = self.get_week_internal().await;
+        if !self
+            .get_all_available_tenants_unnormalized(week, &chore)
+            .await?
+            .into_iter()
+            .any(|(available_tenant, _)| available_tenant == tenant)
+        {
+            println!("{} can't redo {} on {}", tenant, chore, week);
+            return Ok(ReplyMsg::new());
+        }
+
+        let displaced = sqlx::query(
+            r#"
+SELECT Tenant.name, Tenant.chat_tag
+FROM ChoreLog
+JOIN Chore ON Chore.id = ChoreLog.chore_id
+JOIN Tenant ON Tenant.id = ChoreLog.worker
+    WHERE Chore.name = ?1
+    AND ChoreLog.week = ?2
+    AND Tenant.name != ?3;
+"#,
+        )
+        .bind(&chore)
+        .bind(week.db_week())
+        .bind(&tenant)
+        .fetch_optional(&mut self.con)
+        .await?
+        .map(|row| -> Result<(String, Option<String>)> { Ok((row.try_get(0)?, row.try_get(1)?)) })
+        .transpose()?;
+        self.integrity_check().await?;
+
+        // replaces whoever has been planned for this week
+        let affected_rows = sqlx::query(
+            r#"
+REPLACE INTO ChoreLog VALUES
+(
+    (SELECT Chore.id FROM Chore WHERE Chore.name = ?1),
+    ?2,
+    (SELECT Tenant.id FROM Tenant WHERE Tenant.name = ?3),
+    0,
+    NULL
+);
+"#,
+        )
+        .bind(&chore)
+        .bind(week.db_week())
+        .bind(&tenant)
+        .execute(&mut self.con)
+        .await?
+        .rows_affected();
+        self.integrity_check().await?;
+        if affected_rows != 1 {
+            bail!(self.locale.affected_rows(affected_rows));
+        }
+
+        let mut msg = ReplyMsg::from_blocks(vec![Block::heading(
+            1,
+            &self.locale.redo_heading(&chore, week, &tenant),
+        )]);
+        if let Some(tag) = tag {
+            msg.tags.insert(tag);
+        }
+        if let Some((displaced, displaced_tag)) = displaced {
+            msg.blocks.push(Block::heading(
+                1,
+                &self.locale.redo_displaced_heading(&chore, week, &displaced),
+            ));
+            if let Some(tag) = displaced_tag {
+                msg.tags.insert(tag);
+            }
+        }
+        Ok(msg)
+    }
+
+    /// Store the votes of a non-anonymous poll as ratings, excluding the worker's own vote.
+    ///
+    /// Return false when the poll is anonymous.
+    async fn store_poll_votes_as_ratings(&mut self, poll_id: i64) -> Result<bool> {
+        let rows = sqlx::query(
+            r#"
+SELECT RatingPoll.rating_poll_id
+FROM RatingPoll
+    WHERE RatingPoll.rating_poll_id = ?1;
+"#,
+        )
+        .bind(poll_id)
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        if rows.is_empty() {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            r#"
+INSERT INTO Rating
+SELECT NULL, ChoreLog.chore_id, ChoreLog.week, PollVote.rating
+FROM PollVote
+JOIN ChoreLog
+    ON ChoreLog.rating_poll_id = PollVote.rating_poll_id
+    WHERE PollVote.rating_poll_id = ?1
+    AND (PollVote.tenant_id IS NULL OR PollVote.tenant_id != ChoreLog.worker);
+"#,
+        )
+        .bind(poll_id)
+        .execute(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        Ok(true)
+    }
+
+    /// Store the results of an anonymous poll as ratings.
+    ///
+    /// results is a list of (option, count_chosen) tuples.
+    async fn store_poll_results_as_ratings(
+        &mut self,
+        poll_id: i64,
+        results: Vec<(String, u32)>,
+    ) -> Result<()> {
+        for (rating_str, count) in results {
+            // Figure out what to store in the db.
+            // The locale might have changed since the poll was created.
+            let rating = Locale::parse_rating(&rating_str)?;
+            // Store that count times.
+            for _ in 0..count {
+                let affected_rows = sqlx::query(
+                    r#"
+INSERT INTO Rating VALUES (
+    NULL,
+    (SELECT ChoreLog.chore_id FROM ChoreLog WHERE ChoreLog.rating_poll_id = ?1),
+    (SELECT ChoreLog.week     FROM ChoreLog WHERE ChoreLog.rating_poll_id = ?1),
+    ?2
+);
+"#,
+                )
+                .bind(poll_id)
+                .bind(rating)
+                .execute(&mut self.con)
+                .await?
+                .rows_affected();
+                self.integrity_check().await?;
+                if affected_rows != 1 {
+                    bail!(self.locale.affected_rows(affected_rows));
+                }
             }
         }
         Ok(())
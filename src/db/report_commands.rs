@@ -1,19 +1,55 @@
-use crate::db::*;
+use crate::{
+    db::{plan_commands::ChorePlan, score_commands::ScoreAdjustment, tenant_commands::Room, *},
+    template::{render_template, TemplateKind},
+};
 
 use anyhow::Result;
-use tabled::{
-    settings::{object::Segment, Alignment, Settings},
-    Table, Tabled,
-};
+use serde::Serialize;
+use tabled::Tabled;
+
+/// how things went since some week
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Report {
+    pub plan: Vec<ChorePlan>,
+    pub rooms: Vec<Room>,
+    pub score_adjustments: Vec<ScoreAdjustment>,
+}
 
 impl Db {
+    /// Get a report of all ChoreLogs since some week and the tenants' current scores.
+    pub async fn get_report(&mut self, week: Week) -> Result<Report> {
+        Ok(Report {
+            plan: self.get_plan(Some(week)).await?,
+            rooms: self.get_rooms().await?,
+            score_adjustments: self.get_score_adjustments(Some(week)).await?,
+        })
+    }
+
     /// Print a nice report to see how things went (say last semester).
     pub async fn print_report(&mut self, week: Week) -> Result<ReplyMsg> {
-        Ok(self.list_plan(Some(week)).await? + self.list_tenants().await?)
+        let mut report = self.list_plan(Some(week)).await? + self.list_tenants().await?;
+        if !self.get_score_adjustments(Some(week)).await?.is_empty() {
+            report += self.list_score_adjustments(Some(week)).await?;
+        }
+        Ok(report)
     }
 
     /// Print a nice report of who needs to do what job in the new week.
-    pub async fn print_next_week_banner(&mut self) -> Result<ReplyMsg> {
+    ///
+    /// fmt_replan_cmd takes the tenant and week for the replan command,
+    /// fmt_done_cmd the chore and week for the done command and
+    /// fmt_swap_cmd the tenant, the other tenant and week for the swap command
+    pub async fn print_next_week_banner<F, G, H>(
+        &mut self,
+        fmt_replan_cmd: F,
+        fmt_done_cmd: G,
+        fmt_swap_cmd: H,
+    ) -> Result<ReplyMsg>
+    where
+        F: Fn(&str, Week) -> String,
+        G: Fn(&str, Week) -> String,
+        H: Fn(&str, Option<&str>, Week) -> String,
+    {
         #[derive(Tabled)]
         struct JobRow {
             job: String,
@@ -21,6 +57,7 @@ impl Db {
             #[tabled(skip)]
             tag: Option<String>,
         }
+        let week = self.get_week_internal().await;
         let sql_rows = sqlx::query(
             r#"
 SELECT Chore.name, Tenant.name, Tenant.chat_tag
@@ -30,7 +67,7 @@ JOIN Chore ON ChoreLog.chore_id = Chore.id
 WHERE ChoreLog.week = ?1;
 "#,
         )
-        .bind(self.get_week_internal().await.db_week())
+        .bind(week.db_week())
         .fetch_all(&mut self.con)
         .await?;
         self.integrity_check().await?;
@@ -45,14 +82,41 @@ WHERE ChoreLog.week = ?1;
             })
             .collect::<Result<Vec<_>>>()?;
 
-        let mut msg = ReplyMsg::from_mono(&format!(
-            "# Week {}\nHello smart people!\nWe have another week and new jobs to go with it:\n\n{}\n\nHave a very safe and productive week.",
-            self.get_week_internal().await,
-            Table::new(&rows).modify(
-                Segment::all(),
-                Settings::new(Alignment::center(), Alignment::center())
-            )
-        ));
+        // The banner's templates only know the week placeholder.
+        let greeting = self.get_template(TemplateKind::BannerGreeting).await?;
+        let farewell = self.get_template(TemplateKind::BannerFarewell).await?;
+        let mut msg = ReplyMsg::from_blocks(vec![
+            Block::heading(1, &self.locale.banner_heading(week)),
+            Block::Paragraph {
+                inlines: render_template(&greeting, |_| Inline::Text(week.to_string())),
+            },
+            Block::Blank,
+            Block::table_with_header(&self.locale.banner_header(), &rows),
+            Block::Blank,
+            Block::Paragraph {
+                inlines: render_template(&farewell, |_| Inline::Text(week.to_string())),
+            },
+        ]);
+        for row in &rows {
+            msg.push_action(Action::new(
+                &self.locale.cant_do_action(&row.worker, &row.job, None),
+                &fmt_replan_cmd(&row.worker, week),
+            ));
+            msg.push_action(Action::new(
+                &self.locale.done_action(&row.worker, &row.job),
+                &fmt_done_cmd(&row.job, week),
+            ));
+        }
+        // Swapping is about all chores of a tenant, so each worker only gets one button.
+        let mut workers = rows.iter().map(|row| &row.worker).collect::<Vec<_>>();
+        workers.sort();
+        workers.dedup();
+        for worker in workers {
+            msg.push_action(Action::new(
+                &self.locale.swap_action(worker),
+                &fmt_swap_cmd(worker, None, week),
+            ));
+        }
         msg.tags = rows.into_iter().filter_map(|r| r.tag).collect();
         Ok(msg)
     }
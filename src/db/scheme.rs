@@ -205,6 +205,584 @@ CREATE TABLE KeyValue (
 ) STRICT;
 "#,
             // v1.0.0 //
+            r#"
+-- A tenant with a TenantChoreLimit is never assigned more than max_chores_per_week ChoreLogs in the same week.
+-- This is a hard constraint: when no tenant fulfills it, the ChoreLog isn't planned.
+CREATE TABLE TenantChoreLimit (
+    tenant_id INTEGER NOT NULL,
+    max_chores_per_week INTEGER NOT NULL,
+    --
+    CONSTRAINT TenantChoreLimit_PK PRIMARY KEY (tenant_id),
+    CONSTRAINT TenantChoreLimit_TO_Tenant_FK FOREIGN KEY (tenant_id) REFERENCES Tenant (id)
+) STRICT;
+"#,
+            r#"
+-- A tenant with a TenantCoolDown shouldn't do the same chore again within weeks weeks.
+-- This is a soft constraint: when no tenant fulfills it, it is ignored.
+CREATE TABLE TenantCoolDown (
+    tenant_id INTEGER NOT NULL,
+    chore_id INTEGER NOT NULL,
+    weeks INTEGER NOT NULL,
+    --
+    CONSTRAINT TenantCoolDown_PK PRIMARY KEY (tenant_id, chore_id),
+    CONSTRAINT TenantCoolDown_TO_Tenant_FK FOREIGN KEY (tenant_id) REFERENCES Tenant (id),
+    CONSTRAINT TenantCoolDown_TO_Chore_FK FOREIGN KEY (chore_id) REFERENCES Chore (id)
+) STRICT;
+"#,
+            r#"
+-- How much a tenant likes doing a chore.
+-- preference 1 when the tenant likes the chore, -1 when they dislike it
+-- Neutral tenants don't have an entry.
+CREATE TABLE TenantChorePreference (
+    tenant_id INTEGER NOT NULL,
+    chore_id INTEGER NOT NULL,
+    preference INTEGER NOT NULL,
+    --
+    CONSTRAINT TenantChorePreference_PK PRIMARY KEY (tenant_id, chore_id),
+    CONSTRAINT TenantChorePreference_TO_Tenant_FK FOREIGN KEY (tenant_id) REFERENCES Tenant (id),
+    CONSTRAINT TenantChorePreference_TO_Chore_FK FOREIGN KEY (chore_id) REFERENCES Chore (id)
+) STRICT;
+"#,
+            r#"
+-- A non-anonymous rating poll.
+-- vote_key is how the chat protocol refers to the poll when reporting votes.
+CREATE TABLE RatingPoll (
+    rating_poll_id INTEGER NOT NULL,
+    vote_key TEXT NOT NULL,
+    --
+    CONSTRAINT RatingPoll_PK PRIMARY KEY (rating_poll_id),
+    CONSTRAINT RatingPoll_TO_ChoreLog_FK FOREIGN KEY (rating_poll_id) REFERENCES ChoreLog (rating_poll_id),
+    UNIQUE (vote_key)
+) STRICT;
+"#,
+            r#"
+-- The current vote of a voter in a non-anonymous rating poll.
+-- When the poll is stopped, the votes become Ratings.
+-- voter identifies the voter within the chat protocol
+-- tenant_id is the tenant whose chat_tag belongs to the voter, NULL when there is none
+CREATE TABLE PollVote (
+    rating_poll_id INTEGER NOT NULL,
+    voter TEXT NOT NULL,
+    tenant_id INTEGER,
+    rating INTEGER NOT NULL,
+    --
+    CONSTRAINT PollVote_PK PRIMARY KEY (rating_poll_id, voter),
+    CONSTRAINT PollVote_TO_RatingPoll_FK FOREIGN KEY (rating_poll_id) REFERENCES RatingPoll (rating_poll_id),
+    CONSTRAINT PollVote_TO_Tenant_FK FOREIGN KEY (tenant_id) REFERENCES Tenant (id)
+) STRICT;
+"#,
+            r#"
+-- A customised message replacing the locale's default template.
+-- kind is one of assignment, banner-greeting or banner-farewell
+-- template may contain placeholders like {week}, which are validated before storing
+CREATE TABLE MessageTemplate (
+    kind TEXT NOT NULL,
+    template TEXT NOT NULL,
+    --
+    CONSTRAINT MessageTemplate_PK PRIMARY KEY (kind)
+) STRICT;
+"#,
+            r#"
+-- A manual credit or debit of a tenant's score, e.g., for cleaning up after a party.
+-- chore_id NULL adjusts the score of all chores, the amount is split evenly among the active chores
+-- amount is added to the score, doing a chore once is worth 1
+-- week the adjustment has been made in
+CREATE TABLE ScoreAdjustment (
+    id INTEGER PRIMARY KEY,
+    tenant_id INTEGER NOT NULL,
+    chore_id INTEGER,
+    amount REAL NOT NULL,
+    reason TEXT NOT NULL,
+    week INTEGER NOT NULL,
+    --
+    CONSTRAINT ScoreAdjustment_TO_Tenant_FK FOREIGN KEY (tenant_id) REFERENCES Tenant (id),
+    CONSTRAINT ScoreAdjustment_TO_Chore_FK FOREIGN KEY (chore_id) REFERENCES Chore (id)
+) STRICT;
+"#,
+            r#"
+-- replaced by the TenantScore including the ScoreAdjustments
+-- TenantScoreSUM refers to TenantScore by name and thus stays valid
+DROP VIEW TenantScore;
+"#,
+            r#"
+-- get score of tenant for a chore, including their ScoreAdjustments
+--
+-- tenant_id the tenant in question
+-- chore_id the chore in question
+-- score the score of the tenant for that score
+CREATE VIEW TenantScore (tenant_id, chore_id, score) AS
+
+SELECT Tenant.id, Chore.id,
+    COALESCE(SUM(ProfitingTenant.did_work * 1 - (1-ProfitingTenant.did_work) * 1/CAST(TotalProfitingTenant.count - 1 AS FLOAT)), 0)
+    + COALESCE((
+        SELECT SUM(IIF(
+            ScoreAdjustment.chore_id IS NULL,
+            ScoreAdjustment.amount / (SELECT COUNT(*) FROM Chore WHERE Chore.active = 1),
+            ScoreAdjustment.amount
+        ))
+        FROM ScoreAdjustment
+        WHERE ScoreAdjustment.tenant_id = Tenant.id
+        AND (
+            ScoreAdjustment.chore_id = Chore.id
+            OR (ScoreAdjustment.chore_id IS NULL AND Chore.active = 1)
+        )
+    ), 0)
+FROM Tenant, Chore
+LEFT JOIN ProfitingTenant ON ProfitingTenant.tenant_id = Tenant.id AND ProfitingTenant.chore_id = Chore.id
+LEFT JOIN TotalProfitingTenant ON ProfitingTenant.chore_id = TotalProfitingTenant.chore_id
+    AND ProfitingTenant.week = TotalProfitingTenant.week
+GROUP BY Tenant.id, Chore.id
+ORDER BY Chore.id, Tenant.id;
+"#,
+            r#"
+-- the weight of the ChoreLogs and ScoreAdjustments of a week in the TenantScore
+-- The current week has the weight 1, older weeks lose weight according to the score_decay KeyValues.
+-- Weeks after the current week and before the first ChoreLog or ScoreAdjustment aren't listed,
+-- their weight is 1.
+-- Without a current_week the last ChoreLog's week is used instead.
+--
+-- SQLite doesn't have pow by default, so the weights are multiplied week by week.
+CREATE VIEW ScoreWeight (week, weight) AS
+
+WITH RECURSIVE
+Setting (current_week, decay, weeks, factor) AS (
+    SELECT
+        COALESCE(
+            (SELECT CAST(KeyValue.value AS INTEGER) FROM KeyValue WHERE KeyValue.key = 'current_week'),
+            (SELECT MAX(ChoreLog.week) FROM ChoreLog)
+        ),
+        (SELECT KeyValue.value FROM KeyValue WHERE KeyValue.key = 'score_decay'),
+        (SELECT CAST(KeyValue.value AS INTEGER) FROM KeyValue WHERE KeyValue.key = 'score_decay_weeks'),
+        COALESCE((SELECT CAST(KeyValue.value AS REAL) FROM KeyValue WHERE KeyValue.key = 'score_decay_factor'), 1.0)
+),
+Weight (week, weight) AS (
+    SELECT Setting.current_week, 1.0
+    FROM Setting
+        WHERE Setting.current_week IS NOT NULL
+    UNION ALL
+    SELECT Weight.week - 1, Weight.weight * Setting.factor
+    FROM Weight, Setting
+        WHERE Weight.week > (
+            SELECT MIN(week) FROM (
+                SELECT ChoreLog.week FROM ChoreLog
+                UNION ALL
+                SELECT ScoreAdjustment.week FROM ScoreAdjustment
+            )
+        )
+)
+SELECT Weight.week,
+    IIF(Setting.decay = 'window' AND Setting.current_week - Weight.week >= Setting.weeks, 0.0, Weight.weight)
+FROM Weight, Setting;
+"#,
+            r#"
+-- replaced by the TenantScore weighting the weeks with ScoreWeight
+DROP VIEW TenantScore;
+"#,
+            r#"
+-- get score of tenant for a chore, including their ScoreAdjustments
+-- Every week's ChoreLog contribution is multiplied with its ScoreWeight. As the ChoreLog
+-- contributions of a week add up to 0, so do the weighted ones. ScoreAdjustments and StartScores
+-- are added on top and don't add up to 0.
+--
+-- tenant_id the tenant in question
+-- chore_id the chore in question
+-- score the score of the tenant for that score
+CREATE VIEW TenantScore (tenant_id, chore_id, score) AS
+
+SELECT Tenant.id, Chore.id,
+    COALESCE(SUM(
+        (ProfitingTenant.did_work * 1 - (1-ProfitingTenant.did_work) * 1/CAST(TotalProfitingTenant.count - 1 AS FLOAT))
+        * COALESCE(ScoreWeight.weight, 1.0)
+    ), 0)
+    + COALESCE((
+        SELECT SUM(
+            IIF(
+                ScoreAdjustment.chore_id IS NULL,
+                ScoreAdjustment.amount / (SELECT COUNT(*) FROM Chore WHERE Chore.active = 1),
+                ScoreAdjustment.amount
+            )
+            * COALESCE((SELECT ScoreWeight.weight FROM ScoreWeight WHERE ScoreWeight.week = ScoreAdjustment.week), 1.0)
+        )
+        FROM ScoreAdjustment
+        WHERE ScoreAdjustment.tenant_id = Tenant.id
+        AND (
+            ScoreAdjustment.chore_id = Chore.id
+            OR (ScoreAdjustment.chore_id IS NULL AND Chore.active = 1)
+        )
+    ), 0)
+FROM Tenant, Chore
+LEFT JOIN ProfitingTenant ON ProfitingTenant.tenant_id = Tenant.id AND ProfitingTenant.chore_id = Chore.id
+LEFT JOIN TotalProfitingTenant ON ProfitingTenant.chore_id = TotalProfitingTenant.chore_id
+    AND ProfitingTenant.week = TotalProfitingTenant.week
+LEFT JOIN ScoreWeight ON ScoreWeight.week = ProfitingTenant.week
+GROUP BY Tenant.id, Chore.id
+ORDER BY Chore.id, Tenant.id;
+"#,
+            r#"
+-- The score a tenant starts with for a chore when they move into the flat for the first time.
+-- It is computed according to the start_score KeyValue, tenants without an entry start at 0.
+-- week the week the tenant moved in, the score is weighted with its ScoreWeight
+CREATE TABLE StartScore (
+    tenant_id INTEGER NOT NULL,
+    chore_id INTEGER NOT NULL,
+    score REAL NOT NULL,
+    week INTEGER NOT NULL,
+    --
+    CONSTRAINT StartScore_PK PRIMARY KEY (tenant_id, chore_id),
+    CONSTRAINT StartScore_TO_Tenant_FK FOREIGN KEY (tenant_id) REFERENCES Tenant (id),
+    CONSTRAINT StartScore_TO_Chore_FK FOREIGN KEY (chore_id) REFERENCES Chore (id)
+) STRICT;
+"#,
+            r#"
+-- replaced by the TenantScore including the StartScores
+DROP VIEW TenantScore;
+"#,
+            r#"
+-- get score of tenant for a chore, including their ScoreAdjustments and StartScore
+-- Every week's ChoreLog contribution is multiplied with its ScoreWeight. As the ChoreLog
+-- contributions of a week add up to 0, so do the weighted ones. ScoreAdjustments and StartScores
+-- are added on top and don't add up to 0.
+--
+-- tenant_id the tenant in question
+-- chore_id the chore in question
+-- score the score of the tenant for that score
+CREATE VIEW TenantScore (tenant_id, chore_id, score) AS
+
+SELECT Tenant.id, Chore.id,
+    COALESCE(SUM(
+        (ProfitingTenant.did_work * 1 - (1-ProfitingTenant.did_work) * 1/CAST(TotalProfitingTenant.count - 1 AS FLOAT))
+        * COALESCE(ScoreWeight.weight, 1.0)
+    ), 0)
+    + COALESCE((
+        SELECT SUM(
+            IIF(
+                ScoreAdjustment.chore_id IS NULL,
+                ScoreAdjustment.amount / (SELECT COUNT(*) FROM Chore WHERE Chore.active = 1),
+                ScoreAdjustment.amount
+            )
+            * COALESCE((SELECT ScoreWeight.weight FROM ScoreWeight WHERE ScoreWeight.week = ScoreAdjustment.week), 1.0)
+        )
+        FROM ScoreAdjustment
+        WHERE ScoreAdjustment.tenant_id = Tenant.id
+        AND (
+            ScoreAdjustment.chore_id = Chore.id
+            OR (ScoreAdjustment.chore_id IS NULL AND Chore.active = 1)
+        )
+    ), 0)
+    + COALESCE((
+        SELECT StartScore.score
+            * COALESCE((SELECT ScoreWeight.weight FROM ScoreWeight WHERE ScoreWeight.week = StartScore.week), 1.0)
+        FROM StartScore
+        WHERE StartScore.tenant_id = Tenant.id
+        AND StartScore.chore_id = Chore.id
+    ), 0)
+FROM Tenant, Chore
+LEFT JOIN ProfitingTenant ON ProfitingTenant.tenant_id = Tenant.id AND ProfitingTenant.chore_id = Chore.id
+LEFT JOIN TotalProfitingTenant ON ProfitingTenant.chore_id = TotalProfitingTenant.chore_id
+    AND ProfitingTenant.week = TotalProfitingTenant.week
+LEFT JOIN ScoreWeight ON ScoreWeight.week = ProfitingTenant.week
+GROUP BY Tenant.id, Chore.id
+ORDER BY Chore.id, Tenant.id;
+"#,
+            r#"
+-- What happens to a ChoreLog of the chore whose average Rating is below min_rating.
+-- action partial: the ChoreLog only counts score_factor times in the TenantScore
+-- action redo: the ChoreLog doesn't count, instead the worker is assigned the chore again when
+-- the rating poll is stopped
+-- week the rule has been set in, older ChoreLogs aren't affected
+CREATE TABLE ChoreRatingRule (
+    chore_id INTEGER NOT NULL,
+    min_rating REAL NOT NULL,
+    action TEXT NOT NULL,
+    score_factor REAL NOT NULL,
+    week INTEGER NOT NULL,
+    --
+    CONSTRAINT ChoreRatingRule_PK PRIMARY KEY (chore_id),
+    CONSTRAINT ChoreRatingRule_TO_Chore_FK FOREIGN KEY (chore_id) REFERENCES Chore (id)
+) STRICT;
+"#,
+            r#"
+-- all ChoreLogs rated below the min_rating of their chore's ChoreRatingRule
+-- ChoreLogs without Ratings aren't poorly rated.
+--
+-- chore_id the chore of the ChoreLog
+-- week the week of the ChoreLog
+-- score_factor how much the ChoreLog counts in the TenantScore
+-- redo 1 iff the worker needs to do the chore again, else 0
+CREATE VIEW PoorlyRatedChoreLog (chore_id, week, score_factor, redo) AS
+
+SELECT ChoreLog.chore_id, ChoreLog.week,
+    IIF(ChoreRatingRule.action = 'redo', 0.0, ChoreRatingRule.score_factor),
+    IIF(ChoreRatingRule.action = 'redo', 1, 0)
+FROM ChoreLog
+JOIN ChoreRatingRule
+    ON ChoreRatingRule.chore_id = ChoreLog.chore_id
+    AND ChoreRatingRule.week <= ChoreLog.week
+JOIN Rating
+    ON Rating.for_chore_log_chore_id = ChoreLog.chore_id
+    AND Rating.week = ChoreLog.week
+GROUP BY ChoreLog.chore_id, ChoreLog.week
+HAVING AVG(Rating.rating) < ChoreRatingRule.min_rating;
+"#,
+            r#"
+-- replaced by the TenantScore weighting the PoorlyRatedChoreLogs
+DROP VIEW TenantScore;
+"#,
+            r#"
+-- get score of tenant for a chore, including their ScoreAdjustments and StartScore
+-- Every week's ChoreLog contribution is multiplied with its ScoreWeight and the score_factor of a
+-- PoorlyRatedChoreLog. As the ChoreLog contributions of a week add up to 0, so do the weighted
+-- ones. ScoreAdjustments and StartScores are added on top and don't add up to 0.
+--
+-- tenant_id the tenant in question
+-- chore_id the chore in question
+-- score the score of the tenant for that score
+CREATE VIEW TenantScore (tenant_id, chore_id, score) AS
+
+SELECT Tenant.id, Chore.id,
+    COALESCE(SUM(
+        (ProfitingTenant.did_work * 1 - (1-ProfitingTenant.did_work) * 1/CAST(TotalProfitingTenant.count - 1 AS FLOAT))
+        * COALESCE(ScoreWeight.weight, 1.0)
+        * COALESCE(PoorlyRatedChoreLog.score_factor, 1.0)
+    ), 0)
+    + COALESCE((
+        SELECT SUM(
+            IIF(
+                ScoreAdjustment.chore_id IS NULL,
+                ScoreAdjustment.amount / (SELECT COUNT(*) FROM Chore WHERE Chore.active = 1),
+                ScoreAdjustment.amount
+            )
+            * COALESCE((SELECT ScoreWeight.weight FROM ScoreWeight WHERE ScoreWeight.week = ScoreAdjustment.week), 1.0)
+        )
+        FROM ScoreAdjustment
+        WHERE ScoreAdjustment.tenant_id = Tenant.id
+        AND (
+            ScoreAdjustment.chore_id = Chore.id
+            OR (ScoreAdjustment.chore_id IS NULL AND Chore.active = 1)
+        )
+    ), 0)
+    + COALESCE((
+        SELECT StartScore.score
+            * COALESCE((SELECT ScoreWeight.weight FROM ScoreWeight WHERE ScoreWeight.week = StartScore.week), 1.0)
+        FROM StartScore
+        WHERE StartScore.tenant_id = Tenant.id
+        AND StartScore.chore_id = Chore.id
+    ), 0)
+FROM Tenant, Chore
+LEFT JOIN ProfitingTenant ON ProfitingTenant.tenant_id = Tenant.id AND ProfitingTenant.chore_id = Chore.id
+LEFT JOIN TotalProfitingTenant ON ProfitingTenant.chore_id = TotalProfitingTenant.chore_id
+    AND ProfitingTenant.week = TotalProfitingTenant.week
+LEFT JOIN ScoreWeight ON ScoreWeight.week = ProfitingTenant.week
+LEFT JOIN PoorlyRatedChoreLog ON PoorlyRatedChoreLog.chore_id = ProfitingTenant.chore_id
+    AND PoorlyRatedChoreLog.week = ProfitingTenant.week
+GROUP BY Tenant.id, Chore.id
+ORDER BY Chore.id, Tenant.id;
+"#,
+            r#"
+-- replaced by the ScoreWeight ignoring invalid score_decay KeyValues
+-- TenantScore refers to ScoreWeight by name and thus stays valid
+DROP VIEW ScoreWeight;
+"#,
+            r#"
+-- the weight of the ChoreLogs and ScoreAdjustments of a week in the TenantScore
+-- The current week has the weight 1, older weeks lose weight according to the score_decay KeyValues.
+-- Weeks after the current week and before the first ChoreLog or ScoreAdjustment aren't listed,
+-- their weight is 1.
+-- Just like get_score_decay_internal, an invalid decay is treated like no decay:
+-- score_decay needs to be half-life or window, score_decay_weeks a positive integer and for
+-- half-life score_decay_factor a number in (0, 1).
+-- Without a current_week no week is listed. Storing a decay stores the current week, too.
+--
+-- SQLite doesn't have pow by default, so the weights are multiplied week by week.
+CREATE VIEW ScoreWeight (week, weight) AS
+
+WITH RECURSIVE
+RawSetting (decay, weeks, factor) AS (
+    SELECT
+        (SELECT KeyValue.value FROM KeyValue WHERE KeyValue.key = 'score_decay'),
+        (SELECT KeyValue.value FROM KeyValue WHERE KeyValue.key = 'score_decay_weeks'),
+        (SELECT KeyValue.value FROM KeyValue WHERE KeyValue.key = 'score_decay_factor')
+),
+ValidSetting (decay, weeks, factor) AS (
+    SELECT RawSetting.decay,
+        CAST(RawSetting.weeks AS INTEGER),
+        IIF(RawSetting.decay = 'half-life', CAST(RawSetting.factor AS REAL), 1.0)
+    FROM RawSetting
+        WHERE RawSetting.weeks != '' AND RawSetting.weeks NOT GLOB '*[^0-9]*'
+        AND CAST(RawSetting.weeks AS INTEGER) >= 1
+        AND (
+            RawSetting.decay = 'window'
+            OR (
+                RawSetting.decay = 'half-life'
+                AND RawSetting.factor != '' AND RawSetting.factor NOT GLOB '*[^0-9.]*'
+                AND CAST(RawSetting.factor AS REAL) > 0.0 AND CAST(RawSetting.factor AS REAL) < 1.0
+            )
+        )
+),
+Setting (current_week, decay, weeks, factor) AS (
+    SELECT
+        (SELECT CAST(KeyValue.value AS INTEGER) FROM KeyValue WHERE KeyValue.key = 'current_week'),
+        (SELECT ValidSetting.decay FROM ValidSetting),
+        (SELECT ValidSetting.weeks FROM ValidSetting),
+        COALESCE((SELECT ValidSetting.factor FROM ValidSetting), 1.0)
+),
+Weight (week, weight) AS (
+    SELECT Setting.current_week, 1.0
+    FROM Setting
+        WHERE Setting.current_week IS NOT NULL
+    UNION ALL
+    SELECT Weight.week - 1, Weight.weight * Setting.factor
+    FROM Weight, Setting
+        WHERE Weight.week > (
+            SELECT MIN(week) FROM (
+                SELECT ChoreLog.week FROM ChoreLog
+                UNION ALL
+                SELECT ScoreAdjustment.week FROM ScoreAdjustment
+            )
+        )
+)
+SELECT Weight.week,
+    IIF(Setting.decay = 'window' AND Setting.current_week - Weight.week >= Setting.weeks, 0.0, Weight.weight)
+FROM Weight, Setting;
+"#,
+            r#"
+-- from_tenant_id the tenant whose score has been passed on with the room start_score, else NULL
+-- That tenant loses the passed on score, so it isn't counted twice when they move back in.
+ALTER TABLE StartScore ADD COLUMN from_tenant_id INTEGER REFERENCES Tenant (id);
+"#,
+            r#"
+-- replaced by the TenantScore subtracting passed on StartScores
+DROP VIEW TenantScore;
+"#,
+            r#"
+-- get score of tenant for a chore, including their ScoreAdjustments and StartScore
+-- A StartScore passed on from a tenant is subtracted from that tenant's score.
+-- Every week's ChoreLog contribution is multiplied with its ScoreWeight and the score_factor of a
+-- PoorlyRatedChoreLog. As the ChoreLog contributions of a week add up to 0, so do the weighted
+-- ones. ScoreAdjustments and StartScores are added on top and don't add up to 0.
+--
+-- tenant_id the tenant in question
+-- chore_id the chore in question
+-- score the score of the tenant for that score
+CREATE VIEW TenantScore (tenant_id, chore_id, score) AS
+
+SELECT Tenant.id, Chore.id,
+    COALESCE(SUM(
+        (ProfitingTenant.did_work * 1 - (1-ProfitingTenant.did_work) * 1/CAST(TotalProfitingTenant.count - 1 AS FLOAT))
+        * COALESCE(ScoreWeight.weight, 1.0)
+        * COALESCE(PoorlyRatedChoreLog.score_factor, 1.0)
+    ), 0)
+    + COALESCE((
+        SELECT SUM(
+            IIF(
+                ScoreAdjustment.chore_id IS NULL,
+                ScoreAdjustment.amount / (SELECT COUNT(*) FROM Chore WHERE Chore.active = 1),
+                ScoreAdjustment.amount
+            )
+            * COALESCE((SELECT ScoreWeight.weight FROM ScoreWeight WHERE ScoreWeight.week = ScoreAdjustment.week), 1.0)
+        )
+        FROM ScoreAdjustment
+        WHERE ScoreAdjustment.tenant_id = Tenant.id
+        AND (
+            ScoreAdjustment.chore_id = Chore.id
+            OR (ScoreAdjustment.chore_id IS NULL AND Chore.active = 1)
+        )
+    ), 0)
+    + COALESCE((
+        SELECT StartScore.score
+            * COALESCE((SELECT ScoreWeight.weight FROM ScoreWeight WHERE ScoreWeight.week = StartScore.week), 1.0)
+        FROM StartScore
+        WHERE StartScore.tenant_id = Tenant.id
+        AND StartScore.chore_id = Chore.id
+    ), 0)
+    - COALESCE((
+        SELECT SUM(
+            StartScore.score
+            * COALESCE((SELECT ScoreWeight.weight FROM ScoreWeight WHERE ScoreWeight.week = StartScore.week), 1.0)
+        )
+        FROM StartScore
+        WHERE StartScore.from_tenant_id = Tenant.id
+        AND StartScore.chore_id = Chore.id
+    ), 0)
+FROM Tenant, Chore
+LEFT JOIN ProfitingTenant ON ProfitingTenant.tenant_id = Tenant.id AND ProfitingTenant.chore_id = Chore.id
+LEFT JOIN TotalProfitingTenant ON ProfitingTenant.chore_id = TotalProfitingTenant.chore_id
+    AND ProfitingTenant.week = TotalProfitingTenant.week
+LEFT JOIN ScoreWeight ON ScoreWeight.week = ProfitingTenant.week
+LEFT JOIN PoorlyRatedChoreLog ON PoorlyRatedChoreLog.chore_id = ProfitingTenant.chore_id
+    AND PoorlyRatedChoreLog.week = ProfitingTenant.week
+GROUP BY Tenant.id, Chore.id
+ORDER BY Chore.id, Tenant.id;
+"#,
+            r#"
+-- replaced by the ScoreWeight also listing the weeks of StartScores older than any ChoreLog
+-- TenantScore refers to ScoreWeight by name and thus stays valid
+DROP VIEW ScoreWeight;
+"#,
+            r#"
+-- the weight of the ChoreLogs, ScoreAdjustments and StartScores of a week in the TenantScore
+-- The current week has the weight 1, older weeks lose weight according to the score_decay KeyValues.
+-- Weeks after the current week and before the first ChoreLog, ScoreAdjustment or StartScore
+-- aren't listed, their weight is 1.
+-- Just like get_score_decay_internal, an invalid decay is treated like no decay:
+-- score_decay needs to be half-life or window, score_decay_weeks a positive integer and for
+-- half-life score_decay_factor a number in (0, 1).
+-- Without a current_week no week is listed. Storing a decay stores the current week, too.
+--
+-- SQLite doesn't have pow by default, so the weights are multiplied week by week.
+CREATE VIEW ScoreWeight (week, weight) AS
+
+WITH RECURSIVE
+RawSetting (decay, weeks, factor) AS (
+    SELECT
+        (SELECT KeyValue.value FROM KeyValue WHERE KeyValue.key = 'score_decay'),
+        (SELECT KeyValue.value FROM KeyValue WHERE KeyValue.key = 'score_decay_weeks'),
+        (SELECT KeyValue.value FROM KeyValue WHERE KeyValue.key = 'score_decay_factor')
+),
+ValidSetting (decay, weeks, factor) AS (
+    SELECT RawSetting.decay,
+        CAST(RawSetting.weeks AS INTEGER),
+        IIF(RawSetting.decay = 'half-life', CAST(RawSetting.factor AS REAL), 1.0)
+    FROM RawSetting
+        WHERE RawSetting.weeks != '' AND RawSetting.weeks NOT GLOB '*[^0-9]*'
+        AND CAST(RawSetting.weeks AS INTEGER) >= 1
+        AND (
+            RawSetting.decay = 'window'
+            OR (
+                RawSetting.decay = 'half-life'
+                AND RawSetting.factor != '' AND RawSetting.factor NOT GLOB '*[^0-9.]*'
+                AND CAST(RawSetting.factor AS REAL) > 0.0 AND CAST(RawSetting.factor AS REAL) < 1.0
+            )
+        )
+),
+Setting (current_week, decay, weeks, factor) AS (
+    SELECT
+        (SELECT CAST(KeyValue.value AS INTEGER) FROM KeyValue WHERE KeyValue.key = 'current_week'),
+        (SELECT ValidSetting.decay FROM ValidSetting),
+        (SELECT ValidSetting.weeks FROM ValidSetting),
+        COALESCE((SELECT ValidSetting.factor FROM ValidSetting), 1.0)
+),
+Weight (week, weight) AS (
+    SELECT Setting.current_week, 1.0
+    FROM Setting
+        WHERE Setting.current_week IS NOT NULL
+    UNION ALL
+    SELECT Weight.week - 1, Weight.weight * Setting.factor
+    FROM Weight, Setting
+        WHERE Weight.week > (
+            SELECT MIN(week) FROM (
+                SELECT ChoreLog.week FROM ChoreLog
+                UNION ALL
+                SELECT ScoreAdjustment.week FROM ScoreAdjustment
+                UNION ALL
+                SELECT StartScore.week FROM StartScore
+            )
+        )
+)
+SELECT Weight.week,
+    IIF(Setting.decay = 'window' AND Setting.current_week - Weight.week >= Setting.weeks, 0.0, Weight.weight)
+FROM Weight, Setting;
+"#,
         ];
 
         let mut next_migration = self.get_user_version().await?;
@@ -357,16 +935,41 @@ FROM TenantExemption TenantExemptionA, TenantExemption TenantExemptionB
         if !sqlx::query(
             r#"
 SELECT *
-FROM ChoreLog
-    WHERE ChoreLog.completed = 1
-    AND ChoreLog.rating_poll_id IS NULL;
+FROM TenantChoreLimit
+    WHERE TenantChoreLimit.max_chores_per_week < 1;
+"#,
+        )
+        .fetch_all(&mut self.con)
+        .await?
+        .is_empty()
+        {
+            bail!("there is a TenantChoreLimit allowing less than one chore per week");
+        }
+        if !sqlx::query(
+            r#"
+SELECT *
+FROM TenantCoolDown
+    WHERE TenantCoolDown.weeks < 1;
+"#,
+        )
+        .fetch_all(&mut self.con)
+        .await?
+        .is_empty()
+        {
+            bail!("there is a TenantCoolDown shorter than one week");
+        }
+        if !sqlx::query(
+            r#"
+SELECT *
+FROM TenantChorePreference
+    WHERE TenantChorePreference.preference NOT IN (-1, 1);
 "#,
         )
         .fetch_all(&mut self.con)
         .await?
         .is_empty()
         {
-            bail!("there is a completed ChoreLog without a poll id");
+            bail!("there is a TenantChorePreference that is neither 1 nor -1");
         }
         Ok(())
     }
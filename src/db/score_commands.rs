@@ -0,0 +1,134 @@
+use crate::db::*;
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use sqlx::Row;
+use tabled::Tabled;
+
+/// a manual credit or debit of a tenant's score
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ScoreAdjustment {
+    pub week: Week,
+    pub tenant: String,
+    /// None when the adjustment applies to all chores
+    pub chore: Option<String>,
+    pub amount: f64,
+    pub reason: String,
+}
+
+impl Db {
+    /// Get all ScoreAdjustments, optionally only the ones since some week.
+    pub async fn get_score_adjustments(
+        &mut self,
+        since: Option<Week>,
+    ) -> Result<Vec<ScoreAdjustment>> {
+        let sql_rows = sqlx::query(
+            r#"
+SELECT ScoreAdjustment.week, Tenant.name, Chore.name, ScoreAdjustment.amount, ScoreAdjustment.reason
+FROM ScoreAdjustment
+JOIN Tenant
+    ON Tenant.id = ScoreAdjustment.tenant_id
+LEFT JOIN Chore
+    ON Chore.id = ScoreAdjustment.chore_id
+    WHERE ?1 IS NULL OR ScoreAdjustment.week >= ?1
+ORDER BY ScoreAdjustment.week, ScoreAdjustment.id;
+"#,
+        )
+        .bind(since.map(|w| w.db_week()))
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        sql_rows
+            .into_iter()
+            .map(|r| {
+                Ok(ScoreAdjustment {
+                    week: Week::from_db(r.try_get(0)?),
+                    tenant: r.try_get(1)?,
+                    chore: r.try_get(2)?,
+                    amount: r.try_get(3)?,
+                    reason: r.try_get(4)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Print a nice list of the ScoreAdjustments, optionally only the ones since some week.
+    pub async fn list_score_adjustments(&mut self, since: Option<Week>) -> Result<ReplyMsg> {
+        #[derive(Tabled)]
+        struct ScoreAdjustmentRow {
+            week: Week,
+            tenant: String,
+            chore: String,
+            amount: String,
+            reason: String,
+        }
+        let rows = self
+            .get_score_adjustments(since)
+            .await?
+            .into_iter()
+            .map(|a| ScoreAdjustmentRow {
+                week: a.week,
+                tenant: a.tenant,
+                chore: a
+                    .chore
+                    .unwrap_or_else(|| self.locale.all_chores().to_string()),
+                amount: format!("{:+.2}", a.amount),
+                reason: a.reason,
+            });
+        Ok(ReplyMsg::from_blocks(vec![
+            Block::heading(1, self.locale.heading(Heading::ScoreAdjustments)),
+            Block::table_with_header(
+                self.locale.table_header(TableHeader::ScoreAdjustments),
+                rows,
+            ),
+        ]))
+    }
+
+    /// Credit a tenant for extra work or debit them, i.e., add amount to their score.
+    ///
+    /// Without a chore the amount is split evenly among all active chores.
+    pub async fn adjust_score(
+        &mut self,
+        tenant: &str,
+        chore: Option<&str>,
+        amount: f64,
+        reason: &str,
+    ) -> Result<ReplyMsg> {
+        let tenant = Self::capitalize_tenant_name(tenant);
+        let Some(tenant_id) = self.get_tenant_id(&tenant).await? else {
+            bail!(self.locale.tenant_doesnt_exist(&tenant));
+        };
+        let chore_id = match chore {
+            Some(chore) => match self.get_chore_id(chore).await? {
+                Some(chore_id) => Some(chore_id),
+                None => bail!(self.locale.chore_doesnt_exist(chore)),
+            },
+            None => None,
+        };
+        if !amount.is_finite() || amount == 0.0 {
+            bail!(self.locale.score_amount_zero());
+        }
+        if reason.trim().is_empty() {
+            bail!(self.locale.score_reason_empty());
+        }
+
+        let affected_rows = sqlx::query(
+            r#"
+INSERT INTO ScoreAdjustment VALUES (NULL, ?1, ?2, ?3, ?4, ?5);
+"#,
+        )
+        .bind(tenant_id)
+        .bind(chore_id)
+        .bind(amount)
+        .bind(reason.trim())
+        .bind(self.get_week_internal().await.db_week())
+        .execute(&mut self.con)
+        .await?
+        .rows_affected();
+        self.integrity_check().await?;
+        if affected_rows != 1 {
+            bail!(self.locale.affected_rows(affected_rows));
+        }
+        Ok(self.list_score_adjustments(None).await? + self.list_tenants().await?)
+    }
+}
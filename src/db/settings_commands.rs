@@ -0,0 +1,178 @@
+use crate::db::*;
+
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+use std::fmt::Display;
+use tabled::Tabled;
+
+/// how old ChoreLogs lose their weight in the scores
+///
+/// Without decay a tenant who did a lot of chores years ago is shielded from chores forever.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScoreDecay {
+    None,
+    /// a ChoreLog's weight halves every weeks weeks
+    HalfLife {
+        weeks: u32,
+    },
+    /// only the ChoreLogs of the last weeks weeks count
+    Window {
+        weeks: u32,
+    },
+}
+
+/// the score a tenant starts with when they move into the flat for the first time
+///
+/// New tenants starting at 0 while everyone else is far below skews the probabilities of being
+/// assigned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, ValueEnum)]
+pub enum StartScorePolicy {
+    /// start at 0
+    #[default]
+    Zero,
+    /// start at the mean score of the other tenants living in the flat
+    Mean,
+    /// start at the score of the previous tenant of the room who doesn't live in the flat anymore
+    Room,
+}
+
+impl Display for StartScorePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StartScorePolicy::Zero => write!(f, "zero"),
+            StartScorePolicy::Mean => write!(f, "mean"),
+            StartScorePolicy::Room => write!(f, "room"),
+        }
+    }
+}
+
+impl FromStr for StartScorePolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "zero" => Ok(StartScorePolicy::Zero),
+            "mean" => Ok(StartScorePolicy::Mean),
+            "room" => Ok(StartScorePolicy::Room),
+            _ => bail!(
+                "start score '{}' is not supported, use zero, mean or room",
+                s
+            ),
+        }
+    }
+}
+
+impl Db {
+    /// Print a nice list of the settings that can be changed at runtime.
+    pub async fn list_settings(&mut self) -> Result<ReplyMsg> {
+        #[derive(Tabled)]
+        struct SettingRow {
+            setting: String,
+            value: String,
+        }
+        let rows = vec![
+            SettingRow {
+                setting: "weeks-to-plan".to_string(),
+                value: self.weeks_to_plan.to_string(),
+            },
+            SettingRow {
+                setting: "gamma".to_string(),
+                value: self.gamma.to_string(),
+            },
+            SettingRow {
+                setting: "try-exclude-busy-tenants".to_string(),
+                value: self.try_exclude_busy_tenants.to_string(),
+            },
+            SettingRow {
+                setting: "locale".to_string(),
+                value: self.locale.to_string(),
+            },
+            SettingRow {
+                setting: "score-decay".to_string(),
+                value: self
+                    .locale
+                    .score_decay(self.get_score_decay_internal().await),
+            },
+            SettingRow {
+                setting: "start-score".to_string(),
+                value: self.get_start_score_policy_internal().await.to_string(),
+            },
+        ];
+        Ok(ReplyMsg::from_blocks(vec![
+            Block::heading(1, self.locale.heading(Heading::Settings)),
+            Block::table_with_header(self.locale.table_header(TableHeader::Settings), rows),
+        ]))
+    }
+
+    /// Store how many weeks to plan ahead in the database and plan the new weeks.
+    /// This overrides the weeks_to_plan the database has been created with, even after a restart.
+    pub async fn set_weeks_to_plan<F>(
+        &mut self,
+        weeks_to_plan: u32,
+        fmt_replan_cmd: F,
+    ) -> Result<ReplyMsg>
+    where
+        F: Fn(&str, Week) -> String,
+    {
+        if weeks_to_plan > MAX_WEEKS_TO_PLAN {
+            bail!(self.locale.weeks_to_plan_out_of_range(MAX_WEEKS_TO_PLAN));
+        }
+        self.set_weeks_to_plan_internal(weeks_to_plan).await;
+        self.weeks_to_plan = weeks_to_plan;
+        Ok(self.list_settings().await? + self.update_plan(fmt_replan_cmd).await?)
+    }
+
+    /// Store a new gamma in the database.
+    /// This overrides the gamma the database has been created with, even after a restart.
+    ///
+    /// Only chores planned from now on are affected.
+    pub async fn set_gamma(&mut self, gamma: f64) -> Result<ReplyMsg> {
+        if !(0.0..=1.0).contains(&gamma) {
+            bail!(self.locale.gamma_out_of_range());
+        }
+        self.set_gamma_internal(gamma).await;
+        self.gamma = gamma;
+        self.list_settings().await
+    }
+
+    /// Store whether to try excluding busy tenants in the database.
+    /// This overrides the setting the database has been created with, even after a restart.
+    ///
+    /// Only chores planned from now on are affected.
+    pub async fn set_try_exclude_busy_tenants(
+        &mut self,
+        try_exclude_busy_tenants: bool,
+    ) -> Result<ReplyMsg> {
+        self.set_try_exclude_busy_tenants_internal(try_exclude_busy_tenants)
+            .await;
+        self.try_exclude_busy_tenants = try_exclude_busy_tenants;
+        self.list_settings().await
+    }
+
+    /// Store the language of the messages in the database.
+    /// This overrides the locale the database has been created with, even after a restart.
+    pub async fn set_locale(&mut self, locale: Locale) -> Result<ReplyMsg> {
+        self.set_locale_internal(locale).await;
+        self.locale = locale;
+        self.list_settings().await
+    }
+
+    /// Store how old ChoreLogs lose their weight in the scores.
+    ///
+    /// All scores change right away, already planned chores stay as they are.
+    pub async fn set_score_decay(&mut self, score_decay: ScoreDecay) -> Result<ReplyMsg> {
+        if let ScoreDecay::HalfLife { weeks: 0 } | ScoreDecay::Window { weeks: 0 } = score_decay {
+            bail!(self.locale.score_decay_weeks_zero());
+        }
+        self.set_score_decay_internal(score_decay).await;
+        Ok(self.list_settings().await? + self.list_tenants().await?)
+    }
+
+    /// Store what score tenants start with when they move into the flat for the first time.
+    ///
+    /// Tenants who already moved in keep their score.
+    pub async fn set_start_score_policy(&mut self, policy: StartScorePolicy) -> Result<ReplyMsg> {
+        self.set_start_score_policy_internal(policy).await;
+        self.list_settings().await
+    }
+}
@@ -0,0 +1,45 @@
+use crate::db::*;
+
+use anyhow::Result;
+
+impl Db {
+    /// Create an independent in-memory copy of this database.
+    ///
+    /// Nothing done to the copy affects this database.
+    /// The copy uses the provided parameters instead of the ones of this database.
+    pub async fn copy_into_memory(
+        &mut self,
+        gamma: f64,
+        try_exclude_busy_tenants: bool,
+        seed: u64,
+    ) -> Result<Db> {
+        if !(0.0..=1.0).contains(&gamma) {
+            bail!(self.locale.gamma_out_of_range());
+        }
+        let mut image = self.con.serialize(None).await?;
+        // The file format version numbers are 2 for databases in WAL mode.
+        // An in-memory database can't use WAL, so switch the copy back to the legacy journal.
+        if image.len() > 19 {
+            image[18] = 1;
+            image[19] = 1;
+        }
+        let mut con = SqliteConnectOptions::from_str("sqlite::memory:")?
+            .foreign_keys(true)
+            .connect()
+            .await?;
+        con.deserialize(None, image, false).await?;
+
+        let mut copy = Db {
+            con,
+            fallback_week: self.get_week_internal().await,
+            weeks_to_plan: self.weeks_to_plan,
+            gamma,
+            try_exclude_busy_tenants,
+            locale: self.locale,
+            rng: StdRng::seed_from_u64(seed),
+            debug: false,
+        };
+        copy.integrity_check().await?;
+        Ok(copy)
+    }
+}
@@ -0,0 +1,220 @@
+use crate::db::*;
+
+use anyhow::Result;
+use std::collections::HashMap;
+
+impl Db {
+    /// Preview how fair the plan turns out over the next weeks without changing anything.
+    ///
+    /// The simulation runs on an in-memory copy of the database, planning and advancing one week
+    /// at a time.
+    /// gamma and try_exclude_busy_tenants default to this database's parameters.
+    pub async fn simulate(
+        &mut self,
+        weeks: u32,
+        gamma: Option<f64>,
+        try_exclude_busy_tenants: Option<bool>,
+        seed: u64,
+    ) -> Result<ReplyMsg> {
+        let gamma = gamma.unwrap_or(self.gamma);
+        let try_exclude_busy_tenants =
+            try_exclude_busy_tenants.unwrap_or(self.try_exclude_busy_tenants);
+        let mut sim = self
+            .copy_into_memory(gamma, try_exclude_busy_tenants, seed)
+            .await?;
+        // Each simulated week needs to be planned at least.
+        sim.weeks_to_plan = u32::max(sim.weeks_to_plan, 1);
+
+        let start_week = sim.get_week_internal().await;
+        let end_week = Week::from_db(start_week.db_week() + weeks as i64);
+        // Just like for the scores after, the weeks already planned ahead don't count yet.
+        let mut before = self
+            .copy_into_memory(gamma, try_exclude_busy_tenants, seed)
+            .await?;
+        before.delete_chore_logs_from(start_week).await?;
+        let scores_before = before.get_current_tenant_scores(start_week).await?;
+        for _ in 0..weeks {
+            sim.update_plan(|_, _| String::new()).await?;
+            let week = sim.get_week_internal().await;
+            sim.set_week_internal(Week::from_db(week.db_week() + 1))
+                .await;
+        }
+        // The last weeks planned ahead aren't part of the simulation and would skew the scores.
+        sim.delete_chore_logs_from(end_week).await?;
+        let scores_after = sim.get_current_tenant_scores(end_week).await?;
+
+        let chores = sqlx::query(
+            r#"
+SELECT Chore.name
+FROM Chore
+WHERE Chore.active = 1
+ORDER BY Chore.id;
+"#,
+        )
+        .fetch_all(&mut sim.con)
+        .await?
+        .into_iter()
+        .map(|r| -> Result<String> { Ok(r.try_get(0)?) })
+        .collect::<Result<Vec<String>>>()?;
+        sim.integrity_check().await?;
+
+        // all tenants living here at some point during the simulation
+        let tenants = sqlx::query(
+            r#"
+SELECT Tenant.name
+FROM Tenant
+WHERE EXISTS (
+    SELECT *
+    FROM LivesIn
+    WHERE LivesIn.tenant_id = Tenant.id
+    AND LivesIn.move_in_week < ?2
+    AND (LivesIn.move_out_week IS NULL OR LivesIn.move_out_week > ?1)
+)
+ORDER BY Tenant.name;
+"#,
+        )
+        .bind(start_week.db_week())
+        .bind(end_week.db_week())
+        .fetch_all(&mut sim.con)
+        .await?
+        .into_iter()
+        .map(|r| -> Result<String> { Ok(r.try_get(0)?) })
+        .collect::<Result<Vec<String>>>()?;
+        sim.integrity_check().await?;
+
+        let chore_logs = sqlx::query(
+            r#"
+SELECT Tenant.name, Chore.name, ChoreLog.week
+FROM ChoreLog
+JOIN Tenant
+    ON Tenant.id = ChoreLog.worker
+JOIN Chore
+    ON Chore.id = ChoreLog.chore_id
+WHERE ChoreLog.week >= ?1
+AND ChoreLog.week < ?2
+ORDER BY ChoreLog.week;
+"#,
+        )
+        .bind(start_week.db_week())
+        .bind(end_week.db_week())
+        .fetch_all(&mut sim.con)
+        .await?
+        .into_iter()
+        .map(|r| -> Result<(String, String, i64)> {
+            Ok((r.try_get(0)?, r.try_get(1)?, r.try_get(2)?))
+        })
+        .collect::<Result<Vec<(String, String, i64)>>>()?;
+        sim.integrity_check().await?;
+
+        // (tenant, chore) -> assignment count
+        let mut counts = HashMap::<(String, String), u32>::new();
+        // tenant -> (last week with a chore, current streak, longest streak)
+        let mut streaks = HashMap::<String, (i64, u32, u32)>::new();
+        for (tenant, chore, week) in chore_logs {
+            *counts.entry((tenant.clone(), chore)).or_default() += 1;
+            let (last_week, streak, longest_streak) =
+                streaks.entry(tenant).or_insert((i64::MIN, 0, 0));
+            if *last_week == week {
+                continue;
+            }
+            *streak = if *last_week == week - 1 {
+                *streak + 1
+            } else {
+                1
+            };
+            *longest_streak = u32::max(*longest_streak, *streak);
+            *last_week = week;
+        }
+
+        let (first_column, last_columns) = self
+            .locale
+            .table_header(TableHeader::Simulation)
+            .split_at(1);
+        let header = first_column
+            .iter()
+            .map(|c| c.to_string())
+            .chain(chores.iter().cloned())
+            .chain(last_columns.iter().map(|c| c.to_string()))
+            .collect();
+        let mut rows = Vec::new();
+        for tenant in tenants {
+            let chore_counts: Vec<u32> = chores
+                .iter()
+                .map(|chore| *counts.get(&(tenant.clone(), chore.clone())).unwrap_or(&0))
+                .collect();
+            let total: u32 = chore_counts.iter().sum();
+            let longest_streak = streaks.get(&tenant).map_or(0, |(_, _, l)| *l);
+            rows.push(
+                [tenant]
+                    .into_iter()
+                    .chain(chore_counts.into_iter().map(|c| c.to_string()))
+                    .chain([total.to_string(), longest_streak.to_string()])
+                    .collect(),
+            );
+        }
+
+        Ok(ReplyMsg::from_blocks(vec![
+            Block::heading(1, self.locale.heading(Heading::Simulation)),
+            Block::text(&self.locale.simulated(
+                weeks,
+                start_week,
+                end_week,
+                gamma,
+                try_exclude_busy_tenants,
+                seed,
+                Self::score_variance(&scores_before),
+                Self::score_variance(&scores_after),
+            )),
+            Block::Blank,
+            Block::Table { header, rows },
+        ]))
+    }
+
+    /// Delete the ChoreLogs of some week and all weeks after it.
+    async fn delete_chore_logs_from(&mut self, week: Week) -> Result<()> {
+        sqlx::query(
+            r#"
+DELETE
+FROM ChoreLog
+WHERE ChoreLog.week >= ?1;
+"#,
+        )
+        .bind(week.db_week())
+        .execute(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        Ok(())
+    }
+
+    /// Get the total score of every tenant living here in some week.
+    async fn get_current_tenant_scores(&mut self, week: Week) -> Result<Vec<f64>> {
+        let sql_rows = sqlx::query(
+            r#"
+SELECT TenantScoreSUM.score
+FROM TenantScoreSUM
+JOIN LivesIn
+    ON TenantScoreSUM.tenant_id = LivesIn.tenant_id
+    AND LivesIn.move_in_week <= ?1
+    AND (LivesIn.move_out_week IS NULL OR LivesIn.move_out_week > ?1);
+"#,
+        )
+        .bind(week.db_week())
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        sql_rows
+            .into_iter()
+            .map(|r| -> Result<f64> { Ok(r.try_get(0)?) })
+            .collect()
+    }
+
+    /// Calculate the variance of some scores.
+    fn score_variance(scores: &[f64]) -> f64 {
+        if scores.is_empty() {
+            return 0.0;
+        }
+        let n = scores.len() as f64;
+        let mean = scores.iter().sum::<f64>() / n;
+        scores.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n
+    }
+}
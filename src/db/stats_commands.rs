@@ -0,0 +1,360 @@
+use crate::db::*;
+
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tabled::Tabled;
+
+/// how to order the tenants and chores of the statistics
+///
+/// Numbers are sorted descending, names ascending.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, ValueEnum)]
+pub enum StatsSort {
+    #[default]
+    Name,
+    Done,
+    Rating,
+    Unwilling,
+}
+
+/// what a tenant did in the weeks of the statistics
+#[derive(Serialize, Debug, PartialEq)]
+pub struct TenantStats {
+    pub tenant: String,
+    /// how many ChoreLogs the tenant did per chore, chores they didn't do are left out
+    pub done: BTreeMap<String, u32>,
+    /// the average of the average ratings of their ChoreLogs, None when none has been rated
+    pub rating: Option<f64>,
+    /// how many weeks they marked themselves unwilling
+    pub unwilling_weeks: u32,
+    /// the reasons of the exemptions they held at some point
+    pub exemptions: Vec<String>,
+}
+
+impl TenantStats {
+    fn total_done(&self) -> u32 {
+        self.done.values().sum()
+    }
+}
+
+/// how a chore went in the weeks of the statistics
+///
+/// The ratings are the averages of the ChoreLogs' average ratings, None when none has been rated.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ChoreStats {
+    pub chore: String,
+    pub done: u32,
+    pub rating: Option<f64>,
+    /// the rating in the first half of the weeks
+    pub first_half_rating: Option<f64>,
+    /// the rating in the second half of the weeks
+    pub second_half_rating: Option<f64>,
+}
+
+impl ChoreStats {
+    /// How much better the chore has been done in the second half, None without ratings in both.
+    pub fn trend(&self) -> Option<f64> {
+        Some(self.second_half_rating? - self.first_half_rating?)
+    }
+}
+
+/// per tenant and per chore metrics of some weeks
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Stats {
+    pub start_week: Week,
+    pub end_week: Week,
+    pub tenants: Vec<TenantStats>,
+    pub chores: Vec<ChoreStats>,
+}
+
+/// Order two optional numbers descending, None last.
+fn cmp_desc<T: PartialOrd>(a: Option<T>, b: Option<T>) -> std::cmp::Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => b.partial_cmp(&a).unwrap_or(std::cmp::Ordering::Equal),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    }
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    match values.is_empty() {
+        true => None,
+        false => Some(values.iter().sum::<f64>() / values.len() as f64),
+    }
+}
+
+fn display_opt_rating(rating: &Option<f64>) -> String {
+    rating.map(|r| format!("{:.2}", r)).unwrap_or_default()
+}
+
+impl Db {
+    /// Get the statistics of the weeks from start_week to end_week, both included.
+    /// Without end_week everything until last week is included.
+    ///
+    /// Only tenants who lived here in that time are listed.
+    pub async fn get_stats(
+        &mut self,
+        start_week: Week,
+        end_week: Option<Week>,
+        sort: StatsSort,
+    ) -> Result<Stats> {
+        let end_week = match end_week {
+            Some(end_week) => end_week,
+            None => Week::from_db(self.get_week_internal().await.db_week() - 1),
+        };
+        if end_week.db_week() < start_week.db_week() {
+            bail!(self.locale.stats_weeks_reversed(start_week, end_week));
+        }
+
+        let sql_rows = sqlx::query(
+            r#"
+SELECT DISTINCT Tenant.name
+FROM Tenant
+JOIN LivesIn
+    ON LivesIn.tenant_id = Tenant.id
+    AND LivesIn.move_in_week <= ?2
+    AND (LivesIn.move_out_week IS NULL OR LivesIn.move_out_week > ?1)
+ORDER BY Tenant.name;
+"#,
+        )
+        .bind(start_week.db_week())
+        .bind(end_week.db_week())
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        let mut tenants = sql_rows
+            .into_iter()
+            .map(|r| {
+                Ok(TenantStats {
+                    tenant: r.try_get(0)?,
+                    done: BTreeMap::new(),
+                    rating: None,
+                    unwilling_weeks: 0,
+                    exemptions: vec![],
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // every ChoreLog of the weeks with its average rating
+        let sql_rows = sqlx::query(
+            r#"
+SELECT Chore.name, ChoreLog.week, Tenant.name, CAST(AVG(Rating.rating) AS FLOAT)
+FROM ChoreLog
+JOIN Chore ON Chore.id = ChoreLog.chore_id
+JOIN Tenant ON Tenant.id = ChoreLog.worker
+LEFT JOIN Rating
+    ON Rating.for_chore_log_chore_id = ChoreLog.chore_id
+    AND Rating.week = ChoreLog.week
+WHERE ChoreLog.week >= ?1
+    AND ChoreLog.week <= ?2
+GROUP BY ChoreLog.chore_id, ChoreLog.week
+ORDER BY Chore.id, ChoreLog.week;
+"#,
+        )
+        .bind(start_week.db_week())
+        .bind(end_week.db_week())
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        let chore_logs = sql_rows
+            .into_iter()
+            .map(|r| -> Result<(String, i64, String, Option<f64>)> {
+                Ok((r.try_get(0)?, r.try_get(1)?, r.try_get(2)?, r.try_get(3)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for tenant in tenants.iter_mut() {
+            let mut ratings = vec![];
+            for (chore, _, worker, rating) in &chore_logs {
+                if *worker == tenant.tenant {
+                    *tenant.done.entry(chore.clone()).or_default() += 1;
+                    ratings.extend(rating);
+                }
+            }
+            tenant.rating = mean(&ratings);
+        }
+
+        let sql_rows = sqlx::query(
+            r#"
+SELECT Tenant.name, COUNT(*)
+FROM Unwilling
+JOIN Tenant ON Tenant.id = Unwilling.tenant_id
+WHERE Unwilling.week >= ?1
+    AND Unwilling.week <= ?2
+GROUP BY Tenant.id;
+"#,
+        )
+        .bind(start_week.db_week())
+        .bind(end_week.db_week())
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        for r in sql_rows {
+            let name: String = r.try_get(0)?;
+            if let Some(tenant) = tenants.iter_mut().find(|t| t.tenant == name) {
+                tenant.unwilling_weeks = r.try_get(1)?;
+            }
+        }
+
+        let sql_rows = sqlx::query(
+            r#"
+SELECT DISTINCT Tenant.name, ExemptionReason.reason
+FROM TenantExemption
+JOIN Tenant ON Tenant.id = TenantExemption.tenant_id
+JOIN ExemptionReason ON ExemptionReason.id = TenantExemption.exemption_reason_id
+WHERE TenantExemption.start_week <= ?2
+    AND (TenantExemption.end_week IS NULL OR TenantExemption.end_week > ?1)
+ORDER BY ExemptionReason.reason;
+"#,
+        )
+        .bind(start_week.db_week())
+        .bind(end_week.db_week())
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        for r in sql_rows {
+            let name: String = r.try_get(0)?;
+            if let Some(tenant) = tenants.iter_mut().find(|t| t.tenant == name) {
+                tenant.exemptions.push(r.try_get(1)?);
+            }
+        }
+
+        // the chores that are active or have been done in the weeks
+        let sql_rows = sqlx::query(
+            r#"
+SELECT Chore.name
+FROM Chore
+WHERE Chore.active = 1
+    OR EXISTS (
+        SELECT *
+        FROM ChoreLog
+        WHERE ChoreLog.chore_id = Chore.id
+            AND ChoreLog.week >= ?1
+            AND ChoreLog.week <= ?2
+    );
+"#,
+        )
+        .bind(start_week.db_week())
+        .bind(end_week.db_week())
+        .fetch_all(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        // the first week of the second half, the first half is one week longer on odd ranges
+        let middle_week =
+            start_week.db_week() + (end_week.db_week() - start_week.db_week()) / 2 + 1;
+        let mut chores = sql_rows
+            .into_iter()
+            .map(|r| {
+                let chore: String = r.try_get(0)?;
+                let logs = chore_logs.iter().filter(|(c, _, _, _)| *c == chore);
+                let ratings = |in_half: &dyn Fn(i64) -> bool| -> Vec<f64> {
+                    logs.clone()
+                        .filter(|(_, week, _, _)| in_half(*week))
+                        .filter_map(|(_, _, _, rating)| *rating)
+                        .collect()
+                };
+                Ok(ChoreStats {
+                    done: logs.clone().count() as u32,
+                    rating: mean(&ratings(&|_| true)),
+                    first_half_rating: mean(&ratings(&|week| week < middle_week)),
+                    second_half_rating: mean(&ratings(&|week| week >= middle_week)),
+                    chore,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // sort_by is stable, so ties stay sorted by name
+        chores.sort_by(|a, b| a.chore.cmp(&b.chore));
+        match sort {
+            StatsSort::Name => {}
+            StatsSort::Done => {
+                tenants.sort_by(|a, b| cmp_desc(Some(a.total_done()), Some(b.total_done())));
+                chores.sort_by(|a, b| cmp_desc(Some(a.done), Some(b.done)));
+            }
+            StatsSort::Rating => {
+                tenants.sort_by(|a, b| cmp_desc(a.rating, b.rating));
+                chores.sort_by(|a, b| cmp_desc(a.rating, b.rating));
+            }
+            // chores can't be unwilling
+            StatsSort::Unwilling => {
+                tenants.sort_by(|a, b| cmp_desc(Some(a.unwilling_weeks), Some(b.unwilling_weeks)))
+            }
+        }
+
+        Ok(Stats {
+            start_week,
+            end_week,
+            tenants,
+            chores,
+        })
+    }
+
+    /// Print nice statistics of the weeks from start_week to end_week, both included.
+    /// Without end_week everything until last week is included.
+    pub async fn print_stats(
+        &mut self,
+        start_week: Week,
+        end_week: Option<Week>,
+        sort: StatsSort,
+    ) -> Result<ReplyMsg> {
+        #[derive(Tabled)]
+        struct TenantStatsRow {
+            tenant: String,
+            done: u32,
+            chores: String,
+            rating: String,
+            unwilling: u32,
+            exemptions: String,
+        }
+        #[derive(Tabled)]
+        struct ChoreStatsRow {
+            chore: String,
+            done: u32,
+            rating: String,
+            first_half: String,
+            second_half: String,
+            trend: String,
+        }
+        let stats = self.get_stats(start_week, end_week, sort).await?;
+        let tenant_rows = stats.tenants.iter().map(|t| TenantStatsRow {
+            tenant: t.tenant.clone(),
+            done: t.total_done(),
+            chores: t
+                .done
+                .iter()
+                .map(|(chore, count)| format!("{} {}", chore, count))
+                .collect::<Vec<_>>()
+                .join(", "),
+            rating: display_opt_rating(&t.rating),
+            unwilling: t.unwilling_weeks,
+            exemptions: t.exemptions.join(", "),
+        });
+        let chore_rows = stats.chores.iter().map(|c| ChoreStatsRow {
+            chore: c.chore.clone(),
+            done: c.done,
+            rating: display_opt_rating(&c.rating),
+            first_half: display_opt_rating(&c.first_half_rating),
+            second_half: display_opt_rating(&c.second_half_rating),
+            trend: c.trend().map(|t| format!("{:+.2}", t)).unwrap_or_default(),
+        });
+        Ok(ReplyMsg::from_blocks(vec![
+            Block::heading(
+                1,
+                &self.locale.stats_heading(stats.start_week, stats.end_week),
+            ),
+            Block::heading(2, self.locale.heading(Heading::Tenants)),
+            Block::table_with_header(
+                self.locale.table_header(TableHeader::TenantStats),
+                tenant_rows,
+            ),
+            Block::Blank,
+            Block::heading(2, self.locale.heading(Heading::Chores)),
+            Block::table_with_header(
+                self.locale.table_header(TableHeader::ChoreStats),
+                chore_rows,
+            ),
+        ]))
+    }
+}
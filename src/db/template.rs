@@ -0,0 +1,46 @@
+use crate::{
+    db::*,
+    template::{TemplateKind, TemplatePart},
+};
+
+use anyhow::Result;
+
+impl Db {
+    /// Get the customised template of a message.
+    ///
+    /// Return None when the locale's default is used.
+    pub async fn get_custom_template(&mut self, kind: TemplateKind) -> Result<Option<String>> {
+        let row = sqlx::query(
+            r#"
+SELECT MessageTemplate.template
+FROM MessageTemplate
+WHERE MessageTemplate.kind = ?1;
+"#,
+        )
+        .bind(kind.to_string())
+        .fetch_optional(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
+        Ok(match row {
+            Some(row) => Some(row.try_get(0)?),
+            None => None,
+        })
+    }
+
+    /// Get the parsed template of a message, the locale's default when it hasn't been customised.
+    ///
+    /// A customised template that can't be parsed, i.e., because it was edited by hand, is treated
+    /// like a missing one.
+    pub async fn get_template(&mut self, kind: TemplateKind) -> Result<Vec<TemplatePart>> {
+        if let Some(template) = self.get_custom_template(kind).await? {
+            match kind.parse(&template, self.locale) {
+                Ok(parts) => return Ok(parts),
+                Err(e) => eprintln!(
+                    "ignoring the invalid {} template '{}' in the MessageTemplate table: {}",
+                    kind, template, e
+                ),
+            }
+        }
+        kind.parse(self.locale.default_template(kind), self.locale)
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{db::*, template::TemplateKind};
+
+use anyhow::{bail, Result};
+
+impl Db {
+    /// Print all message templates and the placeholders they may use.
+    pub async fn list_templates(&mut self) -> Result<ReplyMsg> {
+        let mut blocks = vec![Block::heading(1, self.locale.heading(Heading::Templates))];
+        for kind in TemplateKind::ALL {
+            let (template, custom) = match self.get_custom_template(kind).await? {
+                Some(template) => (template, true),
+                None => (self.locale.default_template(kind).to_string(), false),
+            };
+            blocks.push(Block::Blank);
+            blocks.push(Block::heading(
+                2,
+                &self.locale.template_heading(kind, custom),
+            ));
+            blocks.push(Block::text(
+                &self.locale.placeholders(
+                    &kind
+                        .placeholders()
+                        .iter()
+                        .map(|p| format!("{{{}}}", p.name()))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+            ));
+            blocks.push(Block::code(&template));
+        }
+        Ok(ReplyMsg::from_blocks(blocks))
+    }
+
+    /// Replace the template of a message.
+    ///
+    /// Fail when the template uses placeholders the message doesn't know.
+    pub async fn set_template(&mut self, kind: TemplateKind, template: &str) -> Result<ReplyMsg> {
+        kind.parse(template, self.locale)?;
+        let affected_rows = sqlx::query(
+            r#"
+REPLACE INTO MessageTemplate VALUES
+    (?1, ?2);
+"#,
+        )
+        .bind(kind.to_string())
+        .bind(template)
+        .execute(&mut self.con)
+        .await?
+        .rows_affected();
+        self.integrity_check().await?;
+        if affected_rows != 1 {
+            bail!(self.locale.affected_rows(affected_rows));
+        }
+        self.list_templates().await
+    }
+
+    /// Go back to the locale's default template of a message.
+    pub async fn reset_template(&mut self, kind: TemplateKind) -> Result<ReplyMsg> {
+        let affected_rows = sqlx::query(
+            r#"
+DELETE FROM MessageTemplate
+WHERE MessageTemplate.kind = ?1;
+"#,
+        )
+        .bind(kind.to_string())
+        .execute(&mut self.con)
+        .await?
+        .rows_affected();
+        self.integrity_check().await?;
+        if affected_rows > 1 {
+            bail!(self.locale.affected_rows(affected_rows));
+        }
+        self.list_templates().await
+    }
+}
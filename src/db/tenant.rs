@@ -1,4 +1,4 @@
-use crate::db::*;
+use crate::db::{settings_commands::StartScorePolicy, *};
 
 use anyhow::Result;
 
@@ -96,10 +96,106 @@ And LivesIn.move_in_week = ?3;
         .await?
         .rows_affected();
         self.integrity_check().await?;
+        // a tenant who never lived here gets a new StartScore when moving in again
+        sqlx::query(
+            r#"
+DELETE
+FROM StartScore
+WHERE StartScore.tenant_id = (SELECT Tenant.id FROM Tenant WHERE Tenant.name = ?1)
+AND NOT EXISTS (SELECT * FROM LivesIn WHERE LivesIn.tenant_id = StartScore.tenant_id);
+"#,
+        )
+        .bind(tenant)
+        .execute(&mut self.con)
+        .await?;
+        self.integrity_check().await?;
         match affected_rows {
             0 => Ok(false),
             1 => Ok(true),
             _ => bail!("undo_move_in adjusted more than one row"),
         }
     }
+
+    /// Store the StartScores of a tenant moving into a room this week according to the start_score
+    /// policy.
+    /// Must be called before the tenant's LivesIn is inserted. Tenants who lived here before keep
+    /// their score, i.e., when moving out for a vacation.
+    /// A score passed on from the room's previous tenant is taken from them, so it isn't counted
+    /// twice when they move back in.
+    pub async fn set_start_score(&mut self, tenant: &str, room: &str) -> Result<()> {
+        let lived_here_before = sqlx::query(
+            r#"
+SELECT *
+FROM LivesIn
+WHERE LivesIn.tenant_id = (SELECT Tenant.id FROM Tenant WHERE Tenant.name = ?1);
+"#,
+        )
+        .bind(tenant)
+        .fetch_optional(&mut self.con)
+        .await?
+        .is_some();
+        self.integrity_check().await?;
+        if lived_here_before {
+            return Ok(());
+        }
+
+        let sql = match self.get_start_score_policy_internal().await {
+            StartScorePolicy::Zero => return Ok(()),
+            // tenants exempt from a chore don't take part in it, so their score doesn't count
+            StartScorePolicy::Mean => {
+                r#"
+INSERT INTO StartScore
+SELECT (SELECT Tenant.id FROM Tenant WHERE Tenant.name = ?1), Chore.id, AVG(TenantScore.score), ?2, NULL
+FROM Chore
+JOIN TenantScore ON TenantScore.chore_id = Chore.id
+JOIN LivesIn
+    ON LivesIn.tenant_id = TenantScore.tenant_id
+    AND LivesIn.move_in_week <= ?2
+    AND (LivesIn.move_out_week IS NULL OR LivesIn.move_out_week > ?2)
+WHERE NOT EXISTS (
+    SELECT *
+    FROM TenantExemption
+    JOIN ChoreExemption ON ChoreExemption.exemption_reason_id = TenantExemption.exemption_reason_id
+        WHERE TenantExemption.tenant_id = TenantScore.tenant_id
+        AND ChoreExemption.chore_id = Chore.id
+        AND TenantExemption.start_week <= ?2
+        AND (TenantExemption.end_week IS NULL OR TenantExemption.end_week > ?2)
+)
+GROUP BY Chore.id;
+"#
+            }
+            StartScorePolicy::Room => {
+                r#"
+INSERT INTO StartScore
+SELECT (SELECT Tenant.id FROM Tenant WHERE Tenant.name = ?1), TenantScore.chore_id, TenantScore.score, ?2,
+    TenantScore.tenant_id
+FROM TenantScore
+WHERE TenantScore.tenant_id = (
+    SELECT LivesIn.tenant_id
+    FROM LivesIn
+        WHERE LivesIn.room_name = ?3
+        AND LivesIn.move_out_week <= ?2
+        -- tenants who moved into another room keep their score to themselves
+        AND NOT EXISTS (
+            SELECT *
+            FROM LivesIn CurrentLivesIn
+                WHERE CurrentLivesIn.tenant_id = LivesIn.tenant_id
+                AND CurrentLivesIn.move_in_week <= ?2
+                AND (CurrentLivesIn.move_out_week IS NULL OR CurrentLivesIn.move_out_week > ?2)
+        )
+    ORDER BY LivesIn.move_out_week DESC
+    LIMIT 1
+);
+"#
+            }
+        };
+        sqlx::query(sql)
+            .bind(tenant)
+            .bind(self.get_week_internal().await.db_week())
+            .bind(room)
+            .execute(&mut self.con)
+            .await?;
+        self.integrity_check().await?;
+        Ok(())
+    }
 }
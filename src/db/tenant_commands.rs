@@ -1,25 +1,28 @@
 use crate::db::*;
 
 use anyhow::{bail, Result};
+use serde::Serialize;
 use sqlx::Row;
-use tabled::{
-    settings::{object::Segment, Alignment, Settings},
-    Table, Tabled,
-};
+use tabled::Tabled;
+
+/// a room and the tenant currently living in it
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Room {
+    pub room: String,
+    pub tenant: Option<String>,
+    pub chat_tag: Option<String>,
+    /// the tenant's score
+    pub score: Option<f32>,
+    /// the average rating of the tenant's chores
+    pub rating: Option<f32>,
+}
 
 impl Db {
-    /// Print a nice list of all rooms and their tenants.
-    pub async fn list_tenants(&mut self) -> Result<ReplyMsg> {
-        #[derive(Tabled)]
-        struct RoomRow {
-            room: String,
-            tenant: String,
-            #[tabled(rename = "score\neval")]
-            score_rating: String,
-        }
+    /// Get all rooms and their current tenants.
+    pub async fn get_rooms(&mut self) -> Result<Vec<Room>> {
         let sql_rows = sqlx::query(
             r#"
-SELECT Room.name, CONCAT(Tenant.name, CHAR(10), Tenant.chat_tag), TenantScoreSum.score, TenantRatingAvg.avg
+SELECT Room.name, Tenant.name, Tenant.chat_tag, TenantScoreSum.score, TenantRatingAvg.avg
 FROM Room
 -- Does a tenant live here currently?
 LEFT JOIN LivesIn
@@ -50,30 +53,47 @@ ORDER BY Room.name ASC;
         .fetch_all(&mut self.con)
         .await?;
         self.integrity_check().await?;
-        let rows = sql_rows
+        sql_rows
             .into_iter()
             .map(|r| {
-                Ok(RoomRow {
+                Ok(Room {
                     room: r.try_get(0)?,
-                    tenant: r.try_get::<Option<String>, usize>(1)?.unwrap_or_default(),
-                    score_rating: format!(
-                        "{}\n{}",
-                        r.try_get::<Option<f32>, usize>(2)?
-                            .map_or("".to_string(), |v| format!("{:.2}", v)),
-                        r.try_get::<Option<f32>, usize>(3)?
-                            .map_or("".to_string(), |v| format!("{:.2}", v)),
-                    ),
+                    tenant: r.try_get(1)?,
+                    chat_tag: r.try_get(2)?,
+                    score: r.try_get(3)?,
+                    rating: r.try_get(4)?,
                 })
             })
-            .collect::<Result<Vec<_>>>()?;
+            .collect()
+    }
 
-        Ok(ReplyMsg::from_mono(&format!(
-            "# Tenants\n\n{}",
-            Table::new(rows).modify(
-                Segment::all(),
-                Settings::new(Alignment::center(), Alignment::center())
-            )
-        )))
+    /// Print a nice list of all rooms and their tenants.
+    pub async fn list_tenants(&mut self) -> Result<ReplyMsg> {
+        #[derive(Tabled)]
+        struct RoomRow {
+            room: String,
+            tenant: String,
+            score_rating: String,
+        }
+        let rows = self.get_rooms().await?.into_iter().map(|r| RoomRow {
+            room: r.room,
+            tenant: format!(
+                "{}\n{}",
+                r.tenant.unwrap_or_default(),
+                r.chat_tag.unwrap_or_default()
+            ),
+            score_rating: format!(
+                "{}\n{}",
+                r.score.map_or("".to_string(), |v| format!("{:.2}", v)),
+                r.rating.map_or("".to_string(), |v| format!("{:.2}", v)),
+            ),
+        });
+
+        Ok(ReplyMsg::from_blocks(vec![
+            Block::heading(1, self.locale.heading(Heading::Tenants)),
+            Block::Blank,
+            Block::table_with_header(self.locale.table_header(TableHeader::Tenants), rows),
+        ]))
     }
 
     /// Move someone into a room starting this week.
@@ -104,7 +124,7 @@ SET chat_tag = ?1
                 .rows_affected();
                 self.integrity_check().await?;
                 if affected_rows != 1 {
-                    bail!("affected {} rows", affected_rows);
+                    bail!(self.locale.affected_rows(affected_rows));
                 }
             }
         } else {
@@ -122,23 +142,22 @@ INSERT INTO Tenant VALUES
             .rows_affected();
             self.integrity_check().await?;
             if affected_rows != 1 {
-                bail!("affected {} rows", affected_rows);
+                bail!(self.locale.affected_rows(affected_rows));
             }
         }
         // assert the room is free
         if let Some(cur_tenant) = self.get_rooms_tenant(room).await? {
-            return Ok(ReplyMsg::from_mono(&format!(
-                "{} is living in room {}",
-                cur_tenant, room
-            )));
+            return Ok(ReplyMsg::from_text(
+                &self.locale.room_occupied(&cur_tenant, room),
+            ));
         }
         // assert the tenant isn't living anywhere else
         if let Some(cur_room) = self.get_tenants_room(&tenant).await? {
-            return Ok(ReplyMsg::from_mono(&format!(
-                "the tenant {} is currenlty living in {}, move them out of there first",
-                tenant, cur_room
-            )));
+            return Ok(ReplyMsg::from_text(
+                &self.locale.tenant_living_elsewhere(&tenant, &cur_room),
+            ));
         }
+        self.set_start_score(&tenant, room).await?;
         sqlx::query(
             r#"
 INSERT INTO LivesIn VALUES
@@ -168,7 +187,7 @@ INSERT INTO LivesIn VALUES
         let tenant = Self::capitalize_tenant_name(tenant);
         let room = match self.get_tenants_room(&tenant).await? {
             Some(r) => r,
-            None => bail!("the tenant {} isn't living anywhere", tenant),
+            None => bail!(self.locale.tenant_not_living_anywhere(&tenant)),
         };
         if !self.undo_move_in(&tenant, &room).await? {
             let affected_rows = sqlx::query(
@@ -187,7 +206,7 @@ SET move_out_week = ?1
             .rows_affected();
             self.integrity_check().await?;
             if affected_rows != 1 {
-                bail!("affected {} rows", affected_rows);
+                bail!(self.locale.affected_rows(affected_rows));
             }
         }
         Ok(self.list_tenants().await? + self.update_plan(fmt_replan_cmd).await?)
@@ -208,7 +227,7 @@ INSERT INTO Room VALUES
         .rows_affected();
         self.integrity_check().await?;
         if affected_rows != 1 {
-            bail!("affected {} rows", affected_rows);
+            bail!(self.locale.affected_rows(affected_rows));
         }
         self.list_tenants().await
     }
@@ -0,0 +1,74 @@
+use crate::{
+    bot::{BotUpdate, MessagableBot, PollableBot},
+    command::{handle_next_msg, weekly_action},
+    db::Db,
+    http::{handle_api_call, ApiCall},
+};
+
+/// a flat with its own chat and database
+///
+/// Each household's chat has the same index as the household.
+pub struct Household {
+    pub name: String,
+    pub db: Db,
+}
+
+/// Handle an update from the bot in the household it belongs to.
+pub async fn handle_update<B: MessagableBot + PollableBot>(
+    households: &mut [Household],
+    bot: &mut B,
+    update: BotUpdate,
+) {
+    // The update came from the selected chat.
+    let db = &mut households[bot.selected_chat()].db;
+    match update {
+        BotUpdate::Msg(msg) => handle_next_msg(db, bot, &msg).await,
+        BotUpdate::WeeklyAction => weekly_action(db, bot).await,
+        BotUpdate::PollAnswer {
+            vote_key,
+            voter_tags,
+            option,
+        } => {
+            // Votes don't say which chat they come from, only the household with the poll
+            // stores it.
+            for household in households.iter_mut() {
+                if let Err(e) = household
+                    .db
+                    .store_poll_vote(&vote_key, &voter_tags, option)
+                    .await
+                {
+                    eprintln!(
+                        "Error storing vote in household {}: {:?}",
+                        household.name, e
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Answer an ApiCall with the database and in the chat of its household.
+pub async fn handle_household_api_call<B: MessagableBot + PollableBot>(
+    households: &mut [Household],
+    bot: &mut B,
+    call: ApiCall,
+) {
+    let selected_chat = bot.selected_chat();
+    bot.select_chat(call.household);
+    handle_api_call(&mut households[call.household].db, bot, call).await;
+    bot.select_chat(selected_chat);
+}
+
+/// Perform the weekly action for each household in its own chat.
+pub async fn weekly_action_for_all<B: MessagableBot + PollableBot>(
+    households: &mut [Household],
+    bot: &mut B,
+) {
+    let selected_chat = bot.selected_chat();
+    for (chat, household) in households.iter_mut().enumerate() {
+        println!("performing the weekly action for {}", household.name);
+        bot.select_chat(chat);
+        weekly_action(&mut household.db, bot).await;
+    }
+    bot.select_chat(selected_chat);
+}
@@ -0,0 +1,425 @@
+pub mod calendar;
+pub mod dashboard;
+
+use crate::{
+    bot::{MessagableBot, PollableBot},
+    command::{fmt_replan_cmd, run_command_for_bot},
+    db::Db,
+    http::{calendar::render_calendar, dashboard::render_dashboard},
+    week::Week,
+};
+
+use anyhow::{Context, Result};
+use axum::{
+    extract::{Path, Query, State},
+    http::{
+        header::{AUTHORIZATION, CONTENT_TYPE},
+        HeaderMap, StatusCode,
+    },
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use tokio::sync::{mpsc, oneshot};
+
+/// what the HTTP server wants from the database
+#[derive(Debug)]
+pub enum ApiRequest {
+    Plan,
+    Tenants,
+    Exemptions,
+    Report {
+        week: Week,
+    },
+    /// the rendered HTML dashboard
+    Dashboard {
+        history_weeks: u32,
+    },
+    /// the iCalendar feed of all ChoreLogs or only those of one tenant
+    Calendar {
+        tenant: Option<String>,
+    },
+    /// a chat command without the bot's name
+    Command(String),
+}
+
+/// a request from the HTTP server and the channel the answer should be sent over
+///
+/// The database can't be shared with the HTTP server, so requests are sent to the main loop.
+pub struct ApiCall {
+    /// the index of the household the request is for
+    pub household: usize,
+    pub request: ApiRequest,
+    pub reply: oneshot::Sender<Result<Value>>,
+}
+
+#[derive(Clone)]
+struct ApiState {
+    sender: mpsc::Sender<ApiCall>,
+    /// the bearer token needed for running commands
+    ///
+    /// Commands are disabled when this is None.
+    token: Option<String>,
+    /// the names of all households in order
+    households: Vec<String>,
+}
+
+/// Serve the HTTP API on address until the main loop stops receiving ApiCalls.
+///
+/// With more than one household, requests need to choose one with the household query parameter.
+pub async fn serve(
+    address: SocketAddr,
+    token: Option<String>,
+    households: Vec<String>,
+    sender: mpsc::Sender<ApiCall>,
+) -> Result<()> {
+    let app = Router::new()
+        .route("/", get(get_dashboard))
+        .route("/calendar.ics", get(get_calendar))
+        .route("/calendar/{file}", get(get_tenant_calendar))
+        .route("/api/plan", get(get_plan))
+        .route("/api/tenants", get(get_tenants))
+        .route("/api/exemptions", get(get_exemptions))
+        .route("/api/report", get(get_report))
+        .route("/api/command", post(post_command))
+        .with_state(ApiState {
+            sender,
+            token,
+            households,
+        });
+    let listener = tokio::net::TcpListener::bind(address)
+        .await
+        .with_context(|| format!("failed to bind the HTTP server to {}", address))?;
+    println!("serving HTTP API on {}", address);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+/// Wait for the next ApiCall or forever when there is no HTTP server.
+pub async fn next_api_call(receiver: &mut Option<mpsc::Receiver<ApiCall>>) -> Option<ApiCall> {
+    match receiver {
+        Some(receiver) => receiver.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Answer an ApiCall with data from the database.
+pub async fn handle_api_call<B: MessagableBot + PollableBot>(
+    db: &mut Db,
+    bot: &mut B,
+    call: ApiCall,
+) {
+    let response = match call.request {
+        ApiRequest::Plan => db.get_plan(None).await.map(|v| json!(v)),
+        ApiRequest::Tenants => db.get_rooms().await.map(|v| json!(v)),
+        ApiRequest::Exemptions => db.get_exemptions().await.map(|v| json!(v)),
+        ApiRequest::Report { week } => db.get_report(week).await.map(|v| json!(v)),
+        ApiRequest::Dashboard { history_weeks } => {
+            render_dashboard(db, history_weeks).await.map(Value::String)
+        }
+        ApiRequest::Calendar { tenant } => {
+            render_calendar(db, tenant.as_deref(), Utc::now(), fmt_replan_cmd(bot))
+                .await
+                .map(Value::String)
+        }
+        ApiRequest::Command(command) => match run_command_for_bot(db, bot, &command).await {
+            Ok(reply) => {
+                // Tagged tenants have to be told in the chat, e.g. after a replan.
+                if !reply.tags.is_empty() {
+                    bot.send_msg(Ok(reply.clone())).await;
+                }
+                // Include the monospace rendering for clients that don't want to render blocks.
+                Ok(json!({
+                    "mono_msg": reply.mono_msg(),
+                    "blocks": reply.blocks,
+                    "tags": reply.tags,
+                    "actions": reply.actions,
+                    "attachments": reply.attachments,
+                }))
+            }
+            Err(e) => Err(e),
+        },
+    };
+    if call.reply.send(response).is_err() {
+        eprintln!("the HTTP client is gone, dropping the response");
+    }
+}
+
+#[derive(Deserialize)]
+struct HouseholdQuery {
+    /// the name of the household, only needed when there are multiple
+    household: Option<String>,
+}
+
+/// Find the index of the household a request is for.
+///
+/// Errors are returned with the status the response should have.
+fn find_household(
+    state: &ApiState,
+    query: &HouseholdQuery,
+) -> std::result::Result<usize, (StatusCode, String)> {
+    match &query.household {
+        Some(household) => state
+            .households
+            .iter()
+            .position(|h| h == household)
+            .ok_or_else(|| {
+                (
+                    StatusCode::NOT_FOUND,
+                    format!("the household '{}' doesn't exist", household),
+                )
+            }),
+        None if state.households.len() == 1 => Ok(0),
+        None => Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "choose a household with ?household=, one of: {}",
+                state.households.join(", ")
+            ),
+        )),
+    }
+}
+
+/// Send a request to the main loop and wait for the answer.
+///
+/// Errors are returned as response with error_status.
+async fn call(
+    state: &ApiState,
+    household: &HouseholdQuery,
+    request: ApiRequest,
+    error_status: StatusCode,
+) -> std::result::Result<Value, Response> {
+    let household = find_household(state, household)
+        .map_err(|(status, error)| error_response(status, &error))?;
+    let (reply, receiver) = oneshot::channel();
+    let api_call = ApiCall {
+        household,
+        request,
+        reply,
+    };
+    if state.sender.send(api_call).await.is_err() {
+        return Err(error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "the chore_planner is stopping",
+        ));
+    }
+    match receiver.await {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(e)) => Err(error_response(error_status, &e.to_string())),
+        Err(_) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "the chore_planner didn't answer",
+        )),
+    }
+}
+
+/// Send a request to the main loop and turn the answer into a JSON response.
+async fn call_json(
+    state: &ApiState,
+    household: &HouseholdQuery,
+    request: ApiRequest,
+    error_status: StatusCode,
+) -> Response {
+    match call(state, household, request, error_status).await {
+        Ok(value) => Json(value).into_response(),
+        Err(response) => response,
+    }
+}
+
+/// Send a request to the main loop that is answered with a string, like a rendered page.
+async fn call_string(
+    state: &ApiState,
+    household: &HouseholdQuery,
+    request: ApiRequest,
+    error_status: StatusCode,
+) -> std::result::Result<String, Response> {
+    match call(state, household, request, error_status).await? {
+        Value::String(string) => Ok(string),
+        _ => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "the chore_planner didn't answer with a string",
+        )),
+    }
+}
+
+fn error_response(status: StatusCode, error: &str) -> Response {
+    (status, Json(json!({ "error": error }))).into_response()
+}
+
+#[derive(Deserialize)]
+struct DashboardQuery {
+    /// how many weeks of history to show
+    #[serde(default = "default_history_weeks")]
+    history_weeks: u32,
+}
+
+fn default_history_weeks() -> u32 {
+    8
+}
+
+async fn get_dashboard(
+    State(state): State<ApiState>,
+    Query(household): Query<HouseholdQuery>,
+    Query(query): Query<DashboardQuery>,
+) -> Response {
+    match call_string(
+        &state,
+        &household,
+        ApiRequest::Dashboard {
+            history_weeks: query.history_weeks,
+        },
+        StatusCode::INTERNAL_SERVER_ERROR,
+    )
+    .await
+    {
+        Ok(html) => Html(html).into_response(),
+        Err(response) => response,
+    }
+}
+
+async fn get_calendar(
+    State(state): State<ApiState>,
+    Query(household): Query<HouseholdQuery>,
+) -> Response {
+    calendar_response(&state, &household, None).await
+}
+
+/// file is the tenant's name followed by .ics
+async fn get_tenant_calendar(
+    State(state): State<ApiState>,
+    Query(household): Query<HouseholdQuery>,
+    Path(file): Path<String>,
+) -> Response {
+    match file.strip_suffix(".ics") {
+        Some(tenant) => calendar_response(&state, &household, Some(tenant.to_string())).await,
+        None => error_response(StatusCode::NOT_FOUND, "calendars end with .ics"),
+    }
+}
+
+async fn calendar_response(
+    state: &ApiState,
+    household: &HouseholdQuery,
+    tenant: Option<String>,
+) -> Response {
+    match call_string(
+        state,
+        household,
+        ApiRequest::Calendar { tenant },
+        StatusCode::NOT_FOUND,
+    )
+    .await
+    {
+        Ok(ics) => ([(CONTENT_TYPE, "text/calendar; charset=utf-8")], ics).into_response(),
+        Err(response) => response,
+    }
+}
+
+async fn get_plan(
+    State(state): State<ApiState>,
+    Query(household): Query<HouseholdQuery>,
+) -> Response {
+    call_json(
+        &state,
+        &household,
+        ApiRequest::Plan,
+        StatusCode::INTERNAL_SERVER_ERROR,
+    )
+    .await
+}
+
+async fn get_tenants(
+    State(state): State<ApiState>,
+    Query(household): Query<HouseholdQuery>,
+) -> Response {
+    call_json(
+        &state,
+        &household,
+        ApiRequest::Tenants,
+        StatusCode::INTERNAL_SERVER_ERROR,
+    )
+    .await
+}
+
+async fn get_exemptions(
+    State(state): State<ApiState>,
+    Query(household): Query<HouseholdQuery>,
+) -> Response {
+    call_json(
+        &state,
+        &household,
+        ApiRequest::Exemptions,
+        StatusCode::INTERNAL_SERVER_ERROR,
+    )
+    .await
+}
+
+#[derive(Deserialize)]
+struct ReportQuery {
+    week: u32,
+    year: i32,
+}
+
+async fn get_report(
+    State(state): State<ApiState>,
+    Query(household): Query<HouseholdQuery>,
+    Query(query): Query<ReportQuery>,
+) -> Response {
+    match Week::new(query.week, query.year) {
+        Ok(week) => {
+            call_json(
+                &state,
+                &household,
+                ApiRequest::Report { week },
+                StatusCode::INTERNAL_SERVER_ERROR,
+            )
+            .await
+        }
+        Err(e) => error_response(StatusCode::BAD_REQUEST, &e.to_string()),
+    }
+}
+
+#[derive(Deserialize)]
+struct CommandBody {
+    /// the chat command without the bot's name, e.g. "tenant list"
+    command: String,
+}
+
+/// Compare two byte strings in a time that only depends on their lengths.
+///
+/// This keeps the bearer token from being guessed byte by byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+async fn post_command(
+    State(state): State<ApiState>,
+    Query(household): Query<HouseholdQuery>,
+    headers: HeaderMap,
+    Json(body): Json<CommandBody>,
+) -> Response {
+    let Some(token) = &state.token else {
+        return error_response(
+            StatusCode::FORBIDDEN,
+            "commands are disabled as CHORE_PLANNER_HTTP_TOKEN isn't set",
+        );
+    };
+    let authorized = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|value| constant_time_eq(value.as_bytes(), token.as_bytes()));
+    if !authorized {
+        return error_response(StatusCode::UNAUTHORIZED, "invalid bearer token");
+    }
+    call_json(
+        &state,
+        &household,
+        ApiRequest::Command(body.command),
+        StatusCode::BAD_REQUEST,
+    )
+    .await
+}
@@ -0,0 +1,98 @@
+use crate::{db::Db, week::Week};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::fmt::Write;
+
+/// the maximum line length in octets before a line needs to be folded
+const MAX_LINE_LEN: usize = 75;
+
+/// Render an iCalendar feed with an all-day event spanning the whole week for each ChoreLog.
+///
+/// Only include the ChoreLogs of one tenant when tenant is Some.
+pub async fn render_calendar<F>(
+    db: &mut Db,
+    tenant: Option<&str>,
+    now: DateTime<Utc>,
+    fmt_replan_cmd: F,
+) -> Result<String>
+where
+    F: Fn(&str, Week) -> String,
+{
+    let entries = db.get_calendar_entries(tenant).await?;
+    let dtstamp = now.format("%Y%m%dT%H%M%SZ");
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//chore_planner//chore_planner//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!(
+            "X-WR-CALNAME:{}",
+            escape_text(&match tenant {
+                Some(_) if !entries.is_empty() => format!("Chores of {}", entries[0].tenant),
+                _ => "Chores".to_string(),
+            })
+        ),
+    ];
+    for entry in entries {
+        let start = entry.week.monday();
+        let end = Week::from_db(entry.week.db_week() + 1).monday();
+        lines.push("BEGIN:VEVENT".to_string());
+        // The uid stays the same when the ChoreLog is replanned, so calendars update the event.
+        lines.push(format!(
+            "UID:{}-{}@chore_planner",
+            entry.week.db_week(),
+            entry.chore_id
+        ));
+        lines.push(format!("DTSTAMP:{}", dtstamp));
+        lines.push(format!("DTSTART;VALUE=DATE:{}", start.format("%Y%m%d")));
+        lines.push(format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")));
+        lines.push(format!(
+            "SUMMARY:{}",
+            escape_text(&format!("{}: {}", entry.chore, entry.tenant))
+        ));
+        lines.push(format!(
+            "DESCRIPTION:{}",
+            escape_text(&format!(
+                "{}\n\nCan't do it? Send:\n{}",
+                entry.description,
+                fmt_replan_cmd(&entry.tenant, entry.week)
+            ))
+        ));
+        lines.push("TRANSP:TRANSPARENT".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut ics = String::new();
+    for line in lines {
+        write!(ics, "{}\r\n", fold_line(&line))?;
+    }
+    Ok(ics)
+}
+
+/// Escape a TEXT value according to RFC 5545.
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Split a line into lines of at most MAX_LINE_LEN octets without splitting characters.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_len = 0;
+    for c in line.chars() {
+        if line_len + c.len_utf8() > MAX_LINE_LEN {
+            folded += "\r\n ";
+            // the leading space counts towards the line length
+            line_len = 1;
+        }
+        folded.push(c);
+        line_len += c.len_utf8();
+    }
+    folded
+}
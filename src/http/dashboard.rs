@@ -0,0 +1,106 @@
+use crate::{
+    db::{plan_commands::ChorePlan, tenant_commands::Room, Db},
+    week::Week,
+};
+
+use anyhow::Result;
+use std::fmt::Write;
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 0 auto; max-width: 60em; padding: 0.5em; }
+table { border-collapse: collapse; margin-bottom: 1em; width: 100%; }
+th, td { border-bottom: 1px solid #ccc; padding: 0.3em; text-align: left; }
+.chores { display: flex; flex-wrap: wrap; gap: 0 1em; }
+.chores > section { flex: 1 1 15em; }
+"#;
+
+/// Render the HTML dashboard with the plan, tenants and the history of the last history_weeks weeks.
+pub async fn render_dashboard(db: &mut Db, history_weeks: u32) -> Result<String> {
+    let week = db.get_week_internal().await;
+    let plan = db.get_plan(None).await?;
+    let rooms = db.get_rooms().await?;
+    let history = db
+        .get_plan(Some(Week::from_db(week.db_week() - history_weeks as i64)))
+        .await?;
+
+    let mut html = String::new();
+    write!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>Chores {week}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>Week {week}</h1>\n"
+    )?;
+    html += "<h2>Plan</h2>\n";
+    html += &render_chores(&plan, false)?;
+    html += "<h2>Tenants</h2>\n";
+    html += &render_rooms(&rooms)?;
+    writeln!(html, "<h2>History of the last {history_weeks} weeks</h2>")?;
+    html += &render_chores(&history, true)?;
+    html += "</body>\n</html>\n";
+    Ok(html)
+}
+
+/// Render a table for each chore, side by side when there is enough space.
+fn render_chores(chores: &[ChorePlan], with_rating: bool) -> Result<String> {
+    let mut html = String::from("<div class=\"chores\">\n");
+    for chore in chores {
+        write!(
+            html,
+            "<section>\n<h3 title=\"{}\">{}</h3>\n<table>\n<tr><th>week</th><th>tenant</th>{}</tr>\n",
+            escape_html(&chore.description),
+            escape_html(&chore.name),
+            if with_rating { "<th>rating</th>" } else { "" }
+        )?;
+        for chore_log in &chore.plan {
+            write!(
+                html,
+                "<tr><td>{}</td><td>{}</td>",
+                chore_log.week,
+                escape_html(&chore_log.tenant)
+            )?;
+            if with_rating {
+                write!(html, "<td>{}</td>", fmt_optional(chore_log.rating))?;
+            }
+            html += "</tr>\n";
+        }
+        html += "</table>\n</section>\n";
+    }
+    html += "</div>\n";
+    Ok(html)
+}
+
+fn render_rooms(rooms: &[Room]) -> Result<String> {
+    let mut html = String::from(
+        "<table>\n<tr><th>room</th><th>tenant</th><th>score</th><th>rating</th></tr>\n",
+    );
+    for room in rooms {
+        writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape_html(&room.room),
+            escape_html(room.tenant.as_deref().unwrap_or_default()),
+            fmt_optional(room.score),
+            fmt_optional(room.rating),
+        )?;
+    }
+    html += "</table>\n";
+    Ok(html)
+}
+
+fn fmt_optional(value: Option<f32>) -> String {
+    value.map_or("".to_string(), |v| format!("{:.2}", v))
+}
+
+/// Escape all characters with a special meaning in HTML.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped += "&amp;",
+            '<' => escaped += "&lt;",
+            '>' => escaped += "&gt;",
+            '"' => escaped += "&quot;",
+            '\'' => escaped += "&#39;",
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
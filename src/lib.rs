@@ -0,0 +1,16 @@
+pub mod bot;
+pub mod command;
+pub mod config;
+pub mod console_bot;
+pub mod csv;
+pub mod db;
+pub mod household;
+pub mod http;
+pub mod locale;
+pub mod paginate;
+pub mod signal_bot;
+pub mod telegram_bot;
+pub mod template;
+#[cfg(test)]
+mod test_bot;
+pub mod week;
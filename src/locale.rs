@@ -0,0 +1,910 @@
+mod help;
+
+use crate::{
+    db::{chore_commands::PoorRatingAction, settings_commands::ScoreDecay},
+    template::TemplateKind,
+    week::Week,
+};
+
+use anyhow::{bail, Result};
+use clap::{Command, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+
+/// the language of the messages the bot sends
+///
+/// The names of commands and their arguments are the same in every locale, only their help is
+/// translated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+}
+
+/// the fixed headings of the lists the bot prints
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Heading {
+    Chores,
+    Constraints,
+    Exemptions,
+    Plan,
+    Preferences,
+    RatingRules,
+    ScoreAdjustments,
+    Settings,
+    Simulation,
+    Templates,
+    Tenants,
+}
+
+/// the tables the bot prints, their headers are translated
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableHeader {
+    ChoreStats,
+    Constraints,
+    Exemptions,
+    Plan,
+    Preferences,
+    RatingRules,
+    ScoreAdjustments,
+    Settings,
+    /// the columns of the simulation besides one per chore, inserted after the first column
+    Simulation,
+    TenantStats,
+    Tenants,
+}
+
+const RATING_OPTIONS_EN: [&str; 5] = [
+    "1  😢  You didn't do anything.",
+    "2  👎  Barely noticeable.",
+    "3  😮  Acceptable.",
+    "4  👍  Well done.",
+    "5  ❤️  Perfect!",
+];
+
+const RATING_OPTIONS_DE: [&str; 5] = [
+    "1  😢  Du hast nichts gemacht.",
+    "2  👎  Kaum bemerkbar.",
+    "3  😮  Akzeptabel.",
+    "4  👍  Gut gemacht.",
+    "5  ❤️  Perfekt!",
+];
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::De];
+
+    /// Get the options of a rating poll, the rating is the index plus one.
+    pub fn rating_options(self) -> [&'static str; 5] {
+        match self {
+            Locale::En => RATING_OPTIONS_EN,
+            Locale::De => RATING_OPTIONS_DE,
+        }
+    }
+
+    /// Translate the help pages of a command and its subcommands.
+    pub fn localize_command(self, command: Command) -> Command {
+        match self {
+            Locale::En => command,
+            Locale::De => help::localize_de(command),
+        }
+    }
+
+    /// Figure out the rating of a poll option regardless of the locale the poll was created in.
+    ///
+    /// Options that aren't known fall back to their leading number.
+    pub fn parse_rating(option: &str) -> Result<u32> {
+        for locale in Locale::ALL {
+            if let Some(i) = locale.rating_options().iter().position(|o| *o == option) {
+                return Ok(i as u32 + 1);
+            }
+        }
+        match option
+            .split_whitespace()
+            .next()
+            .and_then(|n| n.parse::<u32>().ok())
+        {
+            Some(rating) => Ok(rating),
+            None => bail!("failed to parse the rating option '{}'", option),
+        }
+    }
+
+    pub fn rating_question(self, tenant: &str, chore: &str, week: Week) -> String {
+        match self {
+            Locale::En => format!("How well did {} do the {} on {}?", tenant, chore, week),
+            Locale::De => format!("Wie gut hat {} den {} in {} gemacht?", tenant, chore, week),
+        }
+    }
+
+    /// the heading announcing who has been chosen for a ChoreLog
+    pub fn chosen_heading(self, chore: &str, week: Week, week_delta: i64, tenant: &str) -> String {
+        match self {
+            Locale::En => format!(
+                "{} on {} (in {} {}): {}",
+                chore,
+                week,
+                week_delta,
+                if week_delta == 1 { "week" } else { "weeks" },
+                tenant
+            ),
+            Locale::De => format!(
+                "{} in {} (in {} {}): {}",
+                chore,
+                week,
+                week_delta,
+                if week_delta == 1 { "Woche" } else { "Wochen" },
+                tenant
+            ),
+        }
+    }
+
+    /// the label of the button replanning a tenant's ChoreLog
+    pub fn cant_do_action(self, tenant: &str, chore: &str, week: Option<Week>) -> String {
+        match (self, week) {
+            (Locale::En, Some(week)) => format!("{} can't do the {} on {}", tenant, chore, week),
+            (Locale::En, None) => format!("{} can't do the {}", tenant, chore),
+            (Locale::De, Some(week)) => {
+                format!("{} kann den {} in {} nicht machen", tenant, chore, week)
+            }
+            (Locale::De, None) => format!("{} kann den {} nicht machen", tenant, chore),
+        }
+    }
+
+    pub fn banner_heading(self, week: Week) -> String {
+        match self {
+            Locale::En => format!("Week {}", week),
+            Locale::De => format!("Woche {}", week),
+        }
+    }
+
+    /// Get the template used when a household hasn't customised it.
+    pub fn default_template(self, kind: TemplateKind) -> &'static str {
+        match (self, kind) {
+            (Locale::En, TemplateKind::Assignment) => {
+                "{tenant}, you have been chosen for the {chore} on {week}.
+According to your effective score {score} you've had a probability of {probability} to be chosen.
+If you're unhappy about that, type this to schedule someone else:
+    {replan_command}
+Alternatively you can move out and then back in if you're on vacation."
+            }
+            (Locale::De, TemplateKind::Assignment) => {
+                "{tenant}, du wurdest für den {chore} in {week} ausgewählt.
+Mit deinem effektiven Score {score} hattest du eine Wahrscheinlichkeit von {probability}, ausgewählt zu werden.
+Wenn du damit unzufrieden bist, schreib das hier, um jemand anderen einzuteilen:
+    {replan_command}
+Alternativ kannst du aus- und wieder einziehen, wenn du im Urlaub bist."
+            }
+            (Locale::En, TemplateKind::BannerGreeting) => {
+                "Hello smart people!\nWe have another week and new jobs to go with it:"
+            }
+            (Locale::De, TemplateKind::BannerGreeting) => {
+                "Hallo ihr Schlauen!\nEine neue Woche bringt neue Aufgaben mit sich:"
+            }
+            (Locale::En, TemplateKind::BannerFarewell) => "Have a very safe and productive week.",
+            (Locale::De, TemplateKind::BannerFarewell) => "Habt eine sichere und produktive Woche.",
+        }
+    }
+
+    /// the header of the banner's table
+    pub fn banner_header(self) -> [&'static str; 2] {
+        match self {
+            Locale::En => ["job", "worker"],
+            Locale::De => ["Aufgabe", "Zuständig"],
+        }
+    }
+
+    /// the label of the button listing the plan
+    pub fn show_plan_action(self) -> &'static str {
+        match self {
+            Locale::En => "Show plan",
+            Locale::De => "Plan anzeigen",
+        }
+    }
+
+    /// the label of the button opening the rating poll of a chore
+    pub fn done_action(self, tenant: &str, chore: &str) -> String {
+        match self {
+            Locale::En => format!("{} did the {}", tenant, chore),
+            Locale::De => format!("{} hat den {} gemacht", tenant, chore),
+        }
+    }
+
+    /// the label of the button asking whom a tenant can swap their chores with
+    pub fn swap_action(self, tenant: &str) -> String {
+        match self {
+            Locale::En => format!("{} wants to swap", tenant),
+            Locale::De => format!("{} möchte tauschen", tenant),
+        }
+    }
+
+    /// the label of the button swapping the chores with another tenant
+    pub fn swap_with_action(self, other: &str, chores: &str) -> String {
+        match self {
+            Locale::En => format!("Swap with {} ({})", other, chores),
+            Locale::De => format!("Mit {} tauschen ({})", other, chores),
+        }
+    }
+
+    pub fn tenant_doesnt_exist(self, tenant: &str) -> String {
+        match self {
+            Locale::En => format!("the tenant {} doesn't exist", tenant),
+            Locale::De => format!("den Bewohner {} gibt es nicht", tenant),
+        }
+    }
+
+    pub fn chore_doesnt_exist(self, chore: &str) -> String {
+        match self {
+            Locale::En => format!("the chore {} doesn't exist", chore),
+            Locale::De => format!("die Aufgabe {} gibt es nicht", chore),
+        }
+    }
+
+    /// the heading announcing that a worker has to redo a poorly rated chore
+    pub fn redo_heading(self, chore: &str, week: Week, tenant: &str) -> String {
+        match self {
+            Locale::En => format!(
+                "{} on {}: {} has to redo the poorly rated chore",
+                chore, week, tenant
+            ),
+            Locale::De => format!(
+                "{} in {}: {} muss die schlecht bewertete Aufgabe wiederholen",
+                chore, week, tenant
+            ),
+        }
+    }
+
+    /// the heading announcing that a tenant was displaced by a redo
+    pub fn redo_displaced_heading(self, chore: &str, week: Week, tenant: &str) -> String {
+        match self {
+            Locale::En => format!(
+                "{} on {}: {} doesn't have to do the chore anymore",
+                chore, week, tenant
+            ),
+            Locale::De => format!(
+                "{} in {}: {} muss die Aufgabe nicht mehr machen",
+                chore, week, tenant
+            ),
+        }
+    }
+
+    /// the heading announcing that the rating poll of a chore has been opened early
+    pub fn done_heading(self, chore: &str, week: Week, tenant: &str) -> String {
+        match self {
+            Locale::En => format!(
+                "{} on {}: {} is done, please rate the chore",
+                chore, week, tenant
+            ),
+            Locale::De => format!(
+                "{} in {}: {} ist fertig, bitte bewertet die Aufgabe",
+                chore, week, tenant
+            ),
+        }
+    }
+
+    /// the heading asking whom a tenant swaps their chores with
+    pub fn swap_heading(self, tenant: &str, week: Week) -> String {
+        match self {
+            Locale::En => format!("Who swaps chores with {} on {}?", tenant, week),
+            Locale::De => format!("Wer tauscht mit {} die Aufgaben in {}?", tenant, week),
+        }
+    }
+
+    /// the heading announcing that two tenants swapped their chores
+    pub fn swapped_heading(self, tenant: &str, other: &str, week: Week) -> String {
+        match self {
+            Locale::En => format!("{} and {} swapped their chores on {}", tenant, other, week),
+            Locale::De => format!(
+                "{} und {} haben ihre Aufgaben in {} getauscht",
+                tenant, other, week
+            ),
+        }
+    }
+
+    pub fn room_occupied(self, tenant: &str, room: &str) -> String {
+        match self {
+            Locale::En => format!("{} is living in room {}", tenant, room),
+            Locale::De => format!("{} wohnt im Zimmer {}", tenant, room),
+        }
+    }
+
+    pub fn tenant_living_elsewhere(self, tenant: &str, room: &str) -> String {
+        match self {
+            Locale::En => format!(
+                "the tenant {} is currently living in {}, move them out of there first",
+                tenant, room
+            ),
+            Locale::De => format!(
+                "der Bewohner {} wohnt gerade in {}, zieh ihn dort zuerst aus",
+                tenant, room
+            ),
+        }
+    }
+
+    pub fn tenant_not_living_anywhere(self, tenant: &str) -> String {
+        match self {
+            Locale::En => format!("the tenant {} isn't living anywhere", tenant),
+            Locale::De => format!("der Bewohner {} wohnt nirgends", tenant),
+        }
+    }
+
+    pub fn exemption_reason_doesnt_exist(self, reason: &str) -> String {
+        match self {
+            Locale::En => format!("the ExemptionReason {} doesn't exist", reason),
+            Locale::De => format!("den Befreiungsgrund {} gibt es nicht", reason),
+        }
+    }
+
+    pub fn tenant_already_exempt(self) -> &'static str {
+        match self {
+            Locale::En => "tenant is already exempt",
+            Locale::De => "der Bewohner ist bereits befreit",
+        }
+    }
+
+    pub fn tenant_not_exempt(self) -> &'static str {
+        match self {
+            Locale::En => "tenant is not exempt",
+            Locale::De => "der Bewohner ist nicht befreit",
+        }
+    }
+
+    pub fn gamma_out_of_range(self) -> &'static str {
+        match self {
+            Locale::En => "gamma needs to be in [0, 1]",
+            Locale::De => "gamma muss in [0, 1] liegen",
+        }
+    }
+
+    pub fn chore_log_doesnt_exist(self, chore: &str, week: Week) -> String {
+        match self {
+            Locale::En => format!("nobody has been planned for the {} on {}", chore, week),
+            Locale::De => format!("für den {} in {} ist niemand eingeplant", chore, week),
+        }
+    }
+
+    pub fn done_not_this_week(self, week: Week) -> String {
+        match self {
+            Locale::En => format!("only chores of this week {} can be marked as done", week),
+            Locale::De => format!(
+                "nur Aufgaben dieser Woche {} können als erledigt markiert werden",
+                week
+            ),
+        }
+    }
+
+    pub fn chore_log_already_rated(self, chore: &str, week: Week) -> String {
+        match self {
+            Locale::En => format!("the {} on {} is already being rated", chore, week),
+            Locale::De => format!("der {} in {} wird bereits bewertet", chore, week),
+        }
+    }
+
+    pub fn done_only_in_chat(self) -> &'static str {
+        match self {
+            Locale::En => "chores can only be marked as done in the chat",
+            Locale::De => "Aufgaben können nur im Chat als erledigt markiert werden",
+        }
+    }
+
+    pub fn swap_in_past(self, week: Week) -> String {
+        match self {
+            Locale::En => format!("the chores on {} can't be swapped anymore", week),
+            Locale::De => format!(
+                "die Aufgaben in {} können nicht mehr getauscht werden",
+                week
+            ),
+        }
+    }
+
+    pub fn tenant_has_no_chore(self, tenant: &str, week: Week) -> String {
+        match self {
+            Locale::En => format!("{} has no chore on {}", tenant, week),
+            Locale::De => format!("{} hat in {} keine Aufgabe", tenant, week),
+        }
+    }
+
+    pub fn cant_swap(self, tenant: &str, other: &str, week: Week) -> String {
+        match self {
+            Locale::En => format!(
+                "{} and {} can't swap their chores on {}",
+                tenant, other, week
+            ),
+            Locale::De => format!(
+                "{} und {} können ihre Aufgaben in {} nicht tauschen",
+                tenant, other, week
+            ),
+        }
+    }
+
+    pub fn nobody_to_swap_with(self, tenant: &str, week: Week) -> String {
+        match self {
+            Locale::En => format!("nobody can swap chores with {} on {}", tenant, week),
+            Locale::De => format!(
+                "niemand kann in {} mit {} die Aufgaben tauschen",
+                week, tenant
+            ),
+        }
+    }
+
+    pub fn weeks_to_plan_out_of_range(self, max: u32) -> String {
+        match self {
+            Locale::En => format!("weeks_to_plan needs to be in [0, {}]", max),
+            Locale::De => format!("weeks_to_plan muss in [0, {}] liegen", max),
+        }
+    }
+
+    /// the error of a statement that changed an unexpected number of rows
+    pub fn affected_rows(self, affected_rows: u64) -> String {
+        match self {
+            Locale::En => format!("affected {} rows", affected_rows),
+            Locale::De => format!("{} Zeilen wurden verändert", affected_rows),
+        }
+    }
+
+    /// the error of a statement that changed an unexpected number of rows of a table
+    pub fn affected_table_rows(self, affected_rows: u64, table: &str) -> String {
+        match self {
+            Locale::En => format!("affected {} {} rows", affected_rows, table),
+            Locale::De => format!("{} {}-Zeilen wurden verändert", affected_rows, table),
+        }
+    }
+
+    pub fn min_rating_out_of_range(self, max_rating: f64) -> String {
+        match self {
+            Locale::En => format!("the minimum rating needs to be in [1, {}]", max_rating),
+            Locale::De => format!("die minimale Bewertung muss in [1, {}] liegen", max_rating),
+        }
+    }
+
+    pub fn score_factor_out_of_range(self) -> &'static str {
+        match self {
+            Locale::En => "the score factor needs to be in [0, 1)",
+            Locale::De => "der Score-Faktor muss in [0, 1) liegen",
+        }
+    }
+
+    pub fn stats_weeks_reversed(self, start_week: Week, end_week: Week) -> String {
+        match self {
+            Locale::En => format!(
+                "the last week {} is before the first week {}",
+                end_week, start_week
+            ),
+            Locale::De => format!(
+                "die letzte Woche {} liegt vor der ersten Woche {}",
+                end_week, start_week
+            ),
+        }
+    }
+
+    pub fn score_amount_zero(self) -> &'static str {
+        match self {
+            Locale::En => "the amount needs to be a number other than 0",
+            Locale::De => "der Betrag muss eine Zahl ungleich 0 sein",
+        }
+    }
+
+    pub fn score_reason_empty(self) -> &'static str {
+        match self {
+            Locale::En => "the reason may not be empty",
+            Locale::De => "der Grund darf nicht leer sein",
+        }
+    }
+
+    pub fn score_decay_weeks_zero(self) -> &'static str {
+        match self {
+            Locale::En => "the half-life or window needs to be at least 1 week",
+            Locale::De => "die Halbwertszeit oder das Fenster muss mindestens 1 Woche lang sein",
+        }
+    }
+
+    pub fn template_unclosed_brace(self) -> &'static str {
+        match self {
+            Locale::En => "the template has an unclosed '{'",
+            Locale::De => "die Vorlage hat eine nicht geschlossene '{'",
+        }
+    }
+
+    pub fn template_unopened_brace(self) -> &'static str {
+        match self {
+            Locale::En => "the template has an unopened '}'",
+            Locale::De => "die Vorlage hat eine nicht geöffnete '}'",
+        }
+    }
+
+    pub fn template_unknown_placeholder(
+        self,
+        kind: TemplateKind,
+        placeholder: &str,
+        placeholders: &str,
+    ) -> String {
+        match self {
+            Locale::En => format!(
+                "the {} template doesn't know the placeholder {{{}}}, use one of: {}",
+                kind, placeholder, placeholders
+            ),
+            Locale::De => format!(
+                "die Vorlage {} kennt den Platzhalter {{{}}} nicht, nutze einen von: {}",
+                kind, placeholder, placeholders
+            ),
+        }
+    }
+
+    pub fn voter_without_chat_tag(self) -> &'static str {
+        match self {
+            Locale::En => "the voter has no chat tag",
+            Locale::De => "die abstimmende Person hat keinen Chat-Tag",
+        }
+    }
+
+    pub fn rating_option_doesnt_exist(self, option: usize) -> String {
+        match self {
+            Locale::En => format!("the rating option {} doesn't exist", option),
+            Locale::De => format!("die Bewertungsoption {} existiert nicht", option),
+        }
+    }
+
+    pub fn import_column_empty(self, column: &str) -> String {
+        match self {
+            Locale::En => format!("the column {} may not be empty", column),
+            Locale::De => format!("die Spalte {} darf nicht leer sein", column),
+        }
+    }
+
+    pub fn import_column_missing(self, column: &str) -> String {
+        match self {
+            Locale::En => format!("the CSV doesn't have a column {}", column),
+            Locale::De => format!("die CSV hat keine Spalte {}", column),
+        }
+    }
+
+    /// the context of an error while importing the line-th move in
+    pub fn import_lives_in_failed(self, line: usize) -> String {
+        match self {
+            Locale::En => format!("failed to import move in {}", line),
+            Locale::De => format!("Einzug {} konnte nicht importiert werden", line),
+        }
+    }
+
+    /// the context of an error while importing the line-th ChoreLog
+    pub fn import_chore_log_failed(self, line: usize) -> String {
+        match self {
+            Locale::En => format!("failed to import ChoreLog {}", line),
+            Locale::De => format!("Aufgabe {} konnte nicht importiert werden", line),
+        }
+    }
+
+    pub fn import_nothing_imported(self) -> &'static str {
+        match self {
+            Locale::En => "nothing has been imported",
+            Locale::De => "es wurde nichts importiert",
+        }
+    }
+
+    pub fn import_not_in_past(self, current_week: Week, week: Week) -> String {
+        match self {
+            Locale::En => format!(
+                "only weeks before the current week {} can be imported, not {}",
+                current_week, week
+            ),
+            Locale::De => format!(
+                "nur Wochen vor der aktuellen Woche {} können importiert werden, nicht {}",
+                current_week, week
+            ),
+        }
+    }
+
+    pub fn import_chore_doesnt_exist(self, chore: &str) -> String {
+        match self {
+            Locale::En => format!("the chore {} doesn't exist, create it first", chore),
+            Locale::De => format!("die Aufgabe {} gibt es nicht, lege sie zuerst an", chore),
+        }
+    }
+
+    pub fn import_didnt_live_here(self, tenant: &str, week: Week) -> String {
+        match self {
+            Locale::En => format!("{} didn't live in the flat on {}", tenant, week),
+            Locale::De => format!("{} hat in {} nicht in der WG gewohnt", tenant, week),
+        }
+    }
+
+    pub fn import_move_out_not_after_move_in(
+        self,
+        move_in_week: Week,
+        move_out_week: Week,
+    ) -> String {
+        match self {
+            Locale::En => format!(
+                "the move out week {} isn't after the move in week {}",
+                move_out_week, move_in_week
+            ),
+            Locale::De => format!(
+                "die Auszugswoche {} liegt nicht nach der Einzugswoche {}",
+                move_out_week, move_in_week
+            ),
+        }
+    }
+
+    pub fn import_room_taken(self, room: &str, tenant: &str) -> String {
+        match self {
+            Locale::En => format!("{} lived in {} at that time", tenant, room),
+            Locale::De => format!("{} hat zu der Zeit in {} gewohnt", tenant, room),
+        }
+    }
+
+    pub fn import_tenant_lived_elsewhere(self, tenant: &str, room: &str) -> String {
+        match self {
+            Locale::En => format!("{} already lived in {} at that time", tenant, room),
+            Locale::De => format!("{} hat zu der Zeit schon in {} gewohnt", tenant, room),
+        }
+    }
+
+    pub fn heading(self, heading: Heading) -> &'static str {
+        match (self, heading) {
+            (Locale::En, Heading::Chores) => "Chores",
+            (Locale::De, Heading::Chores) => "Aufgaben",
+            (Locale::En, Heading::Constraints) => "Constraints",
+            (Locale::De, Heading::Constraints) => "Einschränkungen",
+            (Locale::En, Heading::Exemptions) => "Exemptions",
+            (Locale::De, Heading::Exemptions) => "Befreiungen",
+            (Locale::En, Heading::Plan) => "Plan",
+            (Locale::De, Heading::Plan) => "Plan",
+            (Locale::En, Heading::Preferences) => "Preferences",
+            (Locale::De, Heading::Preferences) => "Vorlieben",
+            (Locale::En, Heading::RatingRules) => "Rating Rules",
+            (Locale::De, Heading::RatingRules) => "Bewertungsregeln",
+            (Locale::En, Heading::ScoreAdjustments) => "Score Adjustments",
+            (Locale::De, Heading::ScoreAdjustments) => "Score-Anpassungen",
+            (Locale::En, Heading::Settings) => "Settings",
+            (Locale::De, Heading::Settings) => "Einstellungen",
+            (Locale::En, Heading::Simulation) => "Simulation",
+            (Locale::De, Heading::Simulation) => "Simulation",
+            (Locale::En, Heading::Templates) => "Templates",
+            (Locale::De, Heading::Templates) => "Vorlagen",
+            (Locale::En, Heading::Tenants) => "Tenants",
+            (Locale::De, Heading::Tenants) => "Bewohner",
+        }
+    }
+
+    /// the header of a table, one entry per column
+    pub fn table_header(self, table: TableHeader) -> &'static [&'static str] {
+        match (self, table) {
+            (Locale::En, TableHeader::ChoreStats) => &[
+                "chore",
+                "done",
+                "rating",
+                "first_half",
+                "second_half",
+                "trend",
+            ],
+            (Locale::De, TableHeader::ChoreStats) => &[
+                "Aufgabe",
+                "erledigt",
+                "Bewertung",
+                "erste Hälfte",
+                "zweite Hälfte",
+                "Trend",
+            ],
+            (Locale::En, TableHeader::Constraints) => &["tenant", "chore", "constraint"],
+            (Locale::De, TableHeader::Constraints) => &["Bewohner", "Aufgabe", "Einschränkung"],
+            (Locale::En, TableHeader::Exemptions) => &["reason", "chores", "tenants"],
+            (Locale::De, TableHeader::Exemptions) => &["Grund", "Aufgaben", "Bewohner"],
+            (Locale::En, TableHeader::Plan) => &["week", "tenant", "rating"],
+            (Locale::De, TableHeader::Plan) => &["Woche", "Bewohner", "Bewertung"],
+            (Locale::En, TableHeader::Preferences) => &["tenant", "likes", "dislikes"],
+            (Locale::De, TableHeader::Preferences) => &["Bewohner", "mag", "mag nicht"],
+            (Locale::En, TableHeader::RatingRules) => &["chore", "min_rating", "action", "since"],
+            (Locale::De, TableHeader::RatingRules) => {
+                &["Aufgabe", "Mindestbewertung", "Aktion", "seit"]
+            }
+            (Locale::En, TableHeader::ScoreAdjustments) => {
+                &["week", "tenant", "chore", "amount", "reason"]
+            }
+            (Locale::De, TableHeader::ScoreAdjustments) => {
+                &["Woche", "Bewohner", "Aufgabe", "Betrag", "Grund"]
+            }
+            (Locale::En, TableHeader::Settings) => &["setting", "value"],
+            (Locale::De, TableHeader::Settings) => &["Einstellung", "Wert"],
+            (Locale::En, TableHeader::Simulation) => &["tenant", "total", "longest\nstreak"],
+            (Locale::De, TableHeader::Simulation) => &["Bewohner", "gesamt", "längste\nSerie"],
+            (Locale::En, TableHeader::TenantStats) => &[
+                "tenant",
+                "done",
+                "chores",
+                "rating",
+                "unwilling",
+                "exemptions",
+            ],
+            (Locale::De, TableHeader::TenantStats) => &[
+                "Bewohner",
+                "erledigt",
+                "Aufgaben",
+                "Bewertung",
+                "unwillig",
+                "Befreiungen",
+            ],
+            (Locale::En, TableHeader::Tenants) => &["room", "tenant", "score\neval"],
+            (Locale::De, TableHeader::Tenants) => &["Zimmer", "Bewohner", "Score\nBewertung"],
+        }
+    }
+
+    pub fn stats_heading(self, start_week: Week, end_week: Week) -> String {
+        match self {
+            Locale::En => format!("Statistics {} to {}", start_week, end_week),
+            Locale::De => format!("Statistik {} bis {}", start_week, end_week),
+        }
+    }
+
+    /// the heading of a template, custom is false when the household hasn't customised it
+    pub fn template_heading(self, kind: TemplateKind, custom: bool) -> String {
+        match (self, custom) {
+            (Locale::En, true) => format!("{} (custom)", kind),
+            (Locale::En, false) => format!("{} (default)", kind),
+            (Locale::De, true) => format!("{} (angepasst)", kind),
+            (Locale::De, false) => format!("{} (Standard)", kind),
+        }
+    }
+
+    pub fn placeholders(self, placeholders: &str) -> String {
+        match self {
+            Locale::En => format!("Placeholders: {}", placeholders),
+            Locale::De => format!("Platzhalter: {}", placeholders),
+        }
+    }
+
+    pub fn times_performed(self, times_performed: i32) -> String {
+        match self {
+            Locale::En => format!("Times performed: {}", times_performed),
+            Locale::De => format!("Erledigt: {}-mal", times_performed),
+        }
+    }
+
+    pub fn max_chores_constraint(self, max: u32) -> String {
+        match self {
+            Locale::En => format!("max {} per week", max),
+            Locale::De => format!("max. {} pro Woche", max),
+        }
+    }
+
+    pub fn cool_down_constraint(self, weeks: u32) -> String {
+        match self {
+            Locale::En => format!("cool-down {} weeks", weeks),
+            Locale::De => format!("Pause {} Wochen", weeks),
+        }
+    }
+
+    /// the chore of a ScoreAdjustment for all chores
+    pub fn all_chores(self) -> &'static str {
+        match self {
+            Locale::En => "all",
+            Locale::De => "alle",
+        }
+    }
+
+    pub fn score_decay(self, score_decay: ScoreDecay) -> String {
+        match (self, score_decay) {
+            (Locale::En, ScoreDecay::None) => "none".to_string(),
+            (Locale::De, ScoreDecay::None) => "keine".to_string(),
+            (Locale::En, ScoreDecay::HalfLife { weeks }) => format!("half-life {} weeks", weeks),
+            (Locale::De, ScoreDecay::HalfLife { weeks }) => {
+                format!("Halbwertszeit {} Wochen", weeks)
+            }
+            (Locale::En, ScoreDecay::Window { weeks }) => format!("window {} weeks", weeks),
+            (Locale::De, ScoreDecay::Window { weeks }) => format!("Fenster {} Wochen", weeks),
+        }
+    }
+
+    /// the action of a ChoreRatingRule, score_factor is only used for partial
+    pub fn poor_rating_action(self, action: PoorRatingAction) -> String {
+        match (self, action) {
+            (Locale::En, PoorRatingAction::Redo) => "redo".to_string(),
+            (Locale::De, PoorRatingAction::Redo) => "wiederholen".to_string(),
+            (Locale::En, PoorRatingAction::Partial { score_factor }) => {
+                format!("partial {:.0}%", score_factor * 100.0)
+            }
+            (Locale::De, PoorRatingAction::Partial { score_factor }) => {
+                format!("teilweise {:.0}%", score_factor * 100.0)
+            }
+        }
+    }
+
+    pub fn busy_window(self, weeks: u32) -> String {
+        match self {
+            Locale::En => format!("Busy window: {} weeks", weeks),
+            Locale::De => format!("Beschäftigt-Zeitraum: {} Wochen", weeks),
+        }
+    }
+
+    /// the summary of a simulation, the variances are the ones of the scores before and after it
+    #[allow(clippy::too_many_arguments)]
+    pub fn simulated(
+        self,
+        weeks: u32,
+        start_week: Week,
+        end_week: Week,
+        gamma: f64,
+        try_exclude_busy_tenants: bool,
+        seed: u64,
+        variance_before: f64,
+        variance_after: f64,
+    ) -> String {
+        match self {
+            Locale::En => format!(
+                "Simulated {} weeks from {} until {} with gamma {:.2}, {} busy tenants and seed {}.\nScore variance before: {:.2}\nScore variance after: {:.2}",
+                weeks,
+                start_week,
+                end_week,
+                gamma,
+                if try_exclude_busy_tenants { "excluding" } else { "including" },
+                seed,
+                variance_before,
+                variance_after,
+            ),
+            Locale::De => format!(
+                "{} Wochen von {} bis {} mit gamma {:.2}, {} beschäftigter Bewohner und Seed {} simuliert.\nScore-Varianz vorher: {:.2}\nScore-Varianz nachher: {:.2}",
+                weeks,
+                start_week,
+                end_week,
+                gamma,
+                if try_exclude_busy_tenants { "ohne" } else { "mit" },
+                seed,
+                variance_before,
+                variance_after,
+            ),
+        }
+    }
+
+    pub fn imported(self, lives_in: usize, chore_logs: usize) -> String {
+        match self {
+            Locale::En => format!(
+                "Imported {} move ins and {} ChoreLogs.",
+                lives_in, chore_logs
+            ),
+            Locale::De => format!(
+                "{} Einzüge und {} ChoreLogs importiert.",
+                lives_in, chore_logs
+            ),
+        }
+    }
+
+    pub fn exported(
+        self,
+        chore_logs: usize,
+        ratings: usize,
+        lives_in: usize,
+        exemptions: usize,
+        scores: usize,
+    ) -> String {
+        match self {
+            Locale::En => format!(
+                "Exported {} ChoreLogs, {} ratings, {} move ins, {} exemptions and {} scores.",
+                chore_logs, ratings, lives_in, exemptions, scores
+            ),
+            Locale::De => format!(
+                "{} ChoreLogs, {} Bewertungen, {} Einzüge, {} Befreiungen und {} Scores exportiert.",
+                chore_logs, ratings, lives_in, exemptions, scores
+            ),
+        }
+    }
+}
+
+impl Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Locale::En => write!(f, "en"),
+            Locale::De => write!(f, "de"),
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "en" => Ok(Locale::En),
+            "de" => Ok(Locale::De),
+            _ => bail!("locale '{}' is not supported, use en or de", s),
+        }
+    }
+}
@@ -0,0 +1,488 @@
+use clap::{Arg, ArgAction, Command};
+
+/// the German help of every command and argument
+///
+/// Commands are keyed by their path of subcommands, arguments by the path of their command and
+/// their long name. The first paragraph is the short help, the whole text the long help.
+const HELP_DE: &[(&str, &str)] = &[
+    (
+        "replan",
+        "den eingeplanten Bewohner ändern
+
+der angegebene Bewohner wird in dieser Woche für keine Aufgabe berücksichtigt",
+    ),
+    ("replan --tenant", "der Name des auszuschließenden Bewohners"),
+    ("replan --week", "die betroffene Woche"),
+    ("replan --year", "das Jahr der betroffenen Woche"),
+    (
+        "swap",
+        "alle Aufgaben einer Woche mit einem anderen Bewohner tauschen
+
+lass den anderen Bewohner weg, um alle möglichen Tauschpartner angeboten zu bekommen",
+    ),
+    ("swap --tenant", "der Name des Bewohners, der tauschen möchte"),
+    ("swap --with", "der Name des Bewohners, mit dem getauscht wird"),
+    ("swap --week", "die betroffene Woche"),
+    ("swap --year", "das Jahr der betroffenen Woche"),
+    ("report", "einen Bericht erstellen"),
+    ("report --week", "die erste Woche des Berichts"),
+    ("report --year", "das Jahr der ersten Woche des Berichts"),
+    (
+        "stats",
+        "Statistiken pro Bewohner und pro Aufgabe über einige Wochen anzeigen",
+    ),
+    ("stats --week", "die erste Woche der Statistik"),
+    ("stats --year", "das Jahr der ersten Woche der Statistik"),
+    (
+        "stats --end-week",
+        "die letzte Woche der Statistik, weglassen, um alles bis letzte Woche einzubeziehen",
+    ),
+    ("stats --end-year", "das Jahr der letzten Woche"),
+    (
+        "stats --sort",
+        "nach Name oder absteigend nach erledigten Aufgaben, Bewertung oder unwilligen Wochen \
+sortieren",
+    ),
+    (
+        "simulate",
+        "vorhersehen, wie fair der Plan in den nächsten Wochen wird
+
+das ändert nichts, die Simulation läuft auf einer Kopie der Datenbank",
+    ),
+    ("simulate --weeks", "die Anzahl der zu simulierenden Wochen"),
+    (
+        "simulate --gamma",
+        "das gamma der Simulation, standardmäßig das aktuelle gamma",
+    ),
+    (
+        "simulate --try-exclude-busy-tenants",
+        "ob beschäftigte Bewohner möglichst ausgeschlossen werden, standardmäßig die aktuelle \
+Einstellung",
+    ),
+    (
+        "simulate --seed",
+        "der Seed des Zufallszahlengenerators, derselbe Seed ergibt dieselbe Simulation",
+    ),
+    ("tenant", "Bewohner verwalten"),
+    ("tenant list", "aktuelle Bewohner auflisten"),
+    (
+        "tenant move-in",
+        "einen Bewohner einziehen lassen
+
+schlägt fehl, wenn das Zimmer nicht frei ist
+
+schlägt fehl, wenn der Bewohner bereits ein anderes Zimmer bewohnt",
+    ),
+    ("tenant move-in --name", "der eindeutige Name des Bewohners"),
+    (
+        "tenant move-in --tag",
+        "wenn der Bewohner einen eindeutigen Chat-Tag hat (Telegram erzwingt das nicht), kann er \
+zum Markieren verwendet werden
+
+Telegram nimmt eine numerische Nutzer-ID oder einen @Nutzernamen, Signal eine Telefonnummer oder \
+eine UUID
+
+wenn der Bewohner bereits existiert und du das angibst, wird der Tag aktualisiert",
+    ),
+    (
+        "tenant move-in --room",
+        "der Name des Zimmers, in das eingezogen wird",
+    ),
+    (
+        "tenant move-out",
+        "einen Bewohner ausziehen lassen
+
+das macht sein Zimmer frei, sodass jemand anderes einziehen kann
+
+schlägt fehl, wenn er kein aktueller Bewohner ist",
+    ),
+    ("tenant move-out --name", "der Name des ehemaligen Bewohners"),
+    ("tenant create-room", "ein neues Zimmer anlegen"),
+    (
+        "tenant create-room --name",
+        "der eindeutige Name des neuen Zimmers",
+    ),
+    ("chore", "Aufgaben verwalten"),
+    ("chore list", "alle aktiven Aufgaben auflisten"),
+    ("chore create", "eine neue Aufgabe zur Freude aller anlegen"),
+    ("chore create --name", "der eindeutige Name der neuen Aufgabe"),
+    (
+        "chore create --description",
+        "die Beschreibung der neuen Aufgabe",
+    ),
+    (
+        "chore deactivate",
+        "eine Aufgabe deaktivieren
+
+das löscht die Aufgabe nicht, sondern ignoriert sie",
+    ),
+    (
+        "chore deactivate --name",
+        "der Name der zu deaktivierenden Aufgabe",
+    ),
+    ("chore reactivate", "eine Aufgabe reaktivieren"),
+    (
+        "chore reactivate --name",
+        "der Name der zu reaktivierenden Aufgabe",
+    ),
+    (
+        "chore done",
+        "eine Aufgabe als erledigt markieren und ihre Bewertungsumfrage vor Ende der Woche öffnen",
+    ),
+    ("chore done --chore", "der Name der Aufgabe"),
+    (
+        "chore done --week",
+        "die Woche, in der die Aufgabe erledigt wurde",
+    ),
+    ("chore done --year", "das Jahr der Woche"),
+    (
+        "chore rating-rules",
+        "die Regeln für schlecht bewertete Aufgaben auflisten",
+    ),
+    (
+        "chore rating-rule",
+        "die Bewertungen einer Aufgabe wirksam machen
+
+unter dem Minimum bewertete Aufgaben zählen entweder nur teilweise im Score oder werden vom selben \
+Bewohner wiederholt, nur Aufgaben ab dieser Woche sind betroffen",
+    ),
+    ("chore rating-rule --chore", "der Name der Aufgabe"),
+    (
+        "chore rating-rule --min-rating",
+        "die minimale durchschnittliche Bewertung, weglassen, um die Regel zu entfernen",
+    ),
+    (
+        "chore rating-rule --action",
+        "partial zählt die Aufgabe nur teilweise, redo teilt sie demselben Bewohner erneut zu",
+    ),
+    (
+        "chore rating-rule --score-factor",
+        "wie viel eine schlecht bewertete Aufgabe mit partial zählt, in [0, 1)",
+    ),
+    ("exemption", "Befreiungen von Aufgaben verwalten"),
+    ("exemption list", "alle Befreiungen auflisten"),
+    (
+        "exemption create",
+        "einen neuen Grund für die Befreiung von einer Aufgabe anlegen
+
+das befreit noch keinen Bewohner, nutze dafür den Unterbefehl 'grant'",
+    ),
+    (
+        "exemption create --chores",
+        "die Namen der Aufgaben, für die die Befreiung gilt",
+    ),
+    (
+        "exemption create --reason",
+        "der eindeutige Grund der Befreiung (z.B. `Müllminister`)",
+    ),
+    (
+        "exemption change",
+        "die Aufgaben, für die die Befreiung gilt, neu festlegen
+
+die Befreiung muss existieren",
+    ),
+    (
+        "exemption change --reason",
+        "der Grund der Befreiung (z.B. `Müllminister`)",
+    ),
+    (
+        "exemption change --chores",
+        "die Namen der Aufgaben, für die die Befreiung gilt",
+    ),
+    (
+        "exemption grant",
+        "einem Bewohner eine Befreiung erteilen
+
+der Bewohner darf die Befreiung noch nicht haben",
+    ),
+    (
+        "exemption grant --tenant",
+        "der Name des Bewohners, der die Befreiung erhält",
+    ),
+    (
+        "exemption grant --reason",
+        "der Grund der zu erteilenden Befreiung (z.B. `Müllminister`)",
+    ),
+    (
+        "exemption revoke",
+        "einem Bewohner eine Befreiung entziehen
+
+der Bewohner muss die zu entziehende Befreiung haben",
+    ),
+    (
+        "exemption revoke --reason",
+        "der eindeutige Grund der Befreiung (z.B. `Müllminister`)",
+    ),
+    (
+        "exemption revoke --tenant",
+        "der Name des Bewohners, dem die Befreiung entzogen wird",
+    ),
+    (
+        "preference",
+        "festlegen, welche Aufgaben ein Bewohner mag oder nicht mag
+
+Bewohner werden öfter für Aufgaben gewählt, die sie mögen, und seltener für Aufgaben, die sie nicht \
+mögen, der Score sorgt trotzdem dafür, dass langfristig jeder seinen fairen Anteil macht",
+    ),
+    (
+        "preference list",
+        "die Vorlieben aller aktuellen Bewohner auflisten",
+    ),
+    (
+        "preference set",
+        "festlegen, wie sehr ein Bewohner eine Aufgabe mag",
+    ),
+    ("preference set --tenant", "der Name des Bewohners"),
+    ("preference set --chore", "der Name der Aufgabe"),
+    (
+        "preference set --preference",
+        "wie sehr der Bewohner die Aufgabe mag",
+    ),
+    (
+        "constraint",
+        "Einschränkungen verwalten, wie oft Bewohner gewählt werden",
+    ),
+    ("constraint list", "alle Einschränkungen auflisten"),
+    (
+        "constraint busy-window",
+        "festlegen, wie viele Wochen vor und nach einer Woche die Aufgaben eines Bewohners ihn \
+beschäftigt machen
+
+beschäftigte Bewohner werden nur gemieden, wenn CHORE_PLANNER_TRY_EXCLUDE_BUSY_TENANTS aktiviert ist",
+    ),
+    (
+        "constraint busy-window --weeks",
+        "die Anzahl der Wochen, 1 berücksichtigt die letzte, diese und die nächste Woche",
+    ),
+    (
+        "constraint max-chores",
+        "begrenzen, wie viele Aufgaben ein Bewohner in derselben Woche machen darf
+
+das ist eine harte Einschränkung, wenn niemand sonst eine Aufgabe machen kann, wird sie nicht \
+eingeplant",
+    ),
+    (
+        "constraint max-chores --tenant",
+        "der Name des zu begrenzenden Bewohners",
+    ),
+    (
+        "constraint max-chores --max",
+        "die maximale Anzahl an Aufgaben pro Woche, weglassen, um die Grenze zu entfernen",
+    ),
+    (
+        "constraint cool-down",
+        "verhindern, dass ein Bewohner dieselbe Aufgabe zu bald wieder macht
+
+das ist eine weiche Einschränkung, sie wird ignoriert, wenn niemand sonst die Aufgabe machen kann",
+    ),
+    ("constraint cool-down --tenant", "der Name des Bewohners"),
+    ("constraint cool-down --chore", "der Name der Aufgabe"),
+    (
+        "constraint cool-down --weeks",
+        "die minimale Anzahl an Wochen zwischen zwei Erledigungen der Aufgabe, weglassen, um die \
+Pause zu entfernen",
+    ),
+    (
+        "settings",
+        "anzeigen oder ändern, wie der chore_planner plant
+
+geänderte Einstellungen werden in der Datenbank gespeichert und überschreiben die Konfiguration",
+    ),
+    ("settings get", "alle Einstellungen auflisten"),
+    ("settings set", "eine Einstellung ändern"),
+    (
+        "settings set weeks-to-plan",
+        "wie viele Wochen im Voraus geplant werden
+
+die neuen Wochen werden sofort geplant",
+    ),
+    (
+        "settings set weeks-to-plan --value",
+        "die Anzahl der Wochen in [0, 52]",
+    ),
+    (
+        "settings set gamma",
+        "Parameter der Wahrscheinlichkeitsverteilung
+
+je höher gamma, desto weniger erhöht ein niedriger Score die Wahrscheinlichkeit, gewählt zu werden, \
+nur ab jetzt geplante Aufgaben sind betroffen",
+    ),
+    ("settings set gamma --value", "das neue gamma in [0, 1]"),
+    (
+        "settings set try-exclude-busy-tenants",
+        "versuchen, beschäftigte Bewohner von Aufgaben auszuschließen
+
+nur ab jetzt geplante Aufgaben sind betroffen",
+    ),
+    (
+        "settings set try-exclude-busy-tenants --value",
+        "true oder false",
+    ),
+    (
+        "settings set locale",
+        "die Sprache der Nachrichten
+
+die Namen der Befehle bleiben englisch, nur ihre Hilfe wird übersetzt",
+    ),
+    ("settings set locale --value", "die neue Sprache"),
+    (
+        "settings set score-decay",
+        "wie alte Aufgaben ihr Gewicht im Score verlieren
+
+ohne Verfall sind Bewohner, die vor langer Zeit viele Aufgaben gemacht haben, lange vor Aufgaben \
+geschützt",
+    ),
+    (
+        "settings set score-decay --value",
+        "half-life halbiert das Gewicht alle paar Wochen, window zählt nur die letzten paar Wochen",
+    ),
+    (
+        "settings set score-decay --weeks",
+        "die Halbwertszeit oder die Größe des Fensters in Wochen",
+    ),
+    (
+        "settings set start-score",
+        "der Score, mit dem Bewohner bei ihrem ersten Einzug starten
+
+bereits eingezogene Bewohner behalten ihren Score",
+    ),
+    (
+        "settings set start-score --value",
+        "null, der mittlere Score der anderen Bewohner oder der Score des ausgezogenen Vormieters des \
+Zimmers",
+    ),
+    ("score", "Bewohnern zusätzliche Arbeit anrechnen"),
+    ("score list", "alle Score-Anpassungen auflisten"),
+    (
+        "score adjust",
+        "zum Score eines Bewohners addieren, z.B. fürs Aufräumen nach einer Party
+
+eine Aufgabe einmal zu erledigen ist 1 wert, ein negativer Betrag zieht dem Bewohner etwas ab",
+    ),
+    (
+        "score adjust --tenant",
+        "der Name des Bewohners, dem etwas angerechnet wird",
+    ),
+    (
+        "score adjust --chore",
+        "die angerechnete Aufgabe, weglassen, um den Betrag auf alle aktiven Aufgaben aufzuteilen",
+    ),
+    ("score adjust --amount", "der zum Score addierte Betrag"),
+    ("score adjust --reason", "warum der Score angepasst wird"),
+    (
+        "export",
+        "den gesamten Verlauf zur Analyse in einer Tabellenkalkulation exportieren
+
+das sendet die ChoreLogs, Bewertungen, Einzüge, Befreiungen und Scores als Dateien",
+    ),
+    (
+        "export --format",
+        "csv erstellt eine Datei pro Tabelle, json eine einzige Datei",
+    ),
+    ("template", "die Nachrichten des Bots anpassen"),
+    (
+        "template list",
+        "alle Vorlagen und ihre Platzhalter auflisten",
+    ),
+    (
+        "template set",
+        "die Vorlage einer Nachricht ersetzen
+
+Platzhalter stehen in geschweiften Klammern, z.B. {tenant}, unbekannte Platzhalter werden abgelehnt",
+    ),
+    ("template set --kind", "die anzupassende Nachricht"),
+    ("template set --template", "die neue Vorlage"),
+    (
+        "template reset",
+        "zur Standardvorlage der Sprache zurückkehren",
+    ),
+    ("template reset --kind", "die zurückzusetzende Nachricht"),
+];
+
+const HELP_TEMPLATE_DE: &str = "\
+{before-help}{about-with-newline}
+Verwendung: {usage}
+
+{all-args}{after-help}";
+
+fn lookup(key: &str) -> Option<&'static str> {
+    HELP_DE
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, help)| *help)
+}
+
+/// Split a help into its first paragraph and, when there is more, the whole text.
+fn split_help(help: &'static str) -> (&'static str, Option<&'static str>) {
+    match help.split_once("\n\n") {
+        Some((short, _)) => (short, Some(help)),
+        None => (help, None),
+    }
+}
+
+/// Translate the help of a command and all its subcommands to German.
+///
+/// Help that hasn't been translated stays English.
+pub fn localize_de(mut command: Command) -> Command {
+    command = translate_de(command, "");
+    // clap only adds the help subcommands when building the command
+    command.build();
+    translate_help_subcommands_de(command)
+}
+
+fn translate_de(mut command: Command, path: &str) -> Command {
+    if let Some(help) = lookup(path) {
+        let (about, long_about) = split_help(help);
+        command = command.about(about).long_about(long_about);
+    }
+    let has_version = command.get_version().is_some();
+    command = command
+        .help_template(HELP_TEMPLATE_DE)
+        .subcommand_help_heading("Befehle")
+        .subcommand_value_name("BEFEHL")
+        .disable_help_flag(true)
+        .arg(
+            Arg::new("help")
+                .short('h')
+                .long("help")
+                .action(ArgAction::Help)
+                .help("Hilfe anzeigen"),
+        );
+    if has_version {
+        command = command.disable_version_flag(true).arg(
+            Arg::new("version")
+                .short('V')
+                .long("version")
+                .action(ArgAction::Version)
+                .help("Version anzeigen"),
+        );
+    }
+    command = command.mut_args(|arg| {
+        let key = format!("{} --{}", path, arg.get_long().unwrap_or_default());
+        let arg = arg.help_heading("Optionen");
+        match lookup(key.trim_start()) {
+            Some(help) => {
+                let (short, long) = split_help(help);
+                arg.help(short).long_help(long)
+            }
+            None => arg,
+        }
+    });
+    command.mut_subcommands(|subcommand| {
+        let path = format!("{} {}", path, subcommand.get_name());
+        translate_de(subcommand, path.trim_start())
+    })
+}
+
+fn translate_help_subcommands_de(command: Command) -> Command {
+    let command = match command.find_subcommand("help") {
+        Some(_) => command.mut_subcommand("help", |help| {
+            help.about("diese Hilfe oder die der angegebenen Befehle anzeigen")
+        }),
+        None => command,
+    };
+    command.mut_subcommands(|subcommand| match subcommand.get_name() {
+        "help" => subcommand,
+        _ => translate_help_subcommands_de(subcommand),
+    })
+}
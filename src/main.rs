@@ -1,30 +1,36 @@
-mod bot;
-mod command;
-mod db;
-mod paginate;
-mod signal_bot;
-mod telegram_bot;
-mod test_bot;
-mod week;
-
-use crate::{
-    bot::MessagableBot, bot::PollableBot, db::Db, signal_bot::SignalBotBuilder,
-    telegram_bot::TelegramBotBuilder, week::Week,
+use chore_planner::{
+    bot::BotProtocol,
+    bot::MessagableBot,
+    bot::PollableBot,
+    config::{Config, HouseholdSettings},
+    console_bot::ConsoleBot,
+    db::Db,
+    household::{handle_household_api_call, handle_update, weekly_action_for_all, Household},
+    http::{next_api_call, serve, ApiCall},
+    signal_bot::SignalBotBuilder,
+    telegram_bot::TelegramBotBuilder,
+    week::Week,
 };
 
-use anyhow::Context;
-use bot::BotProtocol;
+use anyhow::{Context, Result};
 use chrono::Local;
-use command::{handle_next_msg, weekly_action};
-use std::{env, net::ToSocketAddrs, time::Duration};
+use clap::Parser;
+use std::{net::ToSocketAddrs, time::Duration};
 use teloxide::types::ChatId;
 use tokio::{
     signal::unix::{signal, SignalKind},
+    sync::mpsc,
     time::sleep,
 };
 
 /// This is the main loop the application runs.
-async fn run_loop<T: MessagableBot + PollableBot>(mut db: Db, mut bot: T) {
+///
+/// api_receiver is None when the HTTP API is disabled.
+async fn run_loop<T: MessagableBot + PollableBot>(
+    mut households: Vec<Household>,
+    mut bot: T,
+    mut api_receiver: Option<mpsc::Receiver<ApiCall>>,
+) {
     let mut sighup_stream = signal(SignalKind::hangup()).unwrap();
     let mut sigint_stream = signal(SignalKind::interrupt()).unwrap();
     let mut sigterm_stream = signal(SignalKind::terminate()).unwrap();
@@ -39,133 +45,181 @@ async fn run_loop<T: MessagableBot + PollableBot>(mut db: Db, mut bot: T) {
                 break;
             }
             _ = sighup_stream.recv() => {
-                weekly_action(&mut db, &mut bot).await;
+                weekly_action_for_all(&mut households, &mut bot).await;
             }
-            msg_opt = bot.next_msg() => {
-                if let Some(msg) = msg_opt {
-                    handle_next_msg(&mut db, &mut bot, &msg).await;
+            update_opt = bot.next_update() => {
+                if let Some(update) = update_opt {
+                    handle_update(&mut households, &mut bot, update).await;
                 }
             }
+            Some(call) = next_api_call(&mut api_receiver) => {
+                handle_household_api_call(&mut households, &mut bot, call).await;
+            }
         }
     }
     bot.shutdown().await;
 }
 
-/// Create the database and bot before starting the main application loop.
-///
-/// The bot can't be created by a different function and then passed over as the TelegramBotBuilder may not be
-/// dropped.
-async fn initialize_and_run() {
-    let bot_protocol = env::var("CHORE_PLANNER_CHAT_PROTOCOL")
-        .expect("the environment variable CHORE_PLANNER_CHAT_PROTOCOL must be provided")
-        .parse::<BotProtocol>()
-        .context("failed to convert CHORE_PLANNER_CHAT_PROTOCOL to BotProtocol")
-        .unwrap();
-    let debug = env::var("CHORE_PLANNER_DEBUG")
-        .expect("the environment variable CHORE_PLANNER_DEBUG must be provided")
-        .parse::<bool>()
-        .context("failed to convert CHORE_PLANNER_DEBUG to bool")
-        .unwrap();
-    let weeks_to_plan = env::var("CHORE_PLANNER_WEEKS_TO_PLAN")
-        .expect("the environment variable CHORE_PLANNER_WEEKS_TO_PLAN must be provided")
-        .parse::<u32>()
-        .context("failed to convert CHORE_PLANNER_WEEKS_TO_PLAN to u32")
-        .unwrap();
-    let gamma = env::var("CHORE_PLANNER_GAMMA")
-        .expect("the environment variable CHORE_PLANNER_GAMMA must be provided")
-        .parse::<f64>()
-        .context("failed to convert CHORE_PLANNER_GAMMA to f64")
-        .unwrap();
-    let try_exclude_busy_tenants = env::var("CHORE_PLANNER_TRY_EXCLUDE_BUSY_TENANTS")
-        .expect("the environment variable CHORE_PLANNER_TRY_EXCLUDE_BUSY_TENANTS must be provided")
-        .parse::<bool>()
-        .context("failed to convert CHORE_PLANNER_TRY_EXCLUDE_BUSY_TENANTS to bool")
-        .unwrap();
-    let db_path = env::var("CHORE_PLANNER_DB_PATH")
-        .expect("the environment variable CHORE_PLANNER_DB_PATH must be provided");
-    let fallback_to_last_week = env::var("CHORE_PLANNER_FALLBACK_TO_LAST_WEEK")
-        .expect("the environment variable CHORE_PLANNER_FALLBACK_TO_LAST_WEEK must be provided")
-        .parse::<bool>()
-        .context("failed to convert CHORE_PLANNER_FALLBACK_TO_LAST_WEEK to bool")
-        .unwrap();
-
+/// Open a household's database.
+async fn create_household(settings: &HouseholdSettings) -> Result<Household> {
     let mut fallback_week = Week::from(Local::now().date_naive());
-    if fallback_to_last_week {
+    if settings.fallback_to_last_week {
         fallback_week = Week::from_db(fallback_week.db_week() - 1);
     }
     let db = Db::new(
-        &format!("sqlite://{}", db_path),
+        &format!("sqlite://{}", settings.db_path),
         fallback_week,
-        weeks_to_plan,
-        gamma,
-        try_exclude_busy_tenants,
+        settings.weeks_to_plan,
+        settings.gamma,
+        settings.try_exclude_busy_tenants,
+        settings.locale,
         rand::random::<u64>(),
-        debug,
+        settings.debug,
     )
     .await
-    .unwrap();
+    .with_context(|| format!("failed to open the database of {}", settings.name))?;
+    Ok(Household {
+        name: settings.name.clone(),
+        db,
+    })
+}
+
+/// Create the database and bot before starting the main application loop.
+///
+/// The bot can't be created by a different function and then passed over as the TelegramBotBuilder may not be
+/// dropped.
+async fn initialize_and_run(config: Config) -> Result<()> {
+    let bot_protocol = config
+        .chat_protocol
+        .as_deref()
+        .context("the chat protocol is missing")?
+        .parse::<BotProtocol>()?;
+    let settings = config.household_settings();
+    let mut households = vec![];
+    for household_settings in &settings {
+        households.push(create_household(household_settings).await?);
+    }
+    let names = households
+        .iter()
+        .map(|household| household.name.clone())
+        .collect::<Vec<String>>();
+
+    // The HTTP API is optional.
+    let api_receiver = match &config.http.address {
+        Some(address) => {
+            let address = address
+                .to_socket_addrs()
+                .context("failed to convert the HTTP address to SocketAddr")?
+                .next()
+                .context("failed to find any ip address for the HTTP address via DNS")?;
+            let token = config.http.token.clone();
+            if token.is_none() {
+                eprintln!("the HTTP token isn't set, the HTTP API can't run commands");
+            }
+            let (sender, receiver) = mpsc::channel(16);
+            let names = names.clone();
+            tokio::spawn(async move {
+                if let Err(e) = serve(address, token, names, sender).await {
+                    eprintln!("the HTTP server failed: {:#}", e);
+                }
+            });
+            Some(receiver)
+        }
+        None => None,
+    };
 
     match bot_protocol {
         BotProtocol::Telegram => {
             println!("Creating a Telegram bot");
-            let telegram_bot_token = env::var("TELEGRAM_BOT_TOKEN")
-                .expect("the environment variable TELEGRAM_BOT_TOKEN must be provided");
-            let telegram_chat_id = ChatId(
-                env::var("TELEGRAM_CHAT_ID")
-                    .unwrap_or_else(|_| {
-                        eprintln!("the environment variable TELEGRAM_CHAT_ID should be provided");
-                        "0".to_string()
-                    })
-                    .parse::<i64>()
-                    .expect("failed to convert environment variable TELEGRAM_CHAT_ID to i64"),
-            );
             // the builder may not be deleted as the bot holds a borrow of it
             let mut bot_builder = TelegramBotBuilder::new()
-                .token(telegram_bot_token)
-                .chat_id(telegram_chat_id);
+                .token(
+                    config
+                        .telegram
+                        .bot_token
+                        .context("the telegram bot token is missing")?,
+                )
+                .anonymous_polls(config.telegram.anonymous_polls);
+            for household_settings in &settings {
+                let chat_id = household_settings
+                    .telegram_chat_id
+                    .context("the telegram chat id is missing")?;
+                bot_builder = bot_builder.chat_id(ChatId(chat_id));
+            }
             let bot = bot_builder.build().await;
-            run_loop(db, bot).await;
+            run_loop(households, bot, api_receiver).await;
         }
         BotProtocol::Signal => {
             println!("Creating a Signal bot");
-            let endpoint = env::var("SIGNAL_CLI_ENDPOINT")
-                .expect("the environment variable SIGNAL_CLI_ENDPOINT must be provided")
+            let endpoint = config
+                .signal
+                .endpoint
+                .context("the signal-cli endpoint is missing")?
                 .to_socket_addrs()
-                .context("failed to convert SIGNAL_CLI_ENDPOINT to SocketAddr")
-                .unwrap()
+                .context("failed to convert the signal-cli endpoint to SocketAddr")?
                 .next()
-                .context("failed to find any ip address for SIGNAL_CLI_ENDPOINT via DNS")
-                .unwrap();
+                .context("failed to find any ip address for the signal-cli endpoint via DNS")?;
             println!("using endpoint: {endpoint}");
-            let group_id = env::var("SIGNAL_GROUP_ID")
-                .expect("the environment variable SIGNAL_GROUP_ID must be provided");
-            let account_name = env::var("SIGNAL_ACCOUNT_NAME")
-                .expect("the environment variable SIGNAL_ACCOUNT_NAME must be provided");
-            let display_name = env::var("SIGNAL_DISPLAY_NAME")
-                .expect("the environment variable SIGNAL_DISPLAY_NAME must be provided");
-            let allow_message_from_self = env::var("SIGNAL_ALLOW_MESSAGE_FROM_SELF")
-                .expect("the environment variable SIGNAL_ALLOW_MESSAGE_FROM_SELF must be provided")
-                .parse::<bool>()
-                .context("failed to convert SIGNAL_ALLOW_MESSAGE_FROM_SELF to bool")
-                .unwrap();
 
             println!("waiting 10sec to let signal-cli boot up");
             sleep(Duration::from_secs(10)).await;
-            let bot = SignalBotBuilder::new()
-                .account_name(account_name)
-                .display_name(display_name)
-                .endpoint(endpoint)
-                .group_id(group_id)
-                .allow_message_from_self(allow_message_from_self)
+            let mut bot_builder = SignalBotBuilder::new()
+                .account_name(
+                    config
+                        .signal
+                        .account_name
+                        .context("the signal account name is missing")?,
+                )
+                .display_name(
+                    config
+                        .signal
+                        .display_name
+                        .context("the signal display name is missing")?,
+                )
+                .endpoint(endpoint);
+            for household_settings in &settings {
+                bot_builder = bot_builder.group_id(
+                    household_settings
+                        .signal_group_id
+                        .clone()
+                        .context("the signal group id is missing")?,
+                );
+            }
+            let bot = bot_builder
+                .allow_message_from_self(config.signal.allow_message_from_self)
                 .build()
                 .await;
-            run_loop(db, bot).await;
+            run_loop(households, bot, api_receiver).await;
+        }
+        BotProtocol::Console => {
+            println!("Creating a Console bot");
+            run_loop(households, ConsoleBot::new(names), api_receiver).await;
         }
     }
+    Ok(())
+}
+
+/// the chore_planner, configured with the config file at CHORE_PLANNER_CONFIG and environment
+/// variables
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// print the configuration after applying the environment variables and exit
+    #[arg(long)]
+    print_config: bool,
 }
 
 #[tokio::main]
 async fn main() {
     // TODO: ASCII art splash screen
-    initialize_and_run().await;
+    let cli = Cli::parse();
+    let result = match Config::load() {
+        Ok(config) if cli.print_config => config.to_redacted_toml().map(|toml| print!("{}", toml)),
+        Ok(config) => initialize_and_run(config).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        eprintln!("error: {:#}", e);
+        std::process::exit(1);
+    }
 }
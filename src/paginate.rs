@@ -1,3 +1,9 @@
+use crate::bot::Block;
+
+#[cfg(test)]
+#[path = "./tests/paginate_test.rs"]
+mod paginate_test;
+
 /// Split big messages into multiple.
 ///
 /// The resulting strings never container more than message_limit bytes
@@ -32,3 +38,79 @@ pub fn paginate_str(msg: &str, message_limit: usize) -> Vec<String> {
     }
     paged_mono
 }
+
+/// Split the blocks of a big message into multiple messages.
+///
+/// rendered_len returns how many bytes a block takes up in a message; blocks are separated by a
+/// newline. A block too long for a single message is split into monospace blocks.
+/// Headings are kept together with the block following them and blank blocks at the start or end
+/// of a message are dropped.
+pub fn paginate_blocks<F>(
+    blocks: &[Block],
+    message_limit: usize,
+    rendered_len: F,
+) -> Vec<Vec<Block>>
+where
+    F: Fn(&Block) -> usize,
+{
+    // leave some room for the markup around a monospace block
+    const MARKUP_PADDING: usize = 64;
+
+    let page_len = |page: &[Block]| -> usize {
+        page.iter()
+            .map(|b| rendered_len(b) + 1)
+            .sum::<usize>()
+            .saturating_sub(1)
+    };
+
+    let mut paged_blocks: Vec<Vec<Block>> = vec![];
+    for block in blocks {
+        let parts = match rendered_len(block) <= message_limit {
+            true => vec![block.clone()],
+            false => paginate_str(&block.mono(), message_limit - MARKUP_PADDING)
+                .iter()
+                .map(|part| Block::code(part))
+                .collect(),
+        };
+        for part in parts {
+            let mut new_page = vec![];
+            if let Some(last_page) = paged_blocks.last_mut() {
+                // Can we still fit the new block into the last message?
+                if last_page.is_empty()
+                    || page_len(last_page) + 1 + rendered_len(&part) <= message_limit
+                {
+                    if !(last_page.is_empty() && part == Block::Blank) {
+                        last_page.push(part);
+                    }
+                    continue;
+                }
+                // Move trailing headings to the new message.
+                let split = last_page
+                    .iter()
+                    .rposition(|b| !matches!(b, Block::Heading { .. } | Block::Blank))
+                    .map_or(0, |i| i + 1);
+                if split > 0
+                    && last_page[split..]
+                        .iter()
+                        .any(|b| matches!(b, Block::Heading { .. }))
+                {
+                    new_page = last_page.split_off(split);
+                    while new_page.first() == Some(&Block::Blank) {
+                        new_page.remove(0);
+                    }
+                }
+            }
+            if part != Block::Blank {
+                new_page.push(part);
+            }
+            paged_blocks.push(new_page);
+        }
+    }
+    for paged_block in paged_blocks.iter_mut() {
+        while paged_block.last() == Some(&Block::Blank) {
+            paged_block.pop();
+        }
+    }
+    paged_blocks.retain(|paged_block| !paged_block.is_empty());
+    paged_blocks
+}
@@ -1,12 +1,13 @@
 mod signal_cli_interface;
 
 use crate::{
-    bot::{MessagableBot, PollableBot, ReplyMsg},
-    paginate::paginate_str,
+    bot::{Block, BotUpdate, Inline, MessagableBot, PollableBot, ReplyMsg, SentPoll},
+    paginate::paginate_blocks,
     signal_bot::{signal_cli_interface::tcp, signal_cli_interface::RpcClient},
 };
 
 use anyhow::{bail, Context, Result};
+use base64::prelude::{Engine, BASE64_STANDARD};
 use jsonrpsee::{async_client::Client, async_client::ClientBuilder, core::client::Subscription};
 use serde::Deserialize;
 use serde_json::Value;
@@ -15,7 +16,7 @@ use tokio::time::sleep;
 
 pub struct SignalBotBuilder {
     endpoint: Option<SocketAddr>,
-    group_id: Option<String>,
+    group_ids: Vec<String>,
     account_name: Option<String>,
     display_name: Option<String>,
     allow_message_from_self: Option<bool>,
@@ -23,7 +24,10 @@ pub struct SignalBotBuilder {
 
 pub struct SignalBot {
     client: Client,
-    group_id: String,
+    /// the groups of all households
+    group_ids: Vec<String>,
+    /// the index of the group in group_ids messages are sent to
+    selected_chat: usize,
     account_name: String,
     display_name: String,
     allow_message_from_self: bool,
@@ -32,11 +36,17 @@ pub struct SignalBot {
     receive_stream: Option<Subscription<Value>>,
 }
 
+impl Default for SignalBotBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SignalBotBuilder {
     pub fn new() -> Self {
         Self {
             endpoint: None,
-            group_id: None,
+            group_ids: vec![],
             account_name: None,
             display_name: None,
             allow_message_from_self: None,
@@ -46,8 +56,11 @@ impl SignalBotBuilder {
         self.endpoint = Some(endpoint);
         self
     }
+    /// Add a group the SignalBot should listen on.
+    ///
+    /// Each household has its own group, add them in the order of the households.
     pub fn group_id(mut self, group_id: String) -> SignalBotBuilder {
-        self.group_id = Some(group_id);
+        self.group_ids.push(group_id);
         self
     }
     pub fn account_name(mut self, account_name: String) -> SignalBotBuilder {
@@ -73,7 +86,8 @@ impl SignalBotBuilder {
         SignalBot {
             client,
             receive_stream,
-            group_id: self.group_id.clone().unwrap(),
+            group_ids: self.group_ids.clone(),
+            selected_chat: 0,
             account_name: self.account_name.clone().unwrap(),
             display_name: self.display_name.clone().unwrap(),
             allow_message_from_self: self.allow_message_from_self.unwrap(),
@@ -83,7 +97,7 @@ impl SignalBotBuilder {
 
 impl SignalBot {
     /// Check if the update is a message to hand to the application.
-    fn parse_update(&mut self, update: Value) -> Option<String> {
+    fn parse_update(&mut self, update: Value) -> Option<BotUpdate> {
         // example message from a sender that isn't the bot
         // Object {
         //     "account": String("+491717171717"),
@@ -191,22 +205,27 @@ impl SignalBot {
                     eprintln!("ignoring message that doesn't mention the bot");
                     return None;
                 }
-                if sent_message.group_info.group_id != self.group_id {
+                let Some(chat) = self
+                    .group_ids
+                    .iter()
+                    .position(|g| *g == sent_message.group_info.group_id)
+                else {
                     eprintln!(
                         "ignoring message from new group with group_id: {}",
                         sent_message.group_info.group_id
                     );
                     return None;
-                }
+                };
+                self.selected_chat = chat;
                 // The first word is a special character representing the @chore_planner_bot mention.
                 // This needs to be replaced with the literal @chore_planner_bot string.
                 match sent_message.message.find(" ") {
                     Some(pos) => {
                         let mut cmd_message = sent_message.message;
                         cmd_message.replace_range(0..pos, &self.display_name);
-                        Some(cmd_message)
+                        Some(BotUpdate::Msg(cmd_message))
                     }
-                    None => Some(self.display_name.clone()),
+                    None => Some(BotUpdate::Msg(self.display_name.clone())),
                 }
             }
             Err(e) => {
@@ -218,20 +237,36 @@ impl SignalBot {
 
     /// send a signal message formatted in monospace font
     async fn send_mono_str(&self, msg: &str) -> Result<i64> {
-        let length = msg.len();
+        // Signal measures in UTF-16 code units.
+        let length = msg.encode_utf16().count();
         let format = vec![format!("0:{length}:MONOSPACE")];
-        self.send_raw_str(msg, format).await
+        self.send_raw_str(msg, format, vec![]).await
+    }
+    /// send a signal message with some format and mentions
+    async fn send_raw_str(
+        &self,
+        msg: &str,
+        format: Vec<String>,
+        mentions: Vec<String>,
+    ) -> Result<i64> {
+        self.send_raw(msg, format, mentions, vec![]).await
     }
-    /// send a signal message with some format
-    async fn send_raw_str(&self, msg: &str, format: Vec<String>) -> Result<i64> {
+    /// send a signal message with attachments in signal-cli's path or data URI format
+    async fn send_raw(
+        &self,
+        msg: &str,
+        format: Vec<String>,
+        mentions: Vec<String>,
+        attachments: Vec<String>,
+    ) -> Result<i64> {
         let result = self
             .client
             .send(
                 vec![],
-                vec![self.group_id.clone()],
+                vec![self.group_ids[self.selected_chat].clone()],
                 msg.to_string(),
-                vec![],
-                vec![],
+                attachments,
+                mentions,
                 format,
             )
             .await?;
@@ -260,8 +295,96 @@ impl SignalBot {
     }
 }
 
+/// a Signal message with its text styles
+#[derive(Default)]
+struct StyledText {
+    text: String,
+    /// the text styles in signal-cli's start:length:STYLE format
+    styles: Vec<String>,
+    /// the mentions in signal-cli's start:length:RECIPIENT format
+    mentions: Vec<String>,
+}
+
+impl StyledText {
+    /// Render the blocks with Signal's text styles, falling back to monospace for tables.
+    fn from_blocks(blocks: &[Block]) -> Self {
+        let mut styled_text = StyledText::default();
+        for (i, block) in blocks.iter().enumerate() {
+            if i > 0 {
+                styled_text.push("\n", None);
+            }
+            match block {
+                Block::Heading { text, .. } => styled_text.push(text, Some("BOLD")),
+                Block::Paragraph { inlines } => {
+                    for inline in inlines {
+                        match inline {
+                            Inline::Text(text) => styled_text.push(text, None),
+                            Inline::Bold(text) => styled_text.push(text, Some("BOLD")),
+                            Inline::Code(text) => styled_text.push(text, Some("MONOSPACE")),
+                            Inline::Mention { name, tag } => styled_text.push_mention(name, tag),
+                        }
+                    }
+                }
+                Block::Table { .. } | Block::Code { .. } => {
+                    styled_text.push(&block.mono(), Some("MONOSPACE"))
+                }
+                Block::Blank => {}
+            }
+        }
+        styled_text
+    }
+
+    fn push(&mut self, text: &str, style: Option<&str>) {
+        if let Some(style) = style {
+            // Signal measures in UTF-16 code units.
+            let start = self.text.encode_utf16().count();
+            let length = text.encode_utf16().count();
+            if length > 0 {
+                self.styles.push(format!("{start}:{length}:{style}"));
+            }
+        }
+        self.text += text;
+    }
+
+    /// Mention a tenant when the tag is a Signal recipient, otherwise write the name and tag.
+    ///
+    /// Signal replaces the placeholder character with the recipient's name.
+    fn push_mention(&mut self, name: &str, tag: &str) {
+        match is_recipient(tag) {
+            true => {
+                let start = self.text.encode_utf16().count();
+                self.mentions.push(format!("{start}:1:{tag}"));
+                self.text.push('\u{FFFC}');
+            }
+            false => self.push(&format!("{name} ({tag})"), None),
+        }
+    }
+}
+
+/// Check whether a tag is a phone number or an account's UUID, which Signal can mention.
+fn is_recipient(tag: &str) -> bool {
+    let is_phone_number = tag
+        .strip_prefix('+')
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+    let is_uuid = tag.len() == 36
+        && tag.chars().enumerate().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        });
+    is_phone_number || is_uuid
+}
+
+/// Guess the MIME type of the text files the chore_planner sends from their name.
+fn mime_type(name: &str) -> &'static str {
+    match name.rsplit_once('.').map(|(_, extension)| extension) {
+        Some("csv") => "text/csv",
+        Some("json") => "application/json",
+        _ => "text/plain",
+    }
+}
+
 impl MessagableBot for SignalBot {
-    async fn next_msg(&mut self) -> Option<String> {
+    async fn next_update(&mut self) -> Option<BotUpdate> {
         // The stream is opened at start. When it is closed here, the chore_planner can no longer
         // function and needs to be restarted.
         let stream = self.receive_stream.as_mut().unwrap();
@@ -285,25 +408,96 @@ impl MessagableBot for SignalBot {
             eprintln!("sending error: {:?}", e);
             ReplyMsg::from_mono(&e.to_string())
         });
-        let mut paginated_mono_msgs = paginate_str(&msg.mono_msg, MSG_LIMIT)
+        let mut paginated_msgs = paginate_blocks(&msg.blocks, MSG_LIMIT, |b| b.mono().len())
             .into_iter()
             .peekable();
-        while let Some(paginated_mono_msg) = paginated_mono_msgs.next() {
-            let paginated_mono_msg_trimmed = paginated_mono_msg.trim();
-            // ignore empty messages
-            if paginated_mono_msg_trimmed.is_empty() {
-                continue;
-            }
-            if let Err(e) = self.send_mono_str(paginated_mono_msg_trimmed).await {
-                eprintln!("Error sending mono message: {:?}", e);
+        while let Some(paginated_msg) = paginated_msgs.next() {
+            let styled_text = StyledText::from_blocks(&paginated_msg);
+            if let Err(e) = self
+                .send_raw_str(&styled_text.text, styled_text.styles, styled_text.mentions)
+                .await
+            {
+                eprintln!("Error sending message: {:?}", e);
             };
             println!("sent message");
             // wait between sending messages
-            if paginated_mono_msgs.peek().is_some() {
+            if paginated_msgs.peek().is_some() {
                 sleep(TIME_BETWEEN_MESSAGES).await;
             }
         }
-        // TODO: implement tags
+
+        // tags that were mentioned inline already notified their tenants
+        let tags = msg.unmentioned_tags();
+        if !tags.is_empty() {
+            sleep(TIME_BETWEEN_MESSAGES).await;
+            let mut styled_text = StyledText::default();
+            for (i, tag) in tags.iter().enumerate() {
+                if i > 0 {
+                    styled_text.push(" ", None);
+                }
+                match is_recipient(tag) {
+                    true => styled_text.push_mention(tag, tag),
+                    false => styled_text.push(tag, None),
+                }
+            }
+            if let Err(e) = self
+                .send_raw_str(&styled_text.text, styled_text.styles, styled_text.mentions)
+                .await
+            {
+                eprintln!("Error sending tags {:?}: {:?}", tags, e);
+            };
+        }
+
+        for attachment in &msg.attachments {
+            sleep(TIME_BETWEEN_MESSAGES).await;
+            if let Err(e) = self
+                .send_file(
+                    &attachment.filename,
+                    attachment.content.as_bytes(),
+                    attachment.caption.as_deref(),
+                )
+                .await
+            {
+                eprintln!("Error sending file {}: {:?}", attachment.filename, e);
+                // Let the chat know that something is missing.
+                if let Err(e) = self
+                    .send_raw_str(
+                        &format!("failed to send {}: {}", attachment.filename, e),
+                        vec![],
+                        vec![],
+                    )
+                    .await
+                {
+                    eprintln!("Error sending message: {:?}", e);
+                }
+            }
+        }
+    }
+
+    fn sends_long_msgs_as_file(&self) -> bool {
+        true
+    }
+
+    /// Send the file inline as data URI, so signal-cli doesn't need access to the file system.
+    async fn send_file(&mut self, name: &str, bytes: &[u8], caption: Option<&str>) -> Result<()> {
+        let data_uri = format!(
+            "data:{};filename={};base64,{}",
+            mime_type(name),
+            name,
+            BASE64_STANDARD.encode(bytes)
+        );
+        self.send_raw(caption.unwrap_or_default(), vec![], vec![], vec![data_uri])
+            .await?;
+        println!("sent file {}", name);
+        Ok(())
+    }
+
+    fn selected_chat(&self) -> usize {
+        self.selected_chat
+    }
+
+    fn select_chat(&mut self, chat: usize) {
+        self.selected_chat = chat;
     }
 
     fn get_name(&self) -> &str {
@@ -321,12 +515,15 @@ impl MessagableBot for SignalBot {
 
 impl PollableBot for SignalBot {
     /// The question and options may not be longer than some 2000 bytes combined.
-    async fn send_poll(&mut self, question: &str, options: Vec<String>) -> Result<i64> {
+    async fn send_poll(&mut self, question: &str, options: Vec<String>) -> Result<SentPoll> {
         let msg = question.to_string()
             + "\n\n"
             + &options.into_iter().collect::<Vec<String>>().join("\n");
 
-        self.send_mono_str(&msg).await
+        Ok(SentPoll {
+            id: self.send_mono_str(&msg).await?,
+            vote_key: None,
+        })
     }
 
     async fn stop_poll(&mut self, _poll_id: i64) -> Result<Vec<(String, u32)>> {
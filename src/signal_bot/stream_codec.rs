@@ -41,7 +41,7 @@ impl Decoder for StreamCodec {
 
             match str::from_utf8(line.as_ref()) {
                 Ok(s) => Ok(Some(s.to_string())),
-                Err(_) => Err(io::Error::new(io::ErrorKind::Other, "invalid UTF-8")),
+                Err(_) => Err(io::Error::other("invalid UTF-8")),
             }
         } else {
             Ok(None)
@@ -1,21 +1,28 @@
 use crate::{
-    bot::{MessagableBot, PollableBot, ReplyMsg},
-    paginate::paginate_str,
+    bot::{Action, Block, BotUpdate, Inline, MessagableBot, PollableBot, ReplyMsg, SentPoll},
+    paginate::paginate_blocks,
 };
 
 use anyhow::{bail, Result};
 use futures_util::stream::StreamExt;
 use std::pin::Pin;
 use teloxide::{
-    payloads::{SendMessageSetters, SendPollSetters},
+    payloads::{SendDocumentSetters, SendMessageSetters, SendPollSetters},
     requests::Requester,
-    types::{ChatId, MessageId, ParseMode, UpdateId},
+    types::{
+        CallbackQuery, ChatId, InlineKeyboardButton, InlineKeyboardMarkup, InputFile, MessageId,
+        ParseMode, UpdateId,
+    },
     update_listeners::{self, AsUpdateStream, PollingStream},
-    utils::markdown::code_block,
+    utils::html::escape,
     Bot as TeloxideBot,
 };
 use tokio::time::{sleep, Duration};
 
+const TIME_BETWEEN_MESSAGES: Duration = Duration::from_millis(500);
+// telegrams limit is 4096 but let's leave some padding
+const MSG_LIMIT: usize = 4050;
+
 /// Build a TelegramBot with this.
 ///
 /// rust doesn't support self-referential structs. TelegramBot::update_stream references the
@@ -24,25 +31,37 @@ use tokio::time::{sleep, Duration};
 /// The TelegramBotBuilder may not be dropped before the built TelegramBot.
 pub struct TelegramBotBuilder {
     token: Option<String>,
-    chat_id: Option<ChatId>,
+    chat_ids: Vec<ChatId>,
+    anonymous_polls: bool,
     listener: Option<update_listeners::Polling<TeloxideBot>>,
 }
 
 pub struct TelegramBot<'a> {
     bot: TeloxideBot,
-    chat_id: ChatId,
+    /// the chats of all households
+    chat_ids: Vec<ChatId>,
+    /// the index of the chat in chat_ids messages are sent to
+    selected_chat: usize,
     /// references TelegramBotBuilder::listener
     update_stream: Pin<Box<PollingStream<'a, TeloxideBot>>>,
     bot_username: String,
     /// None when no update has been received yet
     last_id_received: Option<UpdateId>,
+    anonymous_polls: bool,
+}
+
+impl Default for TelegramBotBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TelegramBotBuilder {
     pub fn new() -> Self {
         Self {
             token: None,
-            chat_id: None,
+            chat_ids: vec![],
+            anonymous_polls: true,
             listener: None,
         }
     }
@@ -52,31 +71,44 @@ impl TelegramBotBuilder {
         self.token = Some(token);
         self
     }
-    /// Set id of the chat the TelegramBot should listen on.
+    /// Add the id of a chat the TelegramBot should listen on.
+    ///
+    /// Each household has its own chat, add them in the order of the households.
     pub fn chat_id(mut self, chat_id: ChatId) -> Self {
-        self.chat_id = Some(chat_id);
+        self.chat_ids.push(chat_id);
+        self
+    }
+    /// Set whether polls hide who voted for what.
+    ///
+    /// Votes of non-anonymous polls are reported as BotUpdate::PollAnswer.
+    pub fn anonymous_polls(mut self, anonymous_polls: bool) -> Self {
+        self.anonymous_polls = anonymous_polls;
         self
     }
 
     /// Build a TelegramBot.
     /// Don't drop the TelegramBotBuilder while using the bot.
-    pub async fn build(&mut self) -> TelegramBot {
+    pub async fn build(&mut self) -> TelegramBot<'_> {
         let bot = TeloxideBot::new(self.token.as_ref().unwrap());
         self.listener = Some(update_listeners::polling_default(bot.clone()).await);
         let bot_username = format!("@{}", bot.get_me().await.unwrap().username.clone().unwrap());
         TelegramBot {
             bot,
-            chat_id: self.chat_id.unwrap(),
+            chat_ids: self.chat_ids.clone(),
+            selected_chat: 0,
             update_stream: Box::pin(self.listener.as_mut().unwrap().as_stream()),
             bot_username,
             last_id_received: None,
+            anonymous_polls: self.anonymous_polls,
         }
     }
 }
 
 impl<'a> TelegramBot<'a> {
     /// Check if the update is a message to hand to the application.
-    fn parse_update(&mut self, update: teloxide::types::Update) -> Option<String> {
+    ///
+    /// Pressed buttons are handed to the application as if their command was typed.
+    async fn parse_update(&mut self, update: teloxide::types::Update) -> Option<BotUpdate> {
         if let Some(last_id_received) = self.last_id_received {
             if update.id <= last_id_received {
                 println!("ignore old id: {}", update.id.0);
@@ -86,10 +118,10 @@ impl<'a> TelegramBot<'a> {
         self.last_id_received = Some(update.id);
         match update.kind {
             teloxide::types::UpdateKind::Message(msg) => {
-                if msg.chat.id != self.chat_id {
+                let Some(chat) = self.chat_ids.iter().position(|&c| c == msg.chat.id) else {
                     eprintln!("ignoring new chat with id: {}", msg.chat.id);
                     return None;
-                }
+                };
                 match msg.text() {
                     Some(text) => {
                         // ignore messages not meant for this bot
@@ -99,7 +131,8 @@ impl<'a> TelegramBot<'a> {
                             .to_lowercase()
                             .starts_with(&self.bot_username)
                         {
-                            Some(text.to_string())
+                            self.selected_chat = chat;
+                            Some(BotUpdate::Msg(text.to_string()))
                         } else {
                             eprintln!("ignore as it doesn't start with {}", self.bot_username);
                             None
@@ -108,16 +141,154 @@ impl<'a> TelegramBot<'a> {
                     None => None,
                 }
             }
+            teloxide::types::UpdateKind::PollAnswer(answer) => {
+                let Some(user) = answer.voter.user() else {
+                    eprintln!("ignoring anonymous vote in poll {}", answer.poll_id);
+                    return None;
+                };
+                // Tenants can be tagged by their user id or username.
+                let mut voter_tags = vec![user.id.to_string()];
+                if let Some(username) = &user.username {
+                    voter_tags.push(format!("@{}", username));
+                }
+                Some(BotUpdate::PollAnswer {
+                    vote_key: answer.poll_id,
+                    voter_tags,
+                    option: answer.option_ids.first().map(|&o| o.into()),
+                })
+            }
+            teloxide::types::UpdateKind::CallbackQuery(query) => {
+                self.parse_callback_query(query).await
+            }
             _ => None,
         }
     }
+
+    /// Turn a pressed button back into its command.
+    async fn parse_callback_query(&mut self, query: CallbackQuery) -> Option<BotUpdate> {
+        // Telegram shows a loading indicator on the button until the query is answered.
+        if let Err(e) = self.bot.answer_callback_query(query.id.clone()).await {
+            eprintln!("Error answering callback query: {:?}", e);
+        }
+        let Some(chat) = query
+            .message
+            .as_ref()
+            .and_then(|m| self.chat_ids.iter().position(|&c| c == m.chat().id))
+        else {
+            eprintln!("ignoring callback query from another chat");
+            return None;
+        };
+        let data = query.data?;
+        self.selected_chat = chat;
+        println!("{} pressed button: {}", query.from.full_name(), data);
+        Some(BotUpdate::Msg(format!("{} {}", self.bot_username, data)))
+    }
+
+    /// Send the blocks as one or more messages with the buttons of the actions below the last one.
+    async fn send_blocks(&self, blocks: &[Block], actions: &[Action]) {
+        let mut paginated_msgs = paginate_blocks(blocks, MSG_LIMIT, |b| render_html(b).len())
+            .into_iter()
+            .peekable();
+        while let Some(paginated_msg) = paginated_msgs.next() {
+            let mut request = <TeloxideBot as Requester>::send_message(
+                &self.bot,
+                self.chat_ids[self.selected_chat],
+                paginated_msg
+                    .iter()
+                    .map(render_html)
+                    .collect::<Vec<String>>()
+                    .join("\n"),
+            )
+            .parse_mode(ParseMode::Html);
+            // Put the buttons below the last message.
+            if paginated_msgs.peek().is_none() {
+                if let Some(keyboard) = self.inline_keyboard(actions) {
+                    request = request.reply_markup(keyboard);
+                }
+            }
+            if let Err(e) = request.await {
+                eprintln!("Error sending message: {:?}", e);
+            };
+            println!("sent message");
+            // wait between sending messages
+            if paginated_msgs.peek().is_some() {
+                sleep(TIME_BETWEEN_MESSAGES).await;
+            }
+        }
+    }
+
+    /// Create buttons for the actions.
+    ///
+    /// The bot's name isn't part of the callback data as it is limited to 64 bytes.
+    fn inline_keyboard(&self, actions: &[Action]) -> Option<InlineKeyboardMarkup> {
+        const CALLBACK_DATA_LIMIT: usize = 64;
+
+        let buttons = actions
+            .iter()
+            .filter_map(|action| {
+                let data = action
+                    .command
+                    .trim_start()
+                    .strip_prefix(&self.bot_username)?
+                    .trim_start();
+                if data.len() > CALLBACK_DATA_LIMIT {
+                    eprintln!("ignoring action with too long command: {}", data);
+                    return None;
+                }
+                Some(vec![InlineKeyboardButton::callback(&action.label, data)])
+            })
+            .collect::<Vec<_>>();
+        match buttons.is_empty() {
+            true => None,
+            false => Some(InlineKeyboardMarkup::new(buttons)),
+        }
+    }
+}
+
+/// Render a block in Telegram's HTML.
+fn render_html(block: &Block) -> String {
+    match block {
+        Block::Heading { text, .. } => format!("<b>{}</b>", escape(text)),
+        Block::Paragraph { inlines } => inlines
+            .iter()
+            .map(|inline| match inline {
+                Inline::Text(text) => escape(text),
+                Inline::Bold(text) => format!("<b>{}</b>", escape(text)),
+                Inline::Code(text) => format!("<code>{}</code>", escape(text)),
+                Inline::Mention { name, tag } => render_mention(name, tag),
+            })
+            .collect(),
+        Block::Table { .. } | Block::Code { .. } => format!("<pre>{}</pre>", escape(&block.mono())),
+        Block::Blank => String::new(),
+    }
+}
+
+/// Render a mention in Telegram's HTML.
+///
+/// Numeric tags are user ids which can be linked even if the user has no username.
+/// Other tags are usernames that Telegram turns into mentions by itself.
+fn render_mention(name: &str, tag: &str) -> String {
+    match tag.parse::<u64>() {
+        Ok(user_id) => format!("<a href=\"tg://user?id={}\">{}</a>", user_id, escape(name)),
+        Err(_) => format!("{} ({})", escape(name), escape(tag)),
+    }
+}
+
+/// Render a tag without a name in Telegram's HTML.
+///
+/// User ids only notify their user as a link, the id itself is the link's text.
+fn render_tag(tag: &str) -> String {
+    match tag.parse::<u64>() {
+        Ok(_) => render_mention(tag, tag),
+        Err(_) => escape(tag),
+    }
 }
 
 impl<'a> MessagableBot for TelegramBot<'a> {
-    async fn next_msg(&mut self) -> Option<String> {
+    async fn next_update(&mut self) -> Option<BotUpdate> {
         let update_res = self.update_stream.next().await;
         match update_res {
-            Some(Ok(update)) => self.parse_update(update),
+            Some(Ok(update)) => self.parse_update(update).await,
             Some(Err(e)) => {
                 eprintln!("getting the next telegram update failed: {:#}", e);
                 None
@@ -127,57 +298,74 @@ impl<'a> MessagableBot for TelegramBot<'a> {
     }
 
     async fn send_msg(&mut self, msg: Result<ReplyMsg>) {
-        const TIME_BETWEEN_MESSAGES: Duration = Duration::from_millis(500);
-        // telegrams limit is 4096 but let's leave some padding
-        const MSG_LIMIT: usize = 4050;
-
         let msg = msg.unwrap_or_else(|e| {
             eprintln!("sending error: {:?}", e);
             ReplyMsg::from_mono(&e.to_string())
         });
 
-        let mut paginated_mono_msgs = paginate_str(&msg.mono_msg, MSG_LIMIT)
-            .into_iter()
-            .peekable();
-        while let Some(paginated_mono_msg) = paginated_mono_msgs.next() {
-            let paginated_mono_msg_trimmed = paginated_mono_msg.trim();
-            // ignore empty messages
-            if paginated_mono_msg_trimmed.is_empty() {
-                continue;
-            }
-            if let Err(e) = <TeloxideBot as Requester>::send_message(
-                &self.bot,
-                self.chat_id,
-                code_block(paginated_mono_msg_trimmed),
-            )
-            .parse_mode(ParseMode::MarkdownV2)
-            .await
-            {
-                eprintln!("Error sending mono message: {:?}", e);
-            };
-            println!("sent message");
-            // wait between sending messages
-            if paginated_mono_msgs.peek().is_some() {
-                sleep(TIME_BETWEEN_MESSAGES).await;
-            }
-        }
+        self.send_blocks(&msg.blocks, &msg.actions).await;
 
-        if !msg.tags.is_empty() {
+        // tags that were mentioned inline already notified their tenants
+        let tags = msg.unmentioned_tags();
+        if !tags.is_empty() {
             sleep(TIME_BETWEEN_MESSAGES).await;
             if let Err(e) = <TeloxideBot as Requester>::send_message(
                 &self.bot,
-                self.chat_id,
-                msg.tags
-                    .clone()
-                    .into_iter()
+                self.chat_ids[self.selected_chat],
+                tags.iter()
+                    .map(|tag| render_tag(tag))
                     .collect::<Vec<String>>()
                     .join(" "),
             )
+            .parse_mode(ParseMode::Html)
             .await
             {
-                eprintln!("Error sending tags {:?}: {:?}", msg.tags, e);
+                eprintln!("Error sending tags {:?}: {:?}", tags, e);
             };
         }
+
+        for attachment in &msg.attachments {
+            sleep(TIME_BETWEEN_MESSAGES).await;
+            if let Err(e) = self
+                .send_file(
+                    &attachment.filename,
+                    attachment.content.as_bytes(),
+                    attachment.caption.as_deref(),
+                )
+                .await
+            {
+                eprintln!("Error sending file {}: {:?}", attachment.filename, e);
+                // Send the content as text instead, so nothing is missing.
+                self.send_blocks(&ReplyMsg::from_mono(&attachment.content).blocks, &[])
+                    .await;
+            }
+        }
+    }
+
+    fn sends_long_msgs_as_file(&self) -> bool {
+        true
+    }
+
+    async fn send_file(&mut self, name: &str, bytes: &[u8], caption: Option<&str>) -> Result<()> {
+        let mut request = <TeloxideBot as Requester>::send_document(
+            &self.bot,
+            self.chat_ids[self.selected_chat],
+            InputFile::memory(bytes.to_vec()).file_name(name.to_string()),
+        );
+        if let Some(caption) = caption {
+            request = request.caption(caption);
+        }
+        request.await?;
+        println!("sent file {}", name);
+        Ok(())
+    }
+
+    fn selected_chat(&self) -> usize {
+        self.selected_chat
+    }
+
+    fn select_chat(&mut self, chat: usize) {
+        self.selected_chat = chat;
     }
 
     fn get_name(&self) -> &str {
@@ -188,19 +376,30 @@ impl<'a> MessagableBot for TelegramBot<'a> {
 }
 
 impl<'a> PollableBot for TelegramBot<'a> {
-    async fn send_poll(&mut self, question: &str, options: Vec<String>) -> Result<i64> {
-        let msg = <TeloxideBot as Requester>::send_poll(&self.bot, self.chat_id, question, options)
-            .allows_multiple_answers(false)
-            .is_anonymous(true)
-            .await?;
+    async fn send_poll(&mut self, question: &str, options: Vec<String>) -> Result<SentPoll> {
+        let msg = <TeloxideBot as Requester>::send_poll(
+            &self.bot,
+            self.chat_ids[self.selected_chat],
+            question,
+            options,
+        )
+        .allows_multiple_answers(false)
+        .is_anonymous(self.anonymous_polls)
+        .await?;
         println!("created poll {}", msg.id.0);
-        Ok(msg.id.0.into())
+        Ok(SentPoll {
+            id: msg.id.0.into(),
+            vote_key: match self.anonymous_polls {
+                true => None,
+                false => msg.poll().map(|poll| poll.id.clone()),
+            },
+        })
     }
 
     async fn stop_poll(&mut self, poll_id: i64) -> Result<Vec<(String, u32)>> {
         let poll = <TeloxideBot as Requester>::stop_poll(
             &self.bot,
-            self.chat_id,
+            self.chat_ids[self.selected_chat],
             MessageId(poll_id.try_into()?),
         )
         .await?;
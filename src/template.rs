@@ -0,0 +1,141 @@
+use crate::{bot::Inline, locale::Locale};
+
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+use std::fmt::Display;
+
+/// a message that can be customised with a template
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum TemplateKind {
+    /// the paragraph telling a tenant they have been chosen for a chore
+    Assignment,
+    /// the text above the table of the weekly message
+    BannerGreeting,
+    /// the text below the table of the weekly message
+    BannerFarewell,
+}
+
+/// a value inserted into a template
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Placeholder {
+    Week,
+    Chore,
+    Tenant,
+    Probability,
+    Score,
+    ReplanCommand,
+}
+
+/// a parsed template
+#[derive(Debug, PartialEq)]
+pub enum TemplatePart {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+impl TemplateKind {
+    pub const ALL: [TemplateKind; 3] = [
+        TemplateKind::Assignment,
+        TemplateKind::BannerGreeting,
+        TemplateKind::BannerFarewell,
+    ];
+
+    /// Get the placeholders a template of this kind may use.
+    pub fn placeholders(self) -> &'static [Placeholder] {
+        match self {
+            TemplateKind::Assignment => &[
+                Placeholder::Week,
+                Placeholder::Chore,
+                Placeholder::Tenant,
+                Placeholder::Probability,
+                Placeholder::Score,
+                Placeholder::ReplanCommand,
+            ],
+            TemplateKind::BannerGreeting | TemplateKind::BannerFarewell => &[Placeholder::Week],
+        }
+    }
+
+    /// Split a template into text and placeholders.
+    ///
+    /// Placeholders are written in braces, i.e., `{tenant}`.
+    /// Fail when the template uses a placeholder this kind doesn't know, the error is written in
+    /// the locale.
+    pub fn parse(self, template: &str, locale: Locale) -> Result<Vec<TemplatePart>> {
+        let mut parts = vec![];
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let Some(len) = rest[start..].find('}') else {
+                bail!(locale.template_unclosed_brace());
+            };
+            let name = &rest[start + 1..start + len];
+            let Some(placeholder) = self.placeholders().iter().find(|p| p.name() == name) else {
+                bail!(locale.template_unknown_placeholder(
+                    self,
+                    name,
+                    &self
+                        .placeholders()
+                        .iter()
+                        .map(|p| format!("{{{}}}", p.name()))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            };
+            if start > 0 {
+                parts.push(TemplatePart::Text(rest[..start].to_string()));
+            }
+            parts.push(TemplatePart::Placeholder(*placeholder));
+            rest = &rest[start + len + 1..];
+        }
+        if rest.contains('}') {
+            bail!(locale.template_unopened_brace());
+        }
+        if !rest.is_empty() {
+            parts.push(TemplatePart::Text(rest.to_string()));
+        }
+        Ok(parts)
+    }
+}
+
+impl Placeholder {
+    pub fn name(self) -> &'static str {
+        match self {
+            Placeholder::Week => "week",
+            Placeholder::Chore => "chore",
+            Placeholder::Tenant => "tenant",
+            Placeholder::Probability => "probability",
+            Placeholder::Score => "score",
+            Placeholder::ReplanCommand => "replan_command",
+        }
+    }
+}
+
+/// Fill in the placeholders of a parsed template.
+///
+/// value returns the inline to insert for a placeholder, neighbouring texts are merged.
+pub fn render_template<F>(parts: &[TemplatePart], value: F) -> Vec<Inline>
+where
+    F: Fn(Placeholder) -> Inline,
+{
+    let mut inlines: Vec<Inline> = vec![];
+    for part in parts {
+        let inline = match part {
+            TemplatePart::Text(text) => Inline::Text(text.clone()),
+            TemplatePart::Placeholder(placeholder) => value(*placeholder),
+        };
+        match (inlines.last_mut(), inline) {
+            (Some(Inline::Text(last)), Inline::Text(text)) => last.push_str(&text),
+            (_, inline) => inlines.push(inline),
+        }
+    }
+    inlines
+}
+
+impl Display for TemplateKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TemplateKind::Assignment => write!(f, "assignment"),
+            TemplateKind::BannerGreeting => write!(f, "banner-greeting"),
+            TemplateKind::BannerFarewell => write!(f, "banner-farewell"),
+        }
+    }
+}
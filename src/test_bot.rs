@@ -1,4 +1,4 @@
-use crate::bot::{MessagableBot, PollableBot, ReplyMsg};
+use crate::bot::{BotUpdate, MessagableBot, PollableBot, ReplyMsg, SentPoll};
 
 use anyhow::Result;
 
@@ -23,6 +23,12 @@ pub struct TestBot<
     pub to_send_polls: Vec<Vec<(String, u32)>>,
     /// the id of the next poll to create
     pub next_poll_id: usize,
+    /// whether polls get a vote key, which is the poll id as string
+    pub non_anonymous_polls: bool,
+    /// the chat messages are sent to and updates come from
+    pub selected_chat: usize,
+    /// the chats the messages have been sent to in order
+    pub sent_msg_chats: Vec<usize>,
 }
 
 impl<
@@ -30,17 +36,23 @@ impl<
         ReplyMsgIterator: Iterator<Item = Result<ReplyMsg>>,
     > MessagableBot for TestBot<StringIterator, ReplyMsgIterator>
 {
-    async fn next_msg(&mut self) -> Option<String> {
-        self.to_send_msgs.next()
+    async fn next_update(&mut self) -> Option<BotUpdate> {
+        self.to_send_msgs.next().map(BotUpdate::Msg)
     }
 
     async fn send_msg(&mut self, msg: Result<ReplyMsg>) {
         let expected = self.expected_msgs.next().unwrap();
+        self.sent_msg_chats.push(self.selected_chat);
 
         match msg {
             Ok(msg) => {
                 if let Ok(expected) = expected {
-                    assert_eq!(msg, expected);
+                    // Compare the rendering first for a readable diff.
+                    assert_eq!(msg.mono_msg(), expected.mono_msg());
+                    assert_eq!(msg.blocks, expected.blocks);
+                    assert_eq!(msg.tags, expected.tags);
+                    assert_eq!(msg.actions, expected.actions);
+                    assert_eq!(msg.attachments, expected.attachments);
                 } else {
                     panic!();
                 }
@@ -55,6 +67,14 @@ impl<
         }
     }
 
+    fn selected_chat(&self) -> usize {
+        self.selected_chat
+    }
+
+    fn select_chat(&mut self, chat: usize) {
+        self.selected_chat = chat;
+    }
+
     fn get_name(&self) -> &str {
         "hihi_im_a_test"
     }
@@ -67,12 +87,15 @@ impl<
         ReplyMsgIterator: Iterator<Item = Result<ReplyMsg>>,
     > PollableBot for TestBot<StringIterator, ReplyMsgIterator>
 {
-    async fn send_poll(&mut self, question: &str, options: Vec<String>) -> Result<i64> {
+    async fn send_poll(&mut self, question: &str, options: Vec<String>) -> Result<SentPoll> {
         let t = self.expected_polls[self.next_poll_id].clone();
         assert_eq!(t, (question.to_string(), options));
         let poll_id = self.next_poll_id;
         self.next_poll_id += 1;
-        Ok(poll_id.try_into()?)
+        Ok(SentPoll {
+            id: poll_id.try_into()?,
+            vote_key: self.non_anonymous_polls.then(|| poll_id.to_string()),
+        })
     }
 
     async fn stop_poll(&mut self, poll_id: i64) -> Result<Vec<(String, u32)>> {
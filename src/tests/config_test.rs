@@ -0,0 +1,137 @@
+use crate::{
+    config::{Config, HouseholdSettings},
+    locale::Locale,
+};
+
+#[test]
+fn test_config_household_settings() {
+    let config: Config = toml::from_str(
+        r#"
+chat_protocol = "Console"
+gamma = 0.3
+
+[[households]]
+name = "flat_a"
+db_path = "a.sqlite"
+
+[[households]]
+name = "flat_b"
+db_path = "b.sqlite"
+gamma = 0.9
+try_exclude_busy_tenants = true
+locale = "de"
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.household_settings(),
+        vec![
+            HouseholdSettings {
+                name: "flat_a".to_string(),
+                db_path: "a.sqlite".to_string(),
+                telegram_chat_id: None,
+                signal_group_id: None,
+                debug: false,
+                weeks_to_plan: 5,
+                gamma: 0.3,
+                try_exclude_busy_tenants: false,
+                fallback_to_last_week: true,
+                locale: Locale::En,
+            },
+            HouseholdSettings {
+                name: "flat_b".to_string(),
+                db_path: "b.sqlite".to_string(),
+                telegram_chat_id: None,
+                signal_group_id: None,
+                debug: false,
+                weeks_to_plan: 5,
+                gamma: 0.9,
+                try_exclude_busy_tenants: true,
+                fallback_to_last_week: true,
+                locale: Locale::De,
+            },
+        ]
+    );
+    // Typos shouldn't be silently ignored.
+    assert!(toml::from_str::<Config>("weeks_to_pan = 3").is_err());
+}
+
+#[test]
+fn test_config_validate_weeks_to_plan() {
+    let config: Config = toml::from_str(
+        r#"
+chat_protocol = "Console"
+db_path = "a.sqlite"
+weeks_to_plan = 53
+"#,
+    )
+    .unwrap();
+    let mut errors = vec![];
+    config.validate(&mut errors);
+    assert_eq!(
+        errors,
+        vec!["weeks_to_plan of the household default needs to be in [0, 52]".to_string()]
+    );
+}
+
+#[test]
+fn test_config_validate_http_token() {
+    let mut config: Config = toml::from_str(
+        r#"
+chat_protocol = "Console"
+db_path = "a.sqlite"
+
+[http]
+address = "127.0.0.1:8080"
+token = " "
+"#,
+    )
+    .unwrap();
+    let mut errors = vec![];
+    config.validate(&mut errors);
+    assert_eq!(
+        errors,
+        vec!["http.token (CHORE_PLANNER_HTTP_TOKEN) is empty".to_string()]
+    );
+
+    // Without a token commands are disabled.
+    config.http.token = None;
+    let mut errors = vec![];
+    config.validate(&mut errors);
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn test_config_validate_telegram_chat_id() {
+    let config: Config = toml::from_str(
+        r#"
+chat_protocol = "Telegram"
+telegram.bot_token = "token"
+
+[[households]]
+name = "flat_a"
+db_path = "a.sqlite"
+telegram_chat_id = 42
+
+[[households]]
+name = "flat_b"
+db_path = "b.sqlite"
+telegram_chat_id = 42
+
+[[households]]
+name = "flat_c"
+db_path = "c.sqlite"
+"#,
+    )
+    .unwrap();
+    let mut errors = vec![];
+    config.validate(&mut errors);
+    assert_eq!(
+        errors,
+        vec![
+            "the telegram_chat_id 42 of the household flat_b is used twice".to_string(),
+            "telegram_chat_id (TELEGRAM_CHAT_ID_FLAT_C) of the household flat_c is missing"
+                .to_string(),
+        ]
+    );
+}
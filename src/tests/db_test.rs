@@ -1,12 +1,26 @@
 use crate::{
-    bot::{MessagableBot, ReplyMsg},
-    command::handle_next_msg,
-    db::{rating::RATING_OPTIONS, Db},
+    bot::{Action, Block, BotUpdate, Inline, MessagableBot, ReplyMsg},
+    command::{handle_next_msg, run_command_for_bot},
+    db::{
+        chore_commands::{ChoreRatingRule, PoorRatingAction},
+        export_commands::{ExportedChoreLog, ExportedExemption, ExportedRating},
+        plan_commands::ChorePlan,
+        settings_commands::{ScoreDecay, StartScorePolicy},
+        stats_commands::StatsSort,
+        Db,
+    },
+    household::{handle_household_api_call, handle_update, Household},
+    http::{
+        calendar::render_calendar, dashboard::render_dashboard, handle_api_call, ApiCall,
+        ApiRequest,
+    },
+    locale::Locale,
+    template::TemplateKind,
     test_bot::TestBot,
     week::Week,
 };
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 // cargo test 2>&1 >out.txt ; vi out.txt
 // copy output
@@ -17,65 +31,47 @@ async fn test_bot_messaging() {
     let mut db = prepare_db().await;
     let mut bot = TestBot {
         to_send_msgs: vec!["@chore_planner_bot tenant list".to_string()].into_iter(),
-        expected_msgs: vec![Ok(ReplyMsg::from_mono(
-            r#"# Tenants
-
-+------+---------+-------+
-| room | tenant  | score |
-|      |         | eval  |
-+------+---------+-------+
-| M401 |         |       |
-|      |         |       |
-+------+---------+-------+
-| M402 |  Alex   | -0.58 |
-|      |  @alex  |       |
-+------+---------+-------+
-| M403 | Jonas   | 0.50  |
-|      | @jonas  | 1.00  |
-+------+---------+-------+
-| M404 | Thomas  | 0.00  |
-|      |         |       |
-+------+---------+-------+
-| M405 |         |       |
-|      |         |       |
-+------+---------+-------+
-| M406 |         |       |
-|      |         |       |
-+------+---------+-------+
-| M407 | Olli    | 0.50  |
-|      | @olli69 | 4.00  |
-+------+---------+-------+
-| M408 |  Till   | 0.17  |
-|      |         | 7.33  |
-+------+---------+-------+
-| M409 |  Bob    | -0.58 |
-|      |  @bob   |       |
-+------+---------+-------+
-| M410 |         |       |
-|      |         |       |
-+------+---------+-------+
-| M411 |         |       |
-|      |         |       |
-+------+---------+-------+
-| M412 |         |       |
-|      |         |       |
-+------+---------+-------+
-| M413 |         |       |
-|      |         |       |
-+------+---------+-------+
-| M414 |         |       |
-|      |         |       |
-+------+---------+-------+
-| M415 |         |       |
-|      |         |       |
-+------+---------+-------+"#,
-        ))]
+        expected_msgs: vec![Ok(ReplyMsg::from_blocks(vec![
+            Block::heading(1, "Tenants"),
+            Block::Blank,
+            Block::Table {
+                header: vec!["room", "tenant", "score\neval"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+                rows: vec![
+                    vec!["M401", "\n", "\n"],
+                    vec!["M402", "Alex\n@alex", "-0.58\n"],
+                    vec!["M403", "Jonas\n@jonas", "0.50\n1.00"],
+                    vec!["M404", "Thomas\n", "0.00\n"],
+                    vec!["M405", "\n", "\n"],
+                    vec!["M406", "\n", "\n"],
+                    vec!["M407", "Olli\n@olli69", "0.50\n4.00"],
+                    vec!["M408", "Till\n", "0.17\n7.33"],
+                    vec!["M409", "Bob\n@bob", "-0.58\n"],
+                    vec!["M410", "\n", "\n"],
+                    vec!["M411", "\n", "\n"],
+                    vec!["M412", "\n", "\n"],
+                    vec!["M413", "\n", "\n"],
+                    vec!["M414", "\n", "\n"],
+                    vec!["M415", "\n", "\n"],
+                ]
+                .into_iter()
+                .map(|row| row.into_iter().map(String::from).collect())
+                .collect(),
+            },
+        ]))]
         .into_iter(),
         expected_polls: vec![],
         to_send_polls: vec![],
         next_poll_id: 0,
+        non_anonymous_polls: false,
+        selected_chat: 0,
+        sent_msg_chats: vec![],
+    };
+    let Some(BotUpdate::Msg(msg)) = bot.next_update().await else {
+        panic!();
     };
-    let msg = bot.next_msg().await.unwrap();
     handle_next_msg(&mut db, &mut bot, &msg).await;
 }
 
@@ -88,11 +84,19 @@ async fn test_bot_polling() {
         expected_polls: vec![
             (
                 "How well did Bob do the Spüldienst on 33/2024?".to_string(),
-                RATING_OPTIONS.iter().map(|r| r.to_string()).collect(),
+                Locale::En
+                    .rating_options()
+                    .iter()
+                    .map(|r| r.to_string())
+                    .collect(),
             ),
             (
                 "How well did Bob do the Mülldienst on 33/2024?".to_string(),
-                RATING_OPTIONS.iter().map(|r| r.to_string()).collect(),
+                Locale::En
+                    .rating_options()
+                    .iter()
+                    .map(|r| r.to_string())
+                    .collect(),
             ),
         ],
         to_send_polls: vec![
@@ -103,6 +107,9 @@ async fn test_bot_polling() {
             ],
         ],
         next_poll_id: 0,
+        non_anonymous_polls: false,
+        selected_chat: 0,
+        sent_msg_chats: vec![],
     };
     db.weeks_to_plan = 1;
     db.update_plan(|t, w| format!("testing testing, {}, {}", t, w))
@@ -123,7 +130,7 @@ async fn test_bot_polling() {
         .unwrap();
     // Don't forget that some ratings have been created before this test.
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Chores
 ## Spüldienst
 Times performed: 4
@@ -155,7 +162,7 @@ Take out the trash.
 
     let out = db.list_tenants().await.unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Tenants
 
 +------+---------+-------+
@@ -210,6 +217,120 @@ Take out the trash.
     );
 }
 
+#[tokio::test]
+async fn test_bot_polling_non_anonymous() {
+    let mut db = prepare_db().await;
+    let mut bot = TestBot {
+        to_send_msgs: vec![].into_iter(),
+        expected_msgs: vec![].into_iter(),
+        expected_polls: vec![
+            (
+                "How well did Bob do the Spüldienst on 33/2024?".to_string(),
+                Locale::En
+                    .rating_options()
+                    .iter()
+                    .map(|r| r.to_string())
+                    .collect(),
+            ),
+            (
+                "How well did Bob do the Mülldienst on 33/2024?".to_string(),
+                Locale::En
+                    .rating_options()
+                    .iter()
+                    .map(|r| r.to_string())
+                    .collect(),
+            ),
+        ],
+        // The counts are ignored for non-anonymous polls.
+        to_send_polls: vec![
+            vec![("1 something".to_string(), 7)],
+            vec![("1 something".to_string(), 7)],
+        ],
+        next_poll_id: 0,
+        non_anonymous_polls: true,
+        selected_chat: 0,
+        sent_msg_chats: vec![],
+    };
+    db.weeks_to_plan = 1;
+    db.update_plan(|t, w| format!("testing testing, {}, {}", t, w))
+        .await
+        .unwrap();
+
+    let cur_week = db.get_week_internal().await;
+    db.set_week_internal(Week::from_db(cur_week.db_week() + 1))
+        .await;
+    db.create_rating_polls(&mut bot).await.unwrap();
+
+    let tags = |tags: &[&str]| tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+    // Bob is the worker, his vote doesn't count.
+    db.store_poll_vote("0", &tags(&["1", "@bob"]), Some(4))
+        .await
+        .unwrap();
+    // Chat tags are compared case-insensitively, so Bob can't sneak in a vote.
+    db.store_poll_vote("0", &tags(&["5", "@BOB"]), Some(4))
+        .await
+        .unwrap();
+    db.store_poll_vote("0", &tags(&["2", "@alex"]), Some(1))
+        .await
+        .unwrap();
+    // A retracted vote doesn't count either.
+    db.store_poll_vote("0", &tags(&["3"]), Some(4))
+        .await
+        .unwrap();
+    db.store_poll_vote("0", &tags(&["3"]), None).await.unwrap();
+    // Only the last vote counts.
+    db.store_poll_vote("1", &tags(&["4", "@jonas"]), Some(0))
+        .await
+        .unwrap();
+    db.store_poll_vote("1", &tags(&["4", "@jonas"]), Some(3))
+        .await
+        .unwrap();
+    db.store_poll_vote("1", &tags(&["3"]), Some(2))
+        .await
+        .unwrap();
+    // Votes of other polls are ignored.
+    db.store_poll_vote("other poll", &tags(&["3"]), Some(2))
+        .await
+        .unwrap();
+    assert!(db
+        .store_poll_vote("1", &tags(&["3"]), Some(5))
+        .await
+        .is_err());
+    db.stop_rating_polls(&mut bot).await.unwrap();
+
+    let out = db
+        .list_plan(Some(Week::new(33, 2024).unwrap()))
+        .await
+        .unwrap();
+    // Alex rated the Spüldienst 2, Jonas and someone who isn't a tenant rated the Mülldienst 4 and 3.
+    assert_eq!(
+        out.mono_msg(),
+        r#"# Chores
+## Spüldienst
+Times performed: 4
+Clean the kitchen.
+
+### Plan
++---------+--------+--------+
+|  week   | tenant | rating |
++---------+--------+--------+
+| 33/2024 |  Bob   |  2.00  |
++---------+--------+--------+
+
+
+## Mülldienst
+Times performed: 4
+Take out the trash.
+
+### Plan
++---------+--------+--------+
+|  week   | tenant | rating |
++---------+--------+--------+
+| 33/2024 |  Bob   |  3.50  |
++---------+--------+--------+"#
+    );
+}
+
 #[tokio::test]
 async fn test_get_rooms_tenant_with_tenant() {
     let mut db = prepare_db().await;
@@ -241,7 +362,7 @@ async fn test_list_tenants() {
     let mut db = prepare_db().await;
     let out = db.list_tenants().await.unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Tenants
 
 +------+---------+-------+
@@ -306,7 +427,7 @@ async fn test_move_in_old() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Tenants
 
 +------+---------+-------+
@@ -370,7 +491,7 @@ async fn test_move_in_old_without_tag() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Tenants
 
 +------+---------+-------+
@@ -434,7 +555,7 @@ async fn test_update_tag() {
     .unwrap();
     let out = db.list_tenants().await.unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Tenants
 
 +------+---------+-------+
@@ -498,7 +619,7 @@ async fn test_move_in_new() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Tenants
 
 +------+---------+-------+
@@ -566,7 +687,7 @@ async fn test_move_in_move_out_move_in() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Tenants
 
 +------+---------+-------+
@@ -624,7 +745,7 @@ async fn test_move_in_move_out_move_in() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Tenants
 
 +------+---------+-------+
@@ -684,7 +805,7 @@ async fn test_move_in_move_out_move_in() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Tenants
 
 +------+---------+-------+
@@ -748,8 +869,8 @@ async fn test_move_in_fails() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
-        "the tenant Thomas is currenlty living in M404, move them out of there first"
+        out.mono_msg(),
+        "the tenant Thomas is currently living in M404, move them out of there first"
     );
     let out = db
         .move_in("Jörg", &None, "M402", |t, w| {
@@ -757,7 +878,7 @@ async fn test_move_in_fails() {
         })
         .await
         .unwrap();
-    assert_eq!(out.mono_msg, "Alex is living in room M402");
+    assert_eq!(out.mono_msg(), "Alex is living in room M402");
 }
 
 #[tokio::test]
@@ -768,7 +889,7 @@ async fn test_move_out() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Tenants
 
 +------+---------+-------+
@@ -828,7 +949,7 @@ async fn test_create_room() {
     let mut db = prepare_db().await;
     let out = db.create_room("M400").await.unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Tenants
 
 +------+---------+-------+
@@ -891,7 +1012,7 @@ async fn test_list_plan() {
     let mut db = prepare_db().await;
     let out = db.list_plan(Some(Week::from_db(0))).await.unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Chores
 ## Spüldienst
 Times performed: 3
@@ -936,7 +1057,7 @@ async fn test_create_chore() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Chores
 ## Spüldienst
 Times performed: 3
@@ -985,7 +1106,7 @@ async fn test_set_chore_active_state() {
             .await
             .unwrap();
         assert_eq!(
-            out.mono_msg,
+            out.mono_msg(),
             r#"# Chores
 ## Spüldienst
 Times performed: 3
@@ -1015,7 +1136,7 @@ Make the sprouts happy.
             .await
             .unwrap();
         assert_eq!(
-            out.mono_msg,
+            out.mono_msg(),
             r#"# Chores
 ## Spüldienst
 Times performed: 3
@@ -1035,7 +1156,7 @@ Clean the kitchen.
             .await
             .unwrap();
         assert_eq!(
-            out.mono_msg,
+            out.mono_msg(),
             r#"# Chores
 ## Spüldienst
 Times performed: 3
@@ -1073,7 +1194,7 @@ async fn test_list_exemptions() {
     let mut db = prepare_db().await;
     let out = db.list_exemptions().await.unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Exemptions
 
 +------------------+------------+---------+
@@ -1098,7 +1219,7 @@ async fn test_create_exemptions() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Exemptions
 
 +------------------+------------+---------+
@@ -1130,7 +1251,7 @@ async fn test_change_exemption_reason_remove() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Exemptions
 
 +------------------+------------+---------+
@@ -1158,7 +1279,7 @@ async fn test_change_exemption_reason_remove_and_add() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Exemptions
 
 +------------------+------------+---------+
@@ -1199,7 +1320,7 @@ async fn test_grant_exemptions() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Exemptions
 
 +------------------+------------+---------+
@@ -1250,7 +1371,7 @@ async fn test_revoke_exemptions() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Exemptions
 
 +------------------+------------+---------+
@@ -1340,7 +1461,7 @@ async fn test_get_available_tenants() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Spüldienst on 33/2024 (in 0 weeks): Bob
 Bob, you have been chosen for the Spüldienst on 33/2024.
 According to your effective score -0.83 you've had a probability of 47% to be chosen.
@@ -1486,6 +1607,178 @@ Take out the trash.
     assert_eq!(out, vec![("Jonas".to_string(), 0.0),]);
 }
 
+#[tokio::test]
+async fn test_get_available_tenants_busy_window() {
+    let mut db = prepare_db().await;
+    db.try_exclude_busy_tenants = true;
+    let out_unnormalized = db
+        .get_available_tenants_unnormalized(Week::new(35, 2024).unwrap(), "Mülldienst")
+        .await
+        .unwrap();
+    let out = db.normalize_tenants(out_unnormalized);
+    assert_eq!(
+        out,
+        vec![
+            ("Thomas".to_string(), -0.05555555555555556),
+            ("Jonas".to_string(), -0.05555555555555556),
+            ("Alex".to_string(), 0.11111111111111113)
+        ]
+    );
+
+    // Jonas and Alex did a chore in 32/2024.
+    db.set_busy_window(3).await.unwrap();
+    let out_unnormalized = db
+        .get_available_tenants_unnormalized(Week::new(35, 2024).unwrap(), "Mülldienst")
+        .await
+        .unwrap();
+    let out = db.normalize_tenants(out_unnormalized);
+    assert_eq!(out, vec![("Thomas".to_string(), 0.0)]);
+}
+
+#[tokio::test]
+async fn test_get_limited_tenants() {
+    let mut db = prepare_db().await;
+    sqlx::query(
+        r#"
+INSERT INTO ChoreLog VALUES
+    ((SELECT id FROM Chore WHERE name = 'Spüldienst'), 2850, (SELECT id FROM Tenant WHERE name = 'Bob'), 0, NULL);
+"#,
+    )
+    .execute(&mut db.con)
+    .await
+    .unwrap();
+
+    let out = db
+        .get_limited_tenants(Week::from_db(2850), "Mülldienst")
+        .await
+        .unwrap();
+    assert!(out.is_empty());
+
+    db.set_chore_limit("bob", Some(1)).await.unwrap();
+    let out = db
+        .get_limited_tenants(Week::from_db(2850), "Mülldienst")
+        .await
+        .unwrap();
+    assert_eq!(out, HashSet::from(["Bob".to_string()]));
+    // The ChoreLog being planned doesn't count.
+    let out = db
+        .get_limited_tenants(Week::from_db(2850), "Spüldienst")
+        .await
+        .unwrap();
+    assert!(out.is_empty());
+    let out_unnormalized = db
+        .get_available_tenants_unnormalized(Week::from_db(2850), "Mülldienst")
+        .await
+        .unwrap();
+    assert!(!out_unnormalized.iter().any(|(t, _)| t == "Bob"));
+
+    db.set_chore_limit("Bob", Some(2)).await.unwrap();
+    let out = db
+        .get_limited_tenants(Week::from_db(2850), "Mülldienst")
+        .await
+        .unwrap();
+    assert!(out.is_empty());
+
+    db.set_chore_limit("Bob", None).await.unwrap();
+    // removing a limit that doesn't exist does nothing
+    db.set_chore_limit("Bob", None).await.unwrap();
+    assert!(db.set_chore_limit("Nobody", Some(1)).await.is_err());
+}
+
+#[tokio::test]
+async fn test_get_cooling_down_tenants() {
+    let mut db = prepare_db().await;
+    db.set_cool_down("jonas", "Spüldienst", Some(2))
+        .await
+        .unwrap();
+    let out = db
+        .get_cooling_down_tenants(Week::from_db(2850), "Spüldienst")
+        .await
+        .unwrap();
+    assert_eq!(out, HashSet::from(["Jonas".to_string()]));
+    let out = db
+        .get_cooling_down_tenants(Week::from_db(2851), "Spüldienst")
+        .await
+        .unwrap();
+    assert!(out.is_empty());
+    let out = db
+        .get_cooling_down_tenants(Week::from_db(2850), "Mülldienst")
+        .await
+        .unwrap();
+    assert!(out.is_empty());
+
+    let out_unnormalized = db
+        .get_available_tenants_unnormalized(Week::from_db(2850), "Spüldienst")
+        .await
+        .unwrap();
+    assert!(!out_unnormalized.iter().any(|(t, _)| t == "Jonas"));
+
+    // The cool-down is ignored when no one else is available.
+    let out_unnormalized = db
+        .get_available_tenants_unnormalized(Week::from_db(2850), "Spüldienst")
+        .await
+        .unwrap();
+    for (tenant, _) in out_unnormalized {
+        if tenant != "Jonas" {
+            db.replan(&tenant, Week::from_db(2850), |t, w| {
+                format!("testing testing, {}, {}", t, w)
+            })
+            .await
+            .unwrap();
+        }
+    }
+    let out_unnormalized = db
+        .get_available_tenants_unnormalized(Week::from_db(2850), "Spüldienst")
+        .await
+        .unwrap();
+    let out = db.normalize_tenants(out_unnormalized);
+    assert_eq!(out, vec![("Jonas".to_string(), 0.0)]);
+
+    db.set_cool_down("Jonas", "Spüldienst", None).await.unwrap();
+    let out = db
+        .get_cooling_down_tenants(Week::from_db(2850), "Spüldienst")
+        .await
+        .unwrap();
+    assert!(out.is_empty());
+    db.set_cool_down("Jonas", "Spüldienst", None).await.unwrap();
+
+    assert_eq!(
+        db.set_cool_down("Jonas", "Kochdienst", Some(2))
+            .await
+            .unwrap_err()
+            .to_string(),
+        "the chore Kochdienst doesn't exist"
+    );
+}
+
+#[tokio::test]
+async fn test_list_constraints() {
+    let mut db = prepare_db().await;
+    db.set_chore_limit("Bob", Some(1)).await.unwrap();
+    db.set_cool_down("Jonas", "Spüldienst", Some(2))
+        .await
+        .unwrap();
+    db.set_cool_down("Alex", "Mülldienst", Some(3))
+        .await
+        .unwrap();
+    let out = db.set_busy_window(2).await.unwrap();
+    assert_eq!(
+        out.mono_msg(),
+        r#"# Constraints
+Busy window: 2 weeks
+
++--------+------------+-------------------+
+| tenant |   chore    |    constraint     |
++--------+------------+-------------------+
+|  Alex  | Mülldienst | cool-down 3 weeks |
++--------+------------+-------------------+
+|  Bob   |            |  max 1 per week   |
++--------+------------+-------------------+
+| Jonas  | Spüldienst | cool-down 2 weeks |
++--------+------------+-------------------+"#
+    );
+}
+
 #[tokio::test]
 async fn test_calc_tenant_distribution() {
     let db = prepare_db().await;
@@ -1498,7 +1791,7 @@ async fn test_calc_tenant_distribution() {
         ("Till".to_string(), 0.5),
     ];
     assert_eq!(
-        db.calc_tenant_distribution(tenants),
+        db.calc_tenant_distribution(tenants, &HashMap::new()),
         vec![
             (0.21666666666666665),
             (0.21666666666666665),
@@ -1522,7 +1815,7 @@ async fn test_calc_tenant_distribution_all_zero() {
         ("Till".to_string(), 0.0),
     ];
     assert_eq!(
-        db.calc_tenant_distribution(tenants),
+        db.calc_tenant_distribution(tenants, &HashMap::new()),
         vec![
             (0.16666666666666666),
             (0.16666666666666666),
@@ -1535,9 +1828,16 @@ async fn test_calc_tenant_distribution_all_zero() {
 }
 
 #[tokio::test]
-async fn test_choose_tenant() {
+async fn test_calc_tenant_distribution_preferences() {
     let mut db = prepare_db().await;
-
+    db.set_preference("Alex", "Spüldienst", 1).await.unwrap();
+    db.set_preference("Till", "Spüldienst", -1).await.unwrap();
+    db.set_preference("Olli", "Mülldienst", -1).await.unwrap();
+    let preferences = db.get_preferences("Spüldienst").await.unwrap();
+    assert_eq!(
+        preferences,
+        HashMap::from([("Alex".to_string(), 1), ("Till".to_string(), -1)])
+    );
     let tenants = vec![
         ("Alex".to_string(), -0.75),
         ("Bob".to_string(), -0.75),
@@ -1546,24 +1846,92 @@ async fn test_choose_tenant() {
         ("Olli".to_string(), 0.5),
         ("Till".to_string(), 0.5),
     ];
-    let mut map: HashMap<String, u32> = HashMap::new();
+    let dist = db.calc_tenant_distribution(tenants, &preferences);
+    assert_eq!(
+        dist,
+        vec![
+            0.30548302872062666,
+            0.20365535248041777,
+            0.15665796344647523,
+            0.12532637075718017,
+            0.12532637075718017,
+            0.08355091383812012
+        ]
+    );
+    assert!((dist.iter().sum::<f64>() - 1.0).abs() < 0.0001);
 
-    let n = 100000;
-    for _ in 0..n {
-        let (tenant, score, prob) = db.choose_tenant(tenants.clone()).await.unwrap();
-        match tenant.as_ref() {
-            "Alex" => {
-                assert_eq!(score, -0.75);
-                assert_eq!(prob, 0.21666666666666665);
-            }
-            "Bob" => {
-                assert_eq!(score, -0.75);
-                assert_eq!(prob, 0.21666666666666665);
-            }
-            "Thomas" => {
-                assert_eq!(score, 0.0);
-                assert_eq!(prob, 0.16666666666666666);
-            }
+    // neutral removes the preference
+    db.set_preference("Alex", "Spüldienst", 0).await.unwrap();
+    let preferences = db.get_preferences("Spüldienst").await.unwrap();
+    assert_eq!(preferences, HashMap::from([("Till".to_string(), -1)]));
+    assert!(db.set_preference("Nobody", "Spüldienst", 1).await.is_err());
+    assert_eq!(
+        db.set_preference("Alex", "Kochdienst", 0)
+            .await
+            .unwrap_err()
+            .to_string(),
+        "the chore Kochdienst doesn't exist"
+    );
+}
+
+#[tokio::test]
+async fn test_list_preferences() {
+    let mut db = prepare_db().await;
+    db.set_preference("Alex", "Spüldienst", 1).await.unwrap();
+    db.set_preference("Alex", "Mülldienst", -1).await.unwrap();
+    db.set_preference("Olli", "Mülldienst", 1).await.unwrap();
+    db.set_preference("Olli", "Spüldienst", 1).await.unwrap();
+    // Chris doesn't live here anymore.
+    db.set_preference("Chris", "Spüldienst", 1).await.unwrap();
+    let out = db.list_preferences().await.unwrap();
+    assert_eq!(
+        out.mono_msg(),
+        r#"# Preferences
+
++--------+------------+------------+
+| tenant |   likes    |  dislikes  |
++--------+------------+------------+
+|  Alex  | Spüldienst | Mülldienst |
++--------+------------+------------+
+|  Olli  | Spüldienst |            |
+|        | Mülldienst |            |
++--------+------------+------------+"#
+    );
+}
+
+#[tokio::test]
+async fn test_choose_tenant() {
+    let mut db = prepare_db().await;
+
+    let tenants = vec![
+        ("Alex".to_string(), -0.75),
+        ("Bob".to_string(), -0.75),
+        ("Thomas".to_string(), 0.0),
+        ("Jonas".to_string(), 0.5),
+        ("Olli".to_string(), 0.5),
+        ("Till".to_string(), 0.5),
+    ];
+    let mut map: HashMap<String, u32> = HashMap::new();
+
+    let n = 100000;
+    for _ in 0..n {
+        let (tenant, score, prob) = db
+            .choose_tenant(tenants.clone(), &HashMap::new())
+            .await
+            .unwrap();
+        match tenant.as_ref() {
+            "Alex" => {
+                assert_eq!(score, -0.75);
+                assert_eq!(prob, 0.21666666666666665);
+            }
+            "Bob" => {
+                assert_eq!(score, -0.75);
+                assert_eq!(prob, 0.21666666666666665);
+            }
+            "Thomas" => {
+                assert_eq!(score, 0.0);
+                assert_eq!(prob, 0.16666666666666666);
+            }
             "Jonas" => {
                 assert_eq!(score, 0.5);
                 assert_eq!(prob, 0.13333333333333333);
@@ -1646,7 +2014,7 @@ async fn test_update_plan() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Spüldienst on 33/2024 (in 0 weeks): Bob
 Bob, you have been chosen for the Spüldienst on 33/2024.
 According to your effective score -0.75 you've had a probability of 26% to be chosen.
@@ -1696,7 +2064,7 @@ Take out the trash.
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Spüldienst on 34/2024 (in 1 week): Olli
 Olli, you have been chosen for the Spüldienst on 34/2024.
 According to your effective score 0.30 you've had a probability of 13% to be chosen.
@@ -1826,7 +2194,7 @@ async fn test_create_chore_update() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Clean the Furnace on 33/2024 (in 0 weeks): Jonas
 Jonas, you have been chosen for the Clean the Furnace on 33/2024.
 According to your effective score 0.00 you've had a probability of 20% to be chosen.
@@ -1953,7 +2321,7 @@ async fn test_exempt_update() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Exemptions
 
 +------------------+------------+---------+
@@ -2048,7 +2416,7 @@ async fn test_replan_update() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Mülldienst on 36/2024 (in 3 weeks): Bob
 Bob, you have been chosen for the Mülldienst on 36/2024.
 According to your effective score 0.06 you've had a probability of 33% to be chosen.
@@ -2100,6 +2468,102 @@ Take out the trash.
     );
 }
 
+#[tokio::test]
+async fn test_replan_update_mentions_inline() {
+    let mut db = prepare_db().await;
+    db.weeks_to_plan = 5;
+    db.update_plan(|t, w| format!("testing testing, {}, {}", t, w))
+        .await
+        .unwrap();
+
+    let out = db
+        .replan("Thomas", Week::new(36, 2024).unwrap(), |t, w| {
+            format!("testing testing, {}, {}", t, w)
+        })
+        .await
+        .unwrap();
+    let Block::Paragraph { inlines } = &out.blocks[1] else {
+        panic!("expected a paragraph, got {:?}", out.blocks[1]);
+    };
+    assert_eq!(
+        inlines[0],
+        Inline::Mention {
+            name: "Bob".to_string(),
+            tag: "@bob".to_string()
+        }
+    );
+    assert_eq!(out.tags, HashSet::from(["@bob".to_string()]));
+    assert!(out.unmentioned_tags().is_empty());
+
+    // The banner only shows names in a table, so its tags are sent separately.
+    let banner = db
+        .print_next_week_banner(
+            |t, w| format!("testing testing, {}, {}", t, w),
+            |c, w| format!("done, {}, {}", c, w),
+            |t, o, w| format!("swap, {}, {:?}, {}", t, o, w),
+        )
+        .await
+        .unwrap();
+    assert_eq!(banner.unmentioned_tags(), vec!["@bob"]);
+}
+
+#[tokio::test]
+async fn test_api_command_announces_tags() {
+    let replan = "replan --tenant Thomas --week 36 --year 2024";
+    // the same database to know the reply in advance
+    let mut expected_db = prepare_db().await;
+    expected_db.weeks_to_plan = 5;
+    expected_db
+        .update_plan(|t, w| format!("testing testing, {}, {}", t, w))
+        .await
+        .unwrap();
+    let mut expected_bot = TestBot {
+        to_send_msgs: vec![].into_iter(),
+        expected_msgs: vec![].into_iter(),
+        expected_polls: vec![],
+        to_send_polls: vec![],
+        next_poll_id: 0,
+        non_anonymous_polls: false,
+        selected_chat: 0,
+        sent_msg_chats: vec![],
+    };
+    let expected = run_command_for_bot(&mut expected_db, &mut expected_bot, replan)
+        .await
+        .unwrap();
+    assert!(!expected.tags.is_empty());
+
+    let mut db = prepare_db().await;
+    db.weeks_to_plan = 5;
+    db.update_plan(|t, w| format!("testing testing, {}, {}", t, w))
+        .await
+        .unwrap();
+    // Only the replan is sent to the chat, the tenant list has no tags.
+    let mut bot = TestBot {
+        to_send_msgs: vec![].into_iter(),
+        expected_msgs: vec![Ok(expected.clone())].into_iter(),
+        expected_polls: vec![],
+        to_send_polls: vec![],
+        next_poll_id: 0,
+        non_anonymous_polls: false,
+        selected_chat: 0,
+        sent_msg_chats: vec![],
+    };
+    for command in ["tenant list", replan] {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        let call = ApiCall {
+            household: 0,
+            request: ApiRequest::Command(command.to_string()),
+            reply: sender,
+        };
+        handle_api_call(&mut db, &mut bot, call).await;
+        let response = receiver.await.unwrap().unwrap();
+        if command == replan {
+            assert_eq!(response["mono_msg"], expected.mono_msg());
+        }
+    }
+    assert!(bot.expected_msgs.next().is_none());
+}
+
 #[tokio::test]
 async fn test_replan_update_try_to_exclude_busy() {
     let mut db = prepare_db().await;
@@ -2117,7 +2581,7 @@ async fn test_replan_update_try_to_exclude_busy() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Spüldienst on 33/2024 (in 0 weeks): Till
 Till, you have been chosen for the Spüldienst on 33/2024.
 According to your effective score 0.00 you've had a probability of 100% to be chosen.
@@ -2191,7 +2655,7 @@ async fn test_move_out_update() {
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Tenants
 
 +------+---------+-------+
@@ -2310,7 +2774,7 @@ Take out the trash.
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Tenants
 
 +------+---------+-------+
@@ -2438,7 +2902,7 @@ Take out the trash.
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Tenants
 
 +------+---------+-------+
@@ -2575,7 +3039,7 @@ Take out the trash.
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Tenants
 
 +------+--------+-------+
@@ -2694,7 +3158,7 @@ Take out the trash.
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Tenants
 
 +------+--------+-------+
@@ -2813,7 +3277,7 @@ Take out the trash.
         .await
         .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Tenants
 
 +------+--------+-------+
@@ -2890,6 +3354,366 @@ Take out the trash.
     );
 }
 
+#[tokio::test]
+async fn test_simulate() {
+    let mut db = prepare_db().await;
+    let plan_before = db.list_plan(None).await.unwrap();
+    let out = db.simulate(10, None, Some(true), 42).await.unwrap();
+    assert_eq!(
+        out.mono_msg(),
+        r#"# Simulation
+Simulated 10 weeks from 33/2024 until 43/2024 with gamma 0.80, excluding busy tenants and seed 42.
+Score variance before: 0.20
+Score variance after: 0.53
+
++--------+------------+------------+-------+---------+
+| tenant | Spüldienst | Mülldienst | total | longest |
+|        |            |            |       | streak  |
++--------+------------+------------+-------+---------+
+|  Alex  |     3      |     2      |   5   |    1    |
++--------+------------+------------+-------+---------+
+|  Bob   |     0      |     3      |   3   |    1    |
++--------+------------+------------+-------+---------+
+| Jonas  |     3      |     2      |   5   |    1    |
++--------+------------+------------+-------+---------+
+|  Olli  |     1      |     0      |   1   |    1    |
++--------+------------+------------+-------+---------+
+| Thomas |     2      |     3      |   5   |    1    |
++--------+------------+------------+-------+---------+
+|  Till  |     1      |     0      |   1   |    1    |
++--------+------------+------------+-------+---------+"#
+    );
+
+    // The same seed results in the same simulation.
+    assert_eq!(db.simulate(10, None, Some(true), 42).await.unwrap(), out);
+    // The simulation doesn't change the database.
+    assert_eq!(db.list_plan(None).await.unwrap(), plan_before);
+    assert_eq!(db.get_week_internal().await, Week::new(33, 2024).unwrap());
+
+    assert!(db.simulate(10, Some(1.5), None, 42).await.is_err());
+}
+
+#[tokio::test]
+async fn test_simulate_ignores_weeks_planned_ahead() {
+    let mut db = Db::new(
+        "sqlite::memory:",
+        Week::new(33, 2024).unwrap(),
+        2,
+        0.0,
+        false,
+        Locale::En,
+        0x0DDB1A5E5BAD5EEDu64,
+        false,
+    )
+    .await
+    .unwrap();
+    for (tenant, room) in [("Alex", "M401"), ("Bob", "M402")] {
+        db.create_room(room).await.unwrap();
+        db.move_in(tenant, &None, room, |t, w| format!("{} {}", t, w))
+            .await
+            .unwrap();
+    }
+    db.create_chore("Spüldienst", "Clean the kitchen.", |t, w| {
+        format!("{} {}", t, w)
+    })
+    .await
+    .unwrap();
+    // With gamma 0 Alex and Bob take turns, so after two weeks their scores are even.
+    // The third week planned ahead doesn't count.
+    let out = db.simulate(2, None, None, 0).await.unwrap();
+    assert!(out.mono_msg().contains("Score variance after: 0.00\n"));
+
+    // The weeks already planned ahead don't count before the simulation either.
+    // Three of them would leave one tenant ahead.
+    db.weeks_to_plan = 3;
+    db.update_plan(|t, w| format!("{} {}", t, w)).await.unwrap();
+    let out = db.simulate(2, None, None, 0).await.unwrap();
+    assert!(out.mono_msg().contains("Score variance before: 0.00\n"));
+    assert!(out.mono_msg().contains("Score variance after: 0.00\n"));
+}
+
+#[tokio::test]
+async fn test_simulate_file_database() {
+    let path = std::env::temp_dir().join(format!(
+        "chore_planner_simulate_test_{}.sqlite",
+        std::process::id()
+    ));
+    let mut db = Db::new(
+        &format!("sqlite://{}", path.display()),
+        Week::new(33, 2024).unwrap(),
+        1,
+        0.8,
+        false,
+        Locale::En,
+        0x0DDB1A5E5BAD5EEDu64,
+        false,
+    )
+    .await
+    .unwrap();
+    db.create_room("M401").await.unwrap();
+    db.move_in("Alex", &None, "M401", |t, w| {
+        format!("testing testing, {}, {}", t, w)
+    })
+    .await
+    .unwrap();
+    db.create_chore("Spüldienst", "Clean the kitchen.", |t, w| {
+        format!("testing testing, {}, {}", t, w)
+    })
+    .await
+    .unwrap();
+    let out = db.simulate(2, None, None, 0).await.unwrap();
+    assert_eq!(
+        out.mono_msg(),
+        r#"# Simulation
+Simulated 2 weeks from 33/2024 until 35/2024 with gamma 0.80, including busy tenants and seed 0.
+Score variance before: 0.00
+Score variance after: 0.00
+
++--------+------------+-------+---------+
+| tenant | Spüldienst | total | longest |
+|        |            |       | streak  |
++--------+------------+-------+---------+
+|  Alex  |     2      |   2   |    2    |
++--------+------------+-------+---------+"#
+    );
+    drop(db);
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+}
+
+#[tokio::test]
+async fn test_set_gamma() {
+    let mut db = prepare_db().await;
+    assert!(db.set_gamma(1.5).await.is_err());
+    db.set_gamma(0.3).await.unwrap();
+    assert_eq!(db.gamma, 0.3);
+    assert_eq!(
+        db.get_key_values().await.unwrap(),
+        vec![("gamma".to_string(), Some("0.3".to_string()))]
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_key_values() {
+    let mut db = prepare_db().await;
+    // values edited by hand are ignored instead of panicking
+    for key in [
+        "busy_window",
+        "gamma",
+        "weeks_to_plan",
+        "try_exclude_busy_tenants",
+        "locale",
+    ] {
+        sqlx::query("REPLACE INTO KeyValue VALUES (?1, 'nonsense');")
+            .bind(key)
+            .execute(&mut db.con)
+            .await
+            .unwrap();
+    }
+    assert_eq!(db.get_busy_window_internal().await, 1);
+    assert_eq!(db.get_gamma_internal().await, None);
+    assert_eq!(db.get_weeks_to_plan_internal().await, None);
+    assert_eq!(db.get_try_exclude_busy_tenants_internal().await, None);
+    assert_eq!(db.get_locale_internal().await, None);
+
+    db.set_score_decay_internal(ScoreDecay::HalfLife { weeks: 4 })
+        .await;
+    sqlx::query("REPLACE INTO KeyValue VALUES ('score_decay_weeks', 'nonsense');")
+        .execute(&mut db.con)
+        .await
+        .unwrap();
+    assert_eq!(db.get_score_decay_internal().await, ScoreDecay::None);
+
+    // The ScoreWeight VIEW ignores a garbage decay just like get_score_decay_internal.
+    db.set_score_decay_internal(ScoreDecay::None).await;
+    let none = get_scores(&mut db, None, None).await;
+    for (key, value) in [
+        ("score_decay", "nonsense"),
+        ("score_decay_weeks", "1"),
+        ("score_decay_factor", "0.5"),
+    ] {
+        sqlx::query("REPLACE INTO KeyValue VALUES (?1, ?2);")
+            .bind(key)
+            .bind(value)
+            .execute(&mut db.con)
+            .await
+            .unwrap();
+    }
+    assert_eq!(db.get_score_decay_internal().await, ScoreDecay::None);
+    assert_eq!(get_scores(&mut db, None, None).await, none);
+    for (decay, weeks, factor) in [
+        ("window", "nonsense", "1"),
+        ("window", "0", "1"),
+        ("half-life", "1", "nonsense"),
+        ("half-life", "1", "1"),
+    ] {
+        for (key, value) in [
+            ("score_decay", decay),
+            ("score_decay_weeks", weeks),
+            ("score_decay_factor", factor),
+        ] {
+            sqlx::query("REPLACE INTO KeyValue VALUES (?1, ?2);")
+                .bind(key)
+                .bind(value)
+                .execute(&mut db.con)
+                .await
+                .unwrap();
+        }
+        assert_eq!(db.get_score_decay_internal().await, ScoreDecay::None);
+        assert_eq!(get_scores(&mut db, None, None).await, none);
+    }
+
+    sqlx::query("REPLACE INTO KeyValue VALUES ('start_score', 'nonsense');")
+        .execute(&mut db.con)
+        .await
+        .unwrap();
+    assert_eq!(
+        db.get_start_score_policy_internal().await,
+        StartScorePolicy::Zero
+    );
+}
+
+#[tokio::test]
+async fn test_settings() {
+    let mut db = prepare_db().await;
+    assert!(db
+        .set_weeks_to_plan(53, |t, w| format!("testing testing, {}, {}", t, w))
+        .await
+        .is_err());
+    let out = db
+        .set_weeks_to_plan(1, |t, w| format!("testing testing, {}, {}", t, w))
+        .await
+        .unwrap();
+    assert_eq!(db.weeks_to_plan, 1);
+    // the new week is planned right away
+    assert!(out
+        .mono_msg()
+        .contains("# Spüldienst on 33/2024 (in 0 weeks): Bob"));
+
+    assert!(db.set_gamma(-0.1).await.is_err());
+    db.set_gamma(0.3).await.unwrap();
+    let out = db.set_try_exclude_busy_tenants(true).await.unwrap();
+    assert!(db.try_exclude_busy_tenants);
+    assert_eq!(
+        out.mono_msg(),
+        r#"# Settings
++--------------------------+-------+
+|         setting          | value |
++--------------------------+-------+
+|      weeks-to-plan       |   1   |
++--------------------------+-------+
+|          gamma           |  0.3  |
++--------------------------+-------+
+| try-exclude-busy-tenants | true  |
++--------------------------+-------+
+|          locale          |  en   |
++--------------------------+-------+
+|       score-decay        | none  |
++--------------------------+-------+
+|       start-score        | zero  |
++--------------------------+-------+"#
+    );
+    assert_eq!(
+        db.get_key_values().await.unwrap(),
+        vec![
+            ("gamma".to_string(), Some("0.3".to_string())),
+            (
+                "try_exclude_busy_tenants".to_string(),
+                Some("true".to_string())
+            ),
+            ("weeks_to_plan".to_string(), Some("1".to_string())),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_get_report() {
+    let mut db = prepare_db().await;
+    let report = db.get_report(Week::new(30, 2024).unwrap()).await.unwrap();
+    assert_eq!(
+        report
+            .plan
+            .iter()
+            .map(|c| (c.name.as_str(), c.times_performed, c.plan.len()))
+            .collect::<Vec<_>>(),
+        vec![("Spüldienst", 3, 3), ("Mülldienst", 3, 3)]
+    );
+    assert_eq!(
+        serde_json::to_string(&report.plan[0].plan[1]).unwrap(),
+        r#"{"week":{"week":31,"year":2024},"tenant":"Olli","rating":4.0}"#
+    );
+    assert_eq!(
+        serde_json::to_string(&report.rooms[2]).unwrap(),
+        r#"{"room":"M403","tenant":"Jonas","chat_tag":"@jonas","score":0.5,"rating":1.0}"#
+    );
+    assert_eq!(
+        serde_json::to_string(&db.get_exemptions().await.unwrap()).unwrap(),
+        r#"[{"reason":"God","chores":["Mülldienst","Spüldienst"],"tenants":[]},{"reason":"Getränkeminister","chores":["Mülldienst"],"tenants":[]},{"reason":"Bestandsminister","chores":["Mülldienst"],"tenants":["Olli","Till","Chris"]}]"#
+    );
+}
+
+#[tokio::test]
+async fn test_render_dashboard() {
+    let mut db = prepare_db().await;
+    db.create_chore("<b>Bad</b>", "\"quotes\" & more", |t, w| {
+        format!("testing testing, {}, {}", t, w)
+    })
+    .await
+    .unwrap();
+    let html = render_dashboard(&mut db, 2).await.unwrap();
+    assert!(html.contains("<h1>Week 33/2024</h1>"));
+    assert!(
+        html.contains("<h3 title=\"&quot;quotes&quot; &amp; more\">&lt;b&gt;Bad&lt;/b&gt;</h3>")
+    );
+    assert!(html.contains("<tr><td>M403</td><td>Jonas</td><td>0.50</td><td>1.00</td></tr>"));
+    assert!(html.contains("<h2>History of the last 2 weeks</h2>"));
+    assert!(html.contains("<tr><td>31/2024</td><td>Olli</td><td>4.00</td></tr>"));
+    assert!(!html.contains("<b>"));
+}
+
+#[tokio::test]
+async fn test_render_calendar() {
+    let mut db = prepare_db().await;
+    let now = chrono::DateTime::from_timestamp(1723456789, 0).unwrap();
+    let ics = render_calendar(&mut db, Some("olli"), now, |t, w| {
+        format!("testing testing, {}, {}", t, w)
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        ics,
+        "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+PRODID:-//chore_planner//chore_planner//EN\r
+CALSCALE:GREGORIAN\r
+X-WR-CALNAME:Chores of Olli\r
+BEGIN:VEVENT\r
+UID:2848-2@chore_planner\r
+DTSTAMP:20240812T095949Z\r
+DTSTART;VALUE=DATE:20240729\r
+DTEND;VALUE=DATE:20240805\r
+SUMMARY:Spüldienst: Olli\r
+DESCRIPTION:Clean the kitchen.\\n\\nCan't do it? Send:\\ntesting testing\\, Oll\r
+ i\\, 31/2024\r
+TRANSP:TRANSPARENT\r
+END:VEVENT\r
+END:VCALENDAR\r
+"
+    );
+    let ics = render_calendar(&mut db, None, now, |t, w| {
+        format!("testing testing, {}, {}", t, w)
+    })
+    .await
+    .unwrap();
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 6);
+    assert!(render_calendar(&mut db, Some("nobody"), now, |t, w| {
+        format!("testing testing, {}, {}", t, w)
+    })
+    .await
+    .is_err());
+}
+
 #[tokio::test]
 async fn test_print_next_week_banner() {
     let mut db = prepare_db().await;
@@ -2897,9 +3721,16 @@ async fn test_print_next_week_banner() {
     db.update_plan(|t, w| format!("testing testing, {}, {}", t, w))
         .await
         .unwrap();
-    let out = db.print_next_week_banner().await.unwrap();
+    let out = db
+        .print_next_week_banner(
+            |t, w| format!("testing testing, {}, {}", t, w),
+            |c, w| format!("done, {}, {}", c, w),
+            |t, o, w| format!("swap, {}, {:?}, {}", t, o, w),
+        )
+        .await
+        .unwrap();
     assert_eq!(
-        out.mono_msg,
+        out.mono_msg(),
         r#"# Week 33/2024
 Hello smart people!
 We have another week and new jobs to go with it:
@@ -2914,15 +3745,505 @@ We have another week and new jobs to go with it:
 
 Have a very safe and productive week."#
     );
+    // Bob does both chores, so he gets a button for each but only one for swapping.
+    assert_eq!(
+        out.actions,
+        vec![
+            Action::new(
+                "Bob can't do the Spüldienst",
+                "testing testing, Bob, 33/2024"
+            ),
+            Action::new("Bob did the Spüldienst", "done, Spüldienst, 33/2024"),
+            Action::new(
+                "Bob can't do the Mülldienst",
+                "testing testing, Bob, 33/2024"
+            ),
+            Action::new("Bob did the Mülldienst", "done, Mülldienst, 33/2024"),
+            Action::new("Bob wants to swap", "swap, Bob, None, 33/2024"),
+        ]
+    );
 }
 
 #[tokio::test]
-async fn test_update_current_week() {
-    let mut db = prepare_db().await;
-    assert_eq!(db.get_week_internal().await, Week::new(33, 2024).unwrap());
-    db.set_week_internal(Week::new(35, 2024).unwrap()).await;
-    assert_eq!(db.get_week_internal().await, Week::new(35, 2024).unwrap());
-}
+async fn test_two_households() {
+    let mut expected_db = prepare_db().await;
+    let expected = expected_db.create_room("M500").await.unwrap();
+    let rating_options = Locale::En
+        .rating_options()
+        .iter()
+        .map(|r| r.to_string())
+        .collect::<Vec<_>>();
+    let mut households = vec![
+        Household {
+            name: "flat_a".to_string(),
+            db: prepare_db().await,
+        },
+        Household {
+            name: "flat_b".to_string(),
+            db: prepare_db().await,
+        },
+    ];
+    let mut bot = TestBot {
+        to_send_msgs: vec![].into_iter(),
+        expected_msgs: vec![Ok(expected)].into_iter(),
+        expected_polls: vec![
+            (
+                "How well did Alex do the Mülldienst on 32/2024?".to_string(),
+                rating_options.clone(),
+            ),
+            (
+                "How well did Jonas do the Spüldienst on 32/2024?".to_string(),
+                rating_options,
+            ),
+        ],
+        to_send_polls: vec![vec![], vec![]],
+        next_poll_id: 0,
+        non_anonymous_polls: true,
+        selected_chat: 0,
+        sent_msg_chats: vec![],
+    };
+    async fn has_room(household: &mut Household, room: &str) -> bool {
+        let rooms = household.db.get_rooms().await.unwrap();
+        rooms.iter().any(|r| r.room == room)
+    }
+
+    // A message is handled by the household of the chat it came from.
+    bot.select_chat(1);
+    handle_update(
+        &mut households,
+        &mut bot,
+        BotUpdate::Msg("@chore_planner_bot tenant create-room --name M500".to_string()),
+    )
+    .await;
+    assert_eq!(bot.sent_msg_chats, vec![1]);
+    assert!(!has_room(&mut households[0], "M500").await);
+    assert!(has_room(&mut households[1], "M500").await);
+
+    // An ApiCall chooses its household itself.
+    bot.select_chat(0);
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    let call = ApiCall {
+        household: 1,
+        request: ApiRequest::Command("tenant create-room --name M501".to_string()),
+        reply: sender,
+    };
+    handle_household_api_call(&mut households, &mut bot, call).await;
+    receiver.await.unwrap().unwrap();
+    assert_eq!(bot.selected_chat(), 0);
+    assert!(!has_room(&mut households[0], "M501").await);
+    assert!(has_room(&mut households[1], "M501").await);
+
+    // A vote is only stored by the household with the poll, no matter the selected chat.
+    bot.select_chat(1);
+    households[1]
+        .db
+        .create_rating_polls(&mut bot)
+        .await
+        .unwrap();
+    bot.select_chat(0);
+    handle_update(
+        &mut households,
+        &mut bot,
+        BotUpdate::PollAnswer {
+            vote_key: "1".to_string(),
+            voter_tags: vec!["@bob".to_string()],
+            option: Some(4),
+        },
+    )
+    .await;
+    for household in households.iter_mut() {
+        household.db.stop_rating_polls(&mut bot).await.unwrap();
+    }
+    let spüldienst_rating = |plan: Vec<ChorePlan>| {
+        plan.into_iter()
+            .find(|c| c.name == "Spüldienst")
+            .unwrap()
+            .plan
+            .into_iter()
+            .find(|l| l.week == Week::new(32, 2024).unwrap())
+            .unwrap()
+            .rating
+    };
+    let start_week = Some(Week::new(32, 2024).unwrap());
+    assert_eq!(
+        spüldienst_rating(households[0].db.get_plan(start_week).await.unwrap()),
+        Some(1.0)
+    );
+    // the two existing ratings of 1 and Bob's vote of 5
+    let rating = spüldienst_rating(households[1].db.get_plan(start_week).await.unwrap()).unwrap();
+    assert!((rating - 7.0 / 3.0).abs() < 1e-6);
+    assert!(bot.expected_msgs.next().is_none());
+}
+
+#[tokio::test]
+async fn test_done() {
+    let mut db = prepare_db().await;
+    let rating_options = Locale::En
+        .rating_options()
+        .iter()
+        .map(|r| r.to_string())
+        .collect::<Vec<_>>();
+    let mut bot = TestBot {
+        to_send_msgs: vec![].into_iter(),
+        expected_msgs: vec![].into_iter(),
+        expected_polls: vec![
+            (
+                "How well did Bob do the Spüldienst on 33/2024?".to_string(),
+                rating_options.clone(),
+            ),
+            (
+                "How well did Bob do the Mülldienst on 33/2024?".to_string(),
+                rating_options,
+            ),
+        ],
+        to_send_polls: vec![vec![("4 something".to_string(), 3)], vec![]],
+        next_poll_id: 0,
+        non_anonymous_polls: false,
+        selected_chat: 0,
+        sent_msg_chats: vec![],
+    };
+    db.weeks_to_plan = 1;
+    db.update_plan(|t, w| format!("testing testing, {}, {}", t, w))
+        .await
+        .unwrap();
+
+    let out = run_command_for_bot(
+        &mut db,
+        &mut bot,
+        "chore done --chore Spüldienst --week 33 --year 2024",
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        out.mono_msg(),
+        "# Spüldienst on 33/2024: Bob is done, please rate the chore"
+    );
+    assert_eq!(bot.next_poll_id, 1);
+    for (command, error) in [
+        (
+            "chore done --chore Spüldienst --week 33 --year 2024",
+            "the Spüldienst on 33/2024 is already being rated",
+        ),
+        (
+            "chore done --chore Mülldienst --week 34 --year 2024",
+            "only chores of this week 33/2024 can be marked as done",
+        ),
+        (
+            "chore done --chore Mülldienst --week 30 --year 2024",
+            "only chores of this week 33/2024 can be marked as done",
+        ),
+        (
+            "chore done --chore Kochdienst --week 33 --year 2024",
+            "the chore Kochdienst doesn't exist",
+        ),
+        (
+            "chore done --chore PigeonFeeder --week 33 --year 2024",
+            "nobody has been planned for the PigeonFeeder on 33/2024",
+        ),
+    ] {
+        assert_eq!(
+            run_command_for_bot(&mut db, &mut bot, command)
+                .await
+                .unwrap_err()
+                .to_string(),
+            error
+        );
+    }
+
+    // Only the Mülldienst still needs a poll and the early poll is stopped with it.
+    db.set_week_internal(Week::new(34, 2024).unwrap()).await;
+    db.create_rating_polls(&mut bot).await.unwrap();
+    assert_eq!(bot.next_poll_id, 2);
+    db.stop_rating_polls(&mut bot).await.unwrap();
+    let plan = db
+        .get_plan(Some(Week::new(33, 2024).unwrap()))
+        .await
+        .unwrap();
+    let spüldienst = plan.iter().find(|c| c.name == "Spüldienst").unwrap();
+    assert_eq!(spüldienst.plan[0].rating, Some(4.0));
+}
+
+#[tokio::test]
+async fn test_swap() {
+    let mut db = prepare_db().await;
+    db.weeks_to_plan = 5;
+    db.update_plan(|t, w| format!("testing testing, {}, {}", t, w))
+        .await
+        .unwrap();
+    db.replan("Thomas", Week::new(36, 2024).unwrap(), |t, w| {
+        format!("testing testing, {}, {}", t, w)
+    })
+    .await
+    .unwrap();
+    let fmt_swap_cmd = |t: &str, o: Option<&str>, w| format!("swap, {}, {:?}, {}", t, o, w);
+    let week = Week::new(37, 2024).unwrap();
+
+    // Till is exempt from the Mülldienst.
+    assert_eq!(
+        db.swap("Alex", Some("Till"), week, fmt_swap_cmd)
+            .await
+            .unwrap_err()
+            .to_string(),
+        "Alex and Till can't swap their chores on 37/2024"
+    );
+    // Bob has no chore that week and takes the Spüldienst.
+    let out = db
+        .swap("Till", Some("bob"), week, fmt_swap_cmd)
+        .await
+        .unwrap();
+    assert_eq!(
+        out.mono_msg(),
+        "# Till and Bob swapped their chores on 37/2024"
+    );
+    assert_eq!(out.tags, HashSet::from(["@bob".to_string()]));
+
+    let out = db.swap("Alex", None, week, fmt_swap_cmd).await.unwrap();
+    assert_eq!(out.mono_msg(), "# Who swaps chores with Alex on 37/2024?");
+    assert_eq!(
+        out.actions,
+        vec![Action::new(
+            "Swap with Bob (Spüldienst)",
+            "swap, Alex, Some(\"Bob\"), 37/2024"
+        )]
+    );
+    db.swap("Alex", Some("Bob"), week, fmt_swap_cmd)
+        .await
+        .unwrap();
+    let plan = db.get_plan(None).await.unwrap();
+    let worker = |chore: &str| {
+        plan.iter()
+            .find(|c| c.name == chore)
+            .unwrap()
+            .plan
+            .iter()
+            .find(|l| l.week == week)
+            .unwrap()
+            .tenant
+            .clone()
+    };
+    assert_eq!(worker("Spüldienst"), "Alex");
+    assert_eq!(worker("Mülldienst"), "Bob");
+
+    for (tenant, week, error) in [
+        ("Jonas", 37, "Jonas has no chore on 37/2024"),
+        (
+            "Jonas",
+            32,
+            "the chores on 32/2024 can't be swapped anymore",
+        ),
+        ("Olli", 34, "nobody can swap chores with Olli on 34/2024"),
+    ] {
+        assert_eq!(
+            db.swap(tenant, None, Week::new(week, 2024).unwrap(), fmt_swap_cmd)
+                .await
+                .unwrap_err()
+                .to_string(),
+            error
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_locale_de() {
+    let mut db = prepare_db().await;
+    db.set_locale(Locale::De).await.unwrap();
+    db.weeks_to_plan = 1;
+    let out = db
+        .update_plan(|t, w| format!("testing testing, {}, {}", t, w))
+        .await
+        .unwrap();
+    assert!(out.mono_msg().starts_with(
+        r#"# Spüldienst in 33/2024 (in 0 Wochen): Bob
+Bob, du wurdest für den Spüldienst in 33/2024 ausgewählt."#
+    ));
+    let out = db
+        .print_next_week_banner(
+            |t, w| format!("testing testing, {}, {}", t, w),
+            |c, w| format!("done, {}, {}", c, w),
+            |t, o, w| format!("swap, {}, {:?}, {}", t, o, w),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        out.mono_msg(),
+        r#"# Woche 33/2024
+Hallo ihr Schlauen!
+Eine neue Woche bringt neue Aufgaben mit sich:
+
++------------+-----------+
+|  Aufgabe   | Zuständig |
++------------+-----------+
+| Spüldienst |    Bob    |
++------------+-----------+
+| Mülldienst |    Bob    |
++------------+-----------+
+
+Habt eine sichere und produktive Woche."#
+    );
+    assert_eq!(
+        db.set_chore_limit("Nobody", Some(1))
+            .await
+            .unwrap_err()
+            .to_string(),
+        "den Bewohner Nobody gibt es nicht"
+    );
+    assert_eq!(
+        db.set_template(TemplateKind::BannerGreeting, "Hallo {tenant}")
+            .await
+            .unwrap_err()
+            .to_string(),
+        "die Vorlage banner-greeting kennt den Platzhalter {tenant} nicht, nutze einen von: {week}"
+    );
+    let out = db
+        .move_in("Thomas", &None, "M402", |t, w| format!("{} {}", t, w))
+        .await
+        .unwrap();
+    assert_eq!(out.mono_msg(), "Alex wohnt im Zimmer M402");
+    assert!(db
+        .list_settings()
+        .await
+        .unwrap()
+        .mono_msg()
+        .starts_with("# Einstellungen"));
+    assert!(db
+        .list_settings()
+        .await
+        .unwrap()
+        .mono_msg()
+        .contains("keine"));
+    assert!(db
+        .list_tenants()
+        .await
+        .unwrap()
+        .mono_msg()
+        .contains("Zimmer"));
+    let mut bot = TestBot {
+        to_send_msgs: vec![].into_iter(),
+        expected_msgs: vec![].into_iter(),
+        expected_polls: vec![],
+        to_send_polls: vec![],
+        next_poll_id: 0,
+        non_anonymous_polls: false,
+        selected_chat: 0,
+        sent_msg_chats: vec![],
+    };
+    let help = run_command_for_bot(&mut db, &mut bot, "tenant -h")
+        .await
+        .unwrap_err()
+        .to_string()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    assert!(help.starts_with("Bewohner verwalten Verwendung: hihi_im_a_test tenant [BEFEHL]"));
+    assert!(help.contains("Befehle: list aktuelle Bewohner auflisten"));
+    assert!(help.contains("Optionen: -h, --help Hilfe anzeigen"));
+
+    // Polls created before the locale changed are still understood.
+    assert_eq!(Locale::parse_rating("4  👍  Well done.").unwrap(), 4);
+    assert_eq!(Locale::parse_rating("2  👎  Kaum bemerkbar.").unwrap(), 2);
+    assert_eq!(Locale::parse_rating("5 something else").unwrap(), 5);
+    assert!(Locale::parse_rating("Perfect!").is_err());
+}
+
+#[tokio::test]
+async fn test_templates() {
+    let mut db = prepare_db().await;
+    assert_eq!(
+        db.set_template(TemplateKind::BannerGreeting, "Hi {tenant}")
+            .await
+            .unwrap_err()
+            .to_string(),
+        "the banner-greeting template doesn't know the placeholder {tenant}, use one of: {week}"
+    );
+    assert!(db
+        .set_template(TemplateKind::Assignment, "{tenant, do it")
+        .await
+        .is_err());
+    db.set_template(
+        TemplateKind::Assignment,
+        "{tenant} does the {chore} on {week} ({score}, {probability}): {replan_command}",
+    )
+    .await
+    .unwrap();
+    db.set_template(TemplateKind::BannerGreeting, "Week {week} is here:")
+        .await
+        .unwrap();
+
+    db.weeks_to_plan = 1;
+    let out = db
+        .update_plan(|t, w| format!("testing testing, {}, {}", t, w))
+        .await
+        .unwrap();
+    let Block::Paragraph { inlines } = &out.blocks[1] else {
+        panic!("expected a paragraph, got {:?}", out.blocks[1]);
+    };
+    assert_eq!(
+        inlines,
+        &vec![
+            Inline::Mention {
+                name: "Bob".to_string(),
+                tag: "@bob".to_string()
+            },
+            Inline::Text(" does the Spüldienst on 33/2024 (-0.75, 26%): ".to_string()),
+            Inline::Code("testing testing, Bob, 33/2024".to_string()),
+        ]
+    );
+    let out = db
+        .print_next_week_banner(
+            |t, w| format!("testing testing, {}, {}", t, w),
+            |c, w| format!("done, {}, {}", c, w),
+            |t, o, w| format!("swap, {}, {:?}, {}", t, o, w),
+        )
+        .await
+        .unwrap();
+    assert!(out
+        .mono_msg()
+        .starts_with("# Week 33/2024\nWeek 33/2024 is here:\n"));
+    assert!(out
+        .mono_msg()
+        .ends_with("\nHave a very safe and productive week."));
+
+    db.reset_template(TemplateKind::BannerGreeting)
+        .await
+        .unwrap();
+    let out = db
+        .print_next_week_banner(
+            |t, w| format!("testing testing, {}, {}", t, w),
+            |c, w| format!("done, {}, {}", c, w),
+            |t, o, w| format!("swap, {}, {:?}, {}", t, o, w),
+        )
+        .await
+        .unwrap();
+    assert!(out
+        .mono_msg()
+        .starts_with("# Week 33/2024\nHello smart people!\n"));
+}
+
+#[tokio::test]
+async fn test_invalid_template() {
+    let mut db = prepare_db().await;
+    // a template edited by hand is replaced by the default instead of failing the planning
+    sqlx::query("REPLACE INTO MessageTemplate VALUES ('assignment', '{nonsense}');")
+        .execute(&mut db.con)
+        .await
+        .unwrap();
+    db.weeks_to_plan = 1;
+    let out = db
+        .update_plan(|t, w| format!("testing testing, {}, {}", t, w))
+        .await
+        .unwrap();
+    assert!(out
+        .mono_msg()
+        .contains("Bob, you have been chosen for the Spüldienst on 33/2024."));
+}
+
+#[tokio::test]
+async fn test_update_current_week() {
+    let mut db = prepare_db().await;
+    assert_eq!(db.get_week_internal().await, Week::new(33, 2024).unwrap());
+    db.set_week_internal(Week::new(35, 2024).unwrap()).await;
+    assert_eq!(db.get_week_internal().await, Week::new(35, 2024).unwrap());
+}
 
 async fn prepare_db() -> Db {
     let mut db = Db::new(
@@ -2931,6 +4252,7 @@ async fn prepare_db() -> Db {
         0,
         0.8,
         false,
+        Locale::En,
         0x0DDB1A5E5BAD5EEDu64,
         false,
     )
@@ -3069,3 +4391,942 @@ INSERT INTO Rating VALUES
     }
     db
 }
+
+/// Get the scores by tenant and chore, only the ones of tenant and chore when they are Some.
+async fn get_scores(
+    db: &mut Db,
+    tenant: Option<&str>,
+    chore: Option<&str>,
+) -> BTreeMap<(String, String), f64> {
+    db.get_export()
+        .await
+        .unwrap()
+        .scores
+        .into_iter()
+        .filter(|s| tenant.is_none_or(|t| s.tenant == t) && chore.is_none_or(|c| s.chore == c))
+        .map(|s| ((s.tenant, s.chore), s.score))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_export() {
+    let mut db = prepare_db().await;
+    let export = db.get_export().await.unwrap();
+    assert_eq!(export.chore_logs.len(), 6);
+    assert_eq!(
+        export.chore_logs[0],
+        ExportedChoreLog {
+            week: Week::from_db(2847),
+            chore: "Spüldienst".to_string(),
+            worker: "Till".to_string(),
+            completed: false,
+        }
+    );
+    assert_eq!(
+        export.ratings[1],
+        ExportedRating {
+            week: Week::from_db(2847),
+            chore: "Spüldienst".to_string(),
+            rating: 10,
+        }
+    );
+    assert_eq!(
+        export.exemptions[0],
+        ExportedExemption {
+            tenant: "Jan".to_string(),
+            reason: "Getränkeminister".to_string(),
+            start_week: Week::from_db(2714),
+            end_week: Some(Week::from_db(2727)),
+        }
+    );
+    assert!(!export.lives_in.is_empty());
+    assert!(!export.scores.is_empty());
+
+    let csv = db.export_csv().await.unwrap();
+    assert_eq!(
+        csv.attachments
+            .iter()
+            .map(|a| a.filename.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "chore_logs.csv",
+            "ratings.csv",
+            "lives_in.csv",
+            "exemptions.csv",
+            "scores.csv"
+        ]
+    );
+    assert!(csv.attachments[0]
+        .content
+        .starts_with("week,chore,worker,completed\r\n"));
+    assert_eq!(
+        csv.attachments[0].content.lines().count(),
+        export.chore_logs.len() + 1
+    );
+
+    let json = db.export_json().await.unwrap();
+    assert_eq!(json.attachments.len(), 1);
+    let parsed: serde_json::Value = serde_json::from_str(&json.attachments[0].content).unwrap();
+    assert_eq!(
+        parsed["chore_logs"].as_array().unwrap().len(),
+        export.chore_logs.len()
+    );
+}
+
+#[tokio::test]
+async fn test_long_msg_as_file() {
+    let mut msg = ReplyMsg::from_blocks(vec![
+        Block::heading(1, "Report"),
+        Block::code(&"x".repeat(100)),
+    ]);
+    msg.tags.insert("@bob".to_string());
+    msg.push_action(Action::new("Show plan", "@chore_planner_bot chore list"));
+
+    assert_eq!(
+        msg.clone().into_file_when_longer_than(200, "reply.txt"),
+        msg
+    );
+
+    let file_msg = msg.clone().into_file_when_longer_than(50, "reply.txt");
+    assert_eq!(file_msg.blocks, vec![Block::heading(1, "Report")]);
+    assert_eq!(file_msg.actions, msg.actions);
+    assert_eq!(file_msg.tags, msg.tags);
+    assert_eq!(file_msg.attachments.len(), 1);
+    assert_eq!(file_msg.attachments[0].filename, "reply.txt");
+    assert_eq!(file_msg.attachments[0].content, msg.mono_msg());
+    assert_eq!(file_msg.attachments[0].caption.as_deref(), Some("Report"));
+
+    // Without actions there is nothing to send besides the file.
+    msg.actions.clear();
+    let file_msg = msg.clone().into_file_when_longer_than(50, "reply.txt");
+    assert!(file_msg.blocks.is_empty());
+    assert_eq!(file_msg.attachments.len(), 1);
+}
+
+#[tokio::test]
+async fn test_import_history() {
+    let mut db = prepare_db().await;
+
+    // The second ChoreLog is in the current week, so the entire import is rolled back.
+    let err = db
+        .import_history(
+            Some("tenant,room,move_in_week,move_out_week,chat_tag\r\nanna,M416,10/2024,,@anna\r\n"),
+            Some("week,chore,worker\n20/2024,Spüldienst,Anna\n33/2024,Spüldienst,Anna\n"),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        format!("{:#}", err),
+        "nothing has been imported: failed to import ChoreLog 2: only weeks before the current week 33/2024 can be imported, not 33/2024"
+    );
+    let export = db.get_export().await.unwrap();
+    assert!(!export.lives_in.iter().any(|l| l.tenant == "Anna"));
+    assert_eq!(export.chore_logs.len(), 6);
+
+    assert_eq!(
+        format!(
+            "{:#}",
+            db.import_history(None, Some("week,chore,worker\n1/2000,Mülldienst,Bob\n"))
+                .await
+                .unwrap_err()
+        ),
+        "nothing has been imported: failed to import ChoreLog 1: Bob didn't live in the flat on 1/2000"
+    );
+    assert!(db
+        .import_history(None, Some("week,chore,worker\n20/2024,Kochdienst,Bob\n"))
+        .await
+        .is_err());
+
+    // Move ins may neither overlap with the existing ones nor with the ones imported before.
+    for (lives_in, expected) in [
+        (
+            "anna,M416,10/2024,5/2024",
+            "failed to import move in 1: the move out week 5/2024 isn't after the move in week 10/2024",
+        ),
+        (
+            "anna,M403,25/2024,",
+            "failed to import move in 1: Jonas lived in M403 at that time",
+        ),
+        (
+            "bob,M416,10/2024,12/2024",
+            "failed to import move in 1: Bob already lived in M409 at that time",
+        ),
+        (
+            "anna,M416,10/2024,\nanna,M416,12/2024,",
+            "failed to import move in 2: Anna already lived in M416 at that time",
+        ),
+        (
+            "anna,M416,10/2024,20/2024\nanna,M417,15/2024,",
+            "failed to import move in 2: Anna already lived in M416 at that time",
+        ),
+        (
+            "anna,M416,10/2024,20/2024\nchris,M416,19/2024,30/2024",
+            "failed to import move in 2: Anna lived in M416 at that time",
+        ),
+    ] {
+        let err = db
+            .import_history(
+                Some(&format!("tenant,room,move_in_week,move_out_week\n{}\n", lives_in)),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            format!("nothing has been imported: {}", expected)
+        );
+    }
+    // Moving out and back into the same room in the same week is fine.
+    assert_eq!(
+        db.import_history(
+            Some("tenant,room,move_in_week,move_out_week\nanna,M416,1/2024,5/2024\nanna,M416,5/2024,10/2024\n"),
+            None,
+        )
+        .await
+        .unwrap()
+        .mono_msg(),
+        "Imported 2 move ins and 0 ChoreLogs."
+    );
+
+    assert_eq!(
+        db.import_history(
+            Some("tenant,room,move_in_week,move_out_week,chat_tag\r\nanna,M416,10/2024,,@anna\r\n"),
+            None,
+        )
+        .await
+        .unwrap()
+        .mono_msg(),
+        "Imported 1 move ins and 0 ChoreLogs."
+    );
+    let export = db.get_export().await.unwrap();
+    assert_eq!(
+        export
+            .lives_in
+            .iter()
+            .find(|l| l.tenant == "Anna")
+            .unwrap()
+            .room,
+        "M416"
+    );
+    let score_anna: f64 = get_scores(&mut db, Some("Anna"), None).await.values().sum();
+    let score_bob: f64 = get_scores(&mut db, Some("Bob"), None).await.values().sum();
+
+    // the format of the export works as well
+    assert_eq!(
+        db.import_history(
+            None,
+            Some("week,chore,worker,completed\n20/2024,Spüldienst,Anna,false\n"),
+        )
+        .await
+        .unwrap()
+        .mono_msg(),
+        "Imported 0 move ins and 1 ChoreLogs."
+    );
+    assert!(
+        get_scores(&mut db, Some("Anna"), None)
+            .await
+            .values()
+            .sum::<f64>()
+            > score_anna
+    );
+    assert!(
+        get_scores(&mut db, Some("Bob"), None)
+            .await
+            .values()
+            .sum::<f64>()
+            < score_bob
+    );
+}
+
+#[tokio::test]
+async fn test_import_history_completed() {
+    let mut db = Db::new(
+        "sqlite::memory:",
+        Week::new(33, 2024).unwrap(),
+        0,
+        0.8,
+        false,
+        Locale::En,
+        0x0DDB1A5E5BAD5EEDu64,
+        false,
+    )
+    .await
+    .unwrap();
+    db.create_chore("Spüldienst", "Clean the kitchen.", |t, w| {
+        format!("{} {}", t, w)
+    })
+    .await
+    .unwrap();
+    db.import_history(
+        Some(
+            "tenant,room,move_in_week
+alex,M401,1/2024
+",
+        ),
+        Some(
+            "week,chore,worker
+32/2024,Spüldienst,Alex
+",
+        ),
+    )
+    .await
+    .unwrap();
+    let export = db.get_export().await.unwrap();
+    assert_eq!(
+        export.chore_logs,
+        vec![ExportedChoreLog {
+            week: Week::new(32, 2024).unwrap(),
+            chore: "Spüldienst".to_string(),
+            worker: "Alex".to_string(),
+            completed: true,
+        }]
+    );
+
+    // The imported last week isn't rated.
+    let mut bot = TestBot {
+        to_send_msgs: vec![].into_iter(),
+        expected_msgs: vec![].into_iter(),
+        expected_polls: vec![],
+        to_send_polls: vec![],
+        next_poll_id: 0,
+        non_anonymous_polls: false,
+        selected_chat: 0,
+        sent_msg_chats: vec![],
+    };
+    db.create_rating_polls(&mut bot).await.unwrap();
+    assert_eq!(bot.next_poll_id, 0);
+}
+
+#[tokio::test]
+async fn test_score_adjustment() {
+    let mut db = prepare_db().await;
+    let before = get_scores(&mut db, Some("Bob"), None).await;
+
+    assert_eq!(
+        db.adjust_score("bob", Some("Kochdienst"), 1.0, "party")
+            .await
+            .unwrap_err()
+            .to_string(),
+        "the chore Kochdienst doesn't exist"
+    );
+    assert!(db.adjust_score("bob", None, 0.0, "nothing").await.is_err());
+    assert!(db.adjust_score("bob", None, 1.0, " ").await.is_err());
+
+    db.adjust_score("bob", None, 2.0, "cleaned up after the party")
+        .await
+        .unwrap();
+    db.adjust_score("bob", Some("Spüldienst"), -0.5, "broke a plate")
+        .await
+        .unwrap();
+    let after = get_scores(&mut db, Some("Bob"), None).await;
+    // The global adjustment is split among both active chores.
+    for (((_, chore), score_before), score_after) in before.iter().zip(after.values()) {
+        let expected = match chore.as_str() {
+            "Spüldienst" => score_before + 0.5,
+            "Mülldienst" => score_before + 1.0,
+            _ => *score_before,
+        };
+        assert!((score_after - expected).abs() < 1e-9, "{}", chore);
+    }
+
+    let report = db.get_report(Week::new(30, 2024).unwrap()).await.unwrap();
+    assert_eq!(
+        serde_json::to_string(&report.score_adjustments).unwrap(),
+        r#"[{"week":{"week":33,"year":2024},"tenant":"Bob","chore":null,"amount":2.0,"reason":"cleaned up after the party"},{"week":{"week":33,"year":2024},"tenant":"Bob","chore":"Spüldienst","amount":-0.5,"reason":"broke a plate"}]"#
+    );
+    assert!(db
+        .get_report(Week::new(34, 2024).unwrap())
+        .await
+        .unwrap()
+        .score_adjustments
+        .is_empty());
+    assert!(db
+        .print_report(Week::new(30, 2024).unwrap())
+        .await
+        .unwrap()
+        .mono_msg()
+        .contains("# Score Adjustments"));
+}
+
+#[tokio::test]
+async fn test_score_decay() {
+    let mut db = prepare_db().await;
+    db.set_week_internal(Week::new(33, 2024).unwrap()).await;
+    let expect = |scores: BTreeMap<(String, String), f64>, expected: &[(&str, f64)]| {
+        for ((tenant, _), score) in scores {
+            let expected = expected
+                .iter()
+                .find(|(t, _)| *t == tenant)
+                .map(|(_, s)| *s)
+                .unwrap_or_default();
+            assert!((score - expected).abs() < 1e-9, "{}: {}", tenant, score);
+        }
+    };
+
+    let none = [
+        ("Till", 0.5),
+        ("Olli", 0.5),
+        ("Jonas", 0.5),
+        ("Alex", -0.75),
+        ("Bob", -0.75),
+    ];
+    expect(get_scores(&mut db, None, Some("Spüldienst")).await, &none);
+
+    // Only Jonas' Spüldienst in week 32 is within the window.
+    db.set_score_decay(ScoreDecay::Window { weeks: 2 })
+        .await
+        .unwrap();
+    expect(
+        get_scores(&mut db, None, Some("Spüldienst")).await,
+        &[
+            ("Till", -0.25),
+            ("Olli", -0.25),
+            ("Jonas", 1.0),
+            ("Alex", -0.25),
+            ("Bob", -0.25),
+        ],
+    );
+
+    // The weeks 30, 31 and 32 have the weights 1/8, 1/4 and 1/2.
+    db.set_score_decay(ScoreDecay::HalfLife { weeks: 1 })
+        .await
+        .unwrap();
+    expect(
+        get_scores(&mut db, None, Some("Spüldienst")).await,
+        &[
+            ("Till", -0.0625),
+            ("Olli", 0.09375),
+            ("Jonas", 0.40625),
+            ("Alex", -0.21875),
+            ("Bob", -0.21875),
+        ],
+    );
+    assert!(db
+        .list_settings()
+        .await
+        .unwrap()
+        .mono_msg()
+        .contains("half-life 1 weeks"));
+
+    assert!(db
+        .set_score_decay(ScoreDecay::Window { weeks: 0 })
+        .await
+        .is_err());
+    db.set_score_decay(ScoreDecay::None).await.unwrap();
+    expect(get_scores(&mut db, None, Some("Spüldienst")).await, &none);
+}
+
+#[tokio::test]
+async fn test_score_decay_stores_week() {
+    let mut db = prepare_db().await;
+    // The ScoreWeight VIEW doesn't know the fallback_week, so it needs to be stored.
+    db.set_score_decay(ScoreDecay::Window { weeks: 2 })
+        .await
+        .unwrap();
+    assert!(db.get_key_values().await.unwrap().contains(&(
+        "current_week".to_string(),
+        Some(db.fallback_week.db_week().to_string())
+    )));
+    assert_eq!(db.get_week_internal().await, db.fallback_week);
+    // Only Jonas' Spüldienst in week 32 is within the window.
+    assert_eq!(
+        get_scores(&mut db, Some("Jonas"), Some("Spüldienst")).await
+            [&("Jonas".to_string(), "Spüldienst".to_string())],
+        1.0
+    );
+}
+
+#[tokio::test]
+async fn test_score_decay_old_start_score() {
+    let mut db = Db::new(
+        "sqlite::memory:",
+        Week::new(33, 2024).unwrap(),
+        0,
+        0.8,
+        false,
+        Locale::En,
+        0x0DDB1A5E5BAD5EEDu64,
+        false,
+    )
+    .await
+    .unwrap();
+    for (tenant, room) in [("Alex", "M401"), ("Bob", "M402")] {
+        db.create_room(room).await.unwrap();
+        db.move_in(tenant, &None, room, |t, w| format!("{} {}", t, w))
+            .await
+            .unwrap();
+    }
+    db.create_chore("Spüldienst", "Clean the kitchen.", |t, w| {
+        format!("{} {}", t, w)
+    })
+    .await
+    .unwrap();
+    // Alex' StartScore in week 29 is older than Bob's only ChoreLog in week 32.
+    sqlx::query(
+        r#"
+INSERT INTO ChoreLog
+SELECT Chore.id, ?1, Tenant.id, 0, NULL
+FROM Chore, Tenant
+    WHERE Chore.name = 'Spüldienst' AND Tenant.name = 'Bob';
+"#,
+    )
+    .bind(Week::new(32, 2024).unwrap().db_week())
+    .execute(&mut db.con)
+    .await
+    .unwrap();
+    sqlx::query(
+        r#"
+REPLACE INTO StartScore
+SELECT Tenant.id, Chore.id, 1.0, ?1, NULL
+FROM Chore, Tenant
+    WHERE Chore.name = 'Spüldienst' AND Tenant.name = 'Alex';
+"#,
+    )
+    .bind(Week::new(29, 2024).unwrap().db_week())
+    .execute(&mut db.con)
+    .await
+    .unwrap();
+
+    // The week 29 has the weight 1/16. Alex only moved in in week 33, so the ChoreLog doesn't
+    // concern them.
+    db.set_score_decay(ScoreDecay::HalfLife { weeks: 1 })
+        .await
+        .unwrap();
+    let score = get_scores(&mut db, Some("Alex"), Some("Spüldienst")).await
+        [&("Alex".to_string(), "Spüldienst".to_string())];
+    assert!((score - 1.0 / 16.0).abs() < 1e-9, "{}", score);
+}
+
+#[tokio::test]
+async fn test_start_score() {
+    let mut db = prepare_db().await;
+    // compare the scores of one tenant by chore
+    let assert_scores = |actual: BTreeMap<(String, String), f64>, expected: Vec<(String, f64)>| {
+        assert_eq!(actual.len(), expected.len());
+        for ((_, chore), actual) in actual {
+            let (_, expected) = expected.iter().find(|(c, _)| *c == chore).unwrap();
+            assert!((actual - expected).abs() < 1e-9, "{}", chore);
+        }
+    };
+    let zero = ["Spüldienst", "Mülldienst", "PigeonFeeder"]
+        .map(|chore| (chore.to_string(), 0.0))
+        .to_vec();
+
+    // new tenants start at 0 by default
+    db.move_in("Neo", &None, "M412", |t, w| format!("{} {}", t, w))
+        .await
+        .unwrap();
+    assert_scores(get_scores(&mut db, Some("Neo"), None).await, zero.clone());
+    db.move_out("Neo", |t, w| format!("{} {}", t, w))
+        .await
+        .unwrap();
+
+    // the mean only includes the tenants living here who aren't exempt from the chore
+    db.set_start_score_policy(StartScorePolicy::Mean)
+        .await
+        .unwrap();
+    let all_scores = get_scores(&mut db, None, None).await;
+    let mean = |chore: &str, tenants: &[&str]| -> f64 {
+        tenants
+            .iter()
+            .map(|t| all_scores[&(t.to_string(), chore.to_string())])
+            .sum::<f64>()
+            / tenants.len() as f64
+    };
+    let expected = vec![
+        (
+            "Spüldienst".to_string(),
+            mean(
+                "Spüldienst",
+                &["Jonas", "Olli", "Till", "Alex", "Bob", "Thomas"],
+            ),
+        ),
+        (
+            "Mülldienst".to_string(),
+            mean("Mülldienst", &["Jonas", "Alex", "Bob", "Thomas"]),
+        ),
+        ("PigeonFeeder".to_string(), 0.0),
+    ];
+    db.move_in("Neo", &None, "M412", |t, w| format!("{} {}", t, w))
+        .await
+        .unwrap();
+    assert_scores(get_scores(&mut db, Some("Neo"), None).await, expected);
+
+    // moving out the week they moved in undoes the StartScore
+    db.move_out("Neo", |t, w| format!("{} {}", t, w))
+        .await
+        .unwrap();
+    assert_scores(get_scores(&mut db, Some("Neo"), None).await, zero.clone());
+
+    // inherit the score of Chris, who lived in M401 before
+    db.set_start_score_policy(StartScorePolicy::Room)
+        .await
+        .unwrap();
+    db.adjust_score("Chris", None, 2.0, "left the room spotless")
+        .await
+        .unwrap();
+    let chris = get_scores(&mut db, Some("Chris"), None)
+        .await
+        .into_iter()
+        .map(|((_, chore), score)| (chore, score))
+        .collect::<Vec<_>>();
+    db.move_in("Mia", &None, "M401", |t, w| format!("{} {}", t, w))
+        .await
+        .unwrap();
+    assert_scores(get_scores(&mut db, Some("Mia"), None).await, chris.clone());
+
+    // Chris' score has been passed on, so it doesn't count twice when they move back in
+    let zero_chris = chris
+        .iter()
+        .map(|(chore, _)| (chore.clone(), 0.0))
+        .collect::<Vec<_>>();
+    db.move_in("Chris", &None, "M405", |t, w| format!("{} {}", t, w))
+        .await
+        .unwrap();
+    assert_scores(get_scores(&mut db, Some("Chris"), None).await, zero_chris);
+
+    // undoing Mia's move in gives Chris their score back
+    db.move_out("Mia", |t, w| format!("{} {}", t, w))
+        .await
+        .unwrap();
+    assert_scores(get_scores(&mut db, Some("Chris"), None).await, chris);
+
+    // Chris still lives here, so their score isn't passed on to the next tenant of M401
+    db.move_in("Nina", &None, "M401", |t, w| format!("{} {}", t, w))
+        .await
+        .unwrap();
+    assert_scores(get_scores(&mut db, Some("Nina"), None).await, zero);
+
+    assert!(db
+        .list_settings()
+        .await
+        .unwrap()
+        .mono_msg()
+        .contains("room"));
+}
+
+#[tokio::test]
+async fn test_chore_rating_rule_partial() {
+    let mut db = prepare_db().await;
+    let before = get_scores(&mut db, None, Some("Spüldienst")).await;
+
+    assert_eq!(
+        db.set_chore_rating_rule("Kochdienst", None)
+            .await
+            .unwrap_err()
+            .to_string(),
+        "the chore Kochdienst doesn't exist"
+    );
+    let partial = |min_rating: f64, score_factor: f64| {
+        Some(ChoreRatingRule {
+            min_rating,
+            action: PoorRatingAction::Partial { score_factor },
+        })
+    };
+    assert!(db
+        .set_chore_rating_rule("Spüldienst", partial(6.0, 0.5))
+        .await
+        .is_err());
+    assert!(db
+        .set_chore_rating_rule("Spüldienst", partial(3.0, 1.0))
+        .await
+        .is_err());
+
+    // The rule only affects the ChoreLogs since it has been set, so set it in week 2847.
+    // 2847 is rated 7.33 on average, 2848 4 and 2849 1.
+    db.set_week_internal(Week::from_db(2847)).await;
+    db.set_chore_rating_rule("Spüldienst", partial(5.0, 0.0))
+        .await
+        .unwrap();
+    db.set_week_internal(Week::from_db(2850)).await;
+    let only_2847 = get_scores(&mut db, None, Some("Spüldienst")).await;
+    assert_ne!(only_2847, before);
+
+    db.set_week_internal(Week::from_db(2847)).await;
+    let out = db
+        .set_chore_rating_rule("Spüldienst", partial(5.0, 0.5))
+        .await
+        .unwrap();
+    assert!(out.mono_msg().contains("# Rating Rules"));
+    assert!(out.mono_msg().contains("partial 50%"));
+    db.set_week_internal(Week::from_db(2850)).await;
+    let half = get_scores(&mut db, None, Some("Spüldienst")).await;
+    for ((before, only_2847), half) in before.values().zip(only_2847.values()).zip(half.values()) {
+        assert!((half - (only_2847 + (before - only_2847) * 0.5)).abs() < 1e-9);
+    }
+
+    db.set_chore_rating_rule("Spüldienst", None).await.unwrap();
+    assert_eq!(get_scores(&mut db, None, Some("Spüldienst")).await, before);
+    assert!(db.set_chore_rating_rule("Spüldienst", None).await.is_err());
+}
+
+#[tokio::test]
+async fn test_chore_rating_rule_redo() {
+    let mut db = prepare_db().await;
+    let mut bot = TestBot {
+        to_send_msgs: vec![].into_iter(),
+        expected_msgs: vec![].into_iter(),
+        expected_polls: vec![
+            (
+                "How well did Bob do the Spüldienst on 33/2024?".to_string(),
+                Locale::En
+                    .rating_options()
+                    .iter()
+                    .map(|r| r.to_string())
+                    .collect(),
+            ),
+            (
+                "How well did Bob do the Mülldienst on 33/2024?".to_string(),
+                Locale::En
+                    .rating_options()
+                    .iter()
+                    .map(|r| r.to_string())
+                    .collect(),
+            ),
+        ],
+        to_send_polls: vec![
+            vec![("1 something".to_string(), 7)],
+            vec![("4 something".to_string(), 2)],
+        ],
+        next_poll_id: 0,
+        non_anonymous_polls: false,
+        selected_chat: 0,
+        sent_msg_chats: vec![],
+    };
+    db.set_chore_rating_rule(
+        "Spüldienst",
+        Some(ChoreRatingRule {
+            min_rating: 3.0,
+            action: PoorRatingAction::Redo,
+        }),
+    )
+    .await
+    .unwrap();
+    db.set_chore_rating_rule(
+        "Mülldienst",
+        Some(ChoreRatingRule {
+            min_rating: 3.0,
+            action: PoorRatingAction::Redo,
+        }),
+    )
+    .await
+    .unwrap();
+    db.weeks_to_plan = 1;
+    db.update_plan(|t, w| format!("testing testing, {}, {}", t, w))
+        .await
+        .unwrap();
+
+    db.set_week_internal(Week::new(34, 2024).unwrap()).await;
+    db.create_rating_polls(&mut bot).await.unwrap();
+    let out = db.stop_rating_polls(&mut bot).await.unwrap();
+    // only the Spüldienst has been rated poorly
+    assert_eq!(
+        out.mono_msg(),
+        "# Spüldienst on 34/2024: Bob has to redo the poorly rated chore"
+    );
+    assert!(out.tags.contains("@bob"));
+
+    let plan = db.get_plan(None).await.unwrap();
+    let spüldienst = plan.iter().find(|c| c.name == "Spüldienst").unwrap();
+    assert_eq!(spüldienst.plan.len(), 1);
+    assert_eq!(spüldienst.plan[0].tenant, "Bob");
+    assert!(plan
+        .iter()
+        .find(|c| c.name == "Mülldienst")
+        .unwrap()
+        .plan
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_chore_rating_rule_redo_displaces_planned_tenant() {
+    let mut db = prepare_db().await;
+    let mut bot = TestBot {
+        to_send_msgs: vec![].into_iter(),
+        expected_msgs: vec![].into_iter(),
+        expected_polls: vec![
+            (
+                "How well did Bob do the Spüldienst on 33/2024?".to_string(),
+                Locale::En
+                    .rating_options()
+                    .iter()
+                    .map(|r| r.to_string())
+                    .collect(),
+            ),
+            (
+                "How well did Bob do the Mülldienst on 33/2024?".to_string(),
+                Locale::En
+                    .rating_options()
+                    .iter()
+                    .map(|r| r.to_string())
+                    .collect(),
+            ),
+        ],
+        to_send_polls: vec![
+            vec![("1 something".to_string(), 7)],
+            vec![("4 something".to_string(), 2)],
+        ],
+        next_poll_id: 0,
+        non_anonymous_polls: false,
+        selected_chat: 0,
+        sent_msg_chats: vec![],
+    };
+    db.set_chore_rating_rule(
+        "Spüldienst",
+        Some(ChoreRatingRule {
+            min_rating: 3.0,
+            action: PoorRatingAction::Redo,
+        }),
+    )
+    .await
+    .unwrap();
+    db.weeks_to_plan = 1;
+    db.update_plan(|t, w| format!("testing testing, {}, {}", t, w))
+        .await
+        .unwrap();
+    // Jonas has already been planned for the week of the redo
+    sqlx::query(
+        r#"
+INSERT INTO ChoreLog VALUES
+    ((SELECT id FROM Chore WHERE name = 'Spüldienst'), 2851, (SELECT id FROM Tenant WHERE name = 'Jonas'), 0, NULL);
+"#,
+    )
+    .execute(&mut db.con)
+    .await
+    .unwrap();
+
+    db.set_week_internal(Week::new(34, 2024).unwrap()).await;
+    db.create_rating_polls(&mut bot).await.unwrap();
+    let out = db.stop_rating_polls(&mut bot).await.unwrap();
+    assert_eq!(
+        out.mono_msg(),
+        "# Spüldienst on 34/2024: Bob has to redo the poorly rated chore\n\
+         # Spüldienst on 34/2024: Jonas doesn't have to do the chore anymore"
+    );
+    assert!(out.tags.contains("@bob"));
+    assert!(out.tags.contains("@jonas"));
+
+    let plan = db.get_plan(None).await.unwrap();
+    let spüldienst = plan.iter().find(|c| c.name == "Spüldienst").unwrap();
+    assert_eq!(spüldienst.plan.len(), 1);
+    assert_eq!(spüldienst.plan[0].tenant, "Bob");
+}
+
+#[tokio::test]
+async fn test_stats() {
+    let mut db = prepare_db().await;
+    assert!(db
+        .get_stats(
+            Week::from_db(2850),
+            Some(Week::from_db(2849)),
+            StatsSort::Name
+        )
+        .await
+        .is_err());
+
+    // until last week, i.e., 2849
+    let stats = db
+        .get_stats(Week::from_db(2847), None, StatsSort::Name)
+        .await
+        .unwrap();
+    assert_eq!(stats.end_week, Week::from_db(2849));
+    // Chris moved out and Thomas moved in outside of the weeks.
+    assert_eq!(
+        stats
+            .tenants
+            .iter()
+            .map(|t| (t.tenant.as_str(), t.done.values().sum::<u32>()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("Alex", 1),
+            ("Bob", 1),
+            ("Jonas", 2),
+            ("Olli", 1),
+            ("Till", 1)
+        ]
+    );
+    let jonas = &stats.tenants[2];
+    assert_eq!(
+        jonas.done,
+        BTreeMap::from([("Mülldienst".to_string(), 1), ("Spüldienst".to_string(), 1)])
+    );
+    assert_eq!(jonas.rating, Some(1.0));
+    assert_eq!(stats.tenants[0].rating, None);
+    assert_eq!(stats.tenants[3].exemptions, vec!["Bestandsminister"]);
+    assert!(stats.tenants[0].exemptions.is_empty());
+
+    // The ChoreLogs are rated 7.33, 4 and 1, 2847 and 2848 are the first half.
+    let spüldienst = &stats.chores[1];
+    assert_eq!(spüldienst.chore, "Spüldienst");
+    assert_eq!(spüldienst.done, 3);
+    assert!((spüldienst.rating.unwrap() - 37.0 / 9.0).abs() < 1e-9);
+    assert!((spüldienst.first_half_rating.unwrap() - 17.0 / 3.0).abs() < 1e-9);
+    assert_eq!(spüldienst.second_half_rating, Some(1.0));
+    assert!((spüldienst.trend().unwrap() + 14.0 / 3.0).abs() < 1e-9);
+    assert_eq!(stats.chores[0].chore, "Mülldienst");
+    assert_eq!(stats.chores[0].trend(), None);
+
+    let stats = db
+        .get_stats(Week::from_db(2847), None, StatsSort::Rating)
+        .await
+        .unwrap();
+    assert_eq!(
+        stats
+            .tenants
+            .iter()
+            .map(|t| t.tenant.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Till", "Olli", "Jonas", "Alex", "Bob"]
+    );
+    assert_eq!(stats.chores[0].chore, "Spüldienst");
+
+    let stats = db
+        .get_stats(
+            Week::from_db(2829),
+            Some(Week::from_db(2852)),
+            StatsSort::Unwilling,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        stats
+            .tenants
+            .iter()
+            .take(3)
+            .map(|t| (t.tenant.as_str(), t.unwilling_weeks))
+            .collect::<Vec<_>>(),
+        vec![("Alex", 1), ("Bob", 1), ("Thomas", 1)]
+    );
+
+    let out = db
+        .print_stats(Week::from_db(2847), None, StatsSort::Done)
+        .await
+        .unwrap();
+    assert_eq!(
+        out.mono_msg(),
+        r#"# Statistics 30/2024 to 32/2024
+## Tenants
++--------+------+----------------------------+--------+-----------+------------------+
+| tenant | done |           chores           | rating | unwilling |    exemptions    |
++--------+------+----------------------------+--------+-----------+------------------+
+| Jonas  |  2   | Mülldienst 1, Spüldienst 1 |  1.00  |     0     |                  |
++--------+------+----------------------------+--------+-----------+------------------+
+|  Alex  |  1   |        Mülldienst 1        |        |     0     |                  |
++--------+------+----------------------------+--------+-----------+------------------+
+|  Bob   |  1   |        Mülldienst 1        |        |     0     |                  |
++--------+------+----------------------------+--------+-----------+------------------+
+|  Olli  |  1   |        Spüldienst 1        |  4.00  |     0     | Bestandsminister |
++--------+------+----------------------------+--------+-----------+------------------+
+|  Till  |  1   |        Spüldienst 1        |  7.33  |     0     | Bestandsminister |
++--------+------+----------------------------+--------+-----------+------------------+
+
+## Chores
++------------+------+--------+------------+-------------+-------+
+|   chore    | done | rating | first_half | second_half | trend |
++------------+------+--------+------------+-------------+-------+
+| Mülldienst |  3   |        |            |             |       |
++------------+------+--------+------------+-------------+-------+
+| Spüldienst |  3   |  4.11  |    5.67    |    1.00     | -4.67 |
++------------+------+--------+------------+-------------+-------+"#
+    );
+}
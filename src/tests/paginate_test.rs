@@ -0,0 +1,44 @@
+use crate::{
+    bot::{Block, ReplyMsg},
+    paginate::paginate_blocks,
+};
+
+/// a message with a heading and a table of some rows
+fn table_msg(heading: &str, rows: usize) -> ReplyMsg {
+    ReplyMsg::from_blocks(vec![
+        Block::heading(1, heading),
+        Block::Blank,
+        Block::Table {
+            header: vec!["tenant".to_string(), "room".to_string()],
+            rows: (0..rows)
+                .map(|i| vec![format!("Tenant {}", i), format!("M4{:02}", i)])
+                .collect(),
+        },
+    ])
+}
+
+#[test]
+fn test_paginate_blocks() {
+    let msg = table_msg("Tenants", 10) + table_msg("Exemptions", 10);
+    // everything fits into one message
+    let pages = paginate_blocks(&msg.blocks, 4000, |b| b.mono().len());
+    assert_eq!(pages, vec![msg.blocks.clone()]);
+    // each table needs its own message, the separating blank lines are dropped
+    let pages = paginate_blocks(&msg.blocks, 600, |b| b.mono().len());
+    assert_eq!(
+        pages
+            .iter()
+            .map(|p| ReplyMsg::from_blocks(p.clone()).mono_msg())
+            .collect::<Vec<_>>(),
+        vec![
+            ReplyMsg::from_blocks(msg.blocks[0..3].to_vec()).mono_msg(),
+            ReplyMsg::from_blocks(msg.blocks[6..9].to_vec()).mono_msg(),
+        ]
+    );
+    // too long tables are split into monospace blocks
+    let pages = paginate_blocks(&msg.blocks, 200, |b| b.mono().len());
+    assert!(pages.len() > 2);
+    assert!(pages
+        .iter()
+        .all(|p| ReplyMsg::from_blocks(p.clone()).mono_msg().len() <= 200));
+}
@@ -1,6 +1,8 @@
 use anyhow::{bail, Result};
 use chrono::{Datelike, IsoWeek, NaiveDate, TimeDelta, Weekday};
 use core::fmt;
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+use std::str::FromStr;
 
 /// an ISO week
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -29,6 +31,12 @@ impl Week {
         self.db_week
     }
 
+    /// Get the Monday this week starts with.
+    pub fn monday(&self) -> NaiveDate {
+        let week: IsoWeek = self.iso_week();
+        NaiveDate::from_isoywd_opt(week.year(), week.week(), Weekday::Mon).unwrap()
+    }
+
     /// Turn into an IsoWeek.
     pub fn iso_week(&self) -> IsoWeek {
         // TODO: make const
@@ -50,6 +58,32 @@ impl From<NaiveDate> for Week {
     }
 }
 
+/// Serialize as calendar week and year, the database representation is an implementation detail.
+impl Serialize for Week {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let week: IsoWeek = self.iso_week();
+        let mut state = serializer.serialize_struct("Week", 2)?;
+        state.serialize_field("week", &week.week())?;
+        state.serialize_field("year", &week.year())?;
+        state.end()
+    }
+}
+
+/// Parse a week as written by Display, i.e., `33/2024`.
+impl FromStr for Week {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((week, year)) = s.trim().split_once('/') else {
+            bail!("the week '{}' isn't formatted like 33/2024", s);
+        };
+        match (week.parse::<u32>(), year.parse::<i32>()) {
+            (Ok(week), Ok(year)) => Week::new(week, year),
+            _ => bail!("the week '{}' isn't formatted like 33/2024", s),
+        }
+    }
+}
+
 impl fmt::Display for Week {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let week: IsoWeek = self.iso_week();